@@ -82,8 +82,8 @@ fn do_example<T: game_system::core::System>(
 
         // after gui update, use whatever events are left
         for e in events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                game_system::core::event::Event::Mouse(mouse_event) => {
+            match e.as_ref().unwrap() {
+                &game_system::core::event::Event::Mouse(mouse_event) => {
                     if !mouse_event.down && mouse_event.changed {
                         *e = None; // intentional redundant
                         println!(
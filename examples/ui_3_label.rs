@@ -88,7 +88,7 @@ fn do_example<T: game_system::core::System>(
 
     gui_loop(DELAY, &mut system, |system, events, dt| {
         for e in events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
+            match e.as_ref().unwrap() {
                 game_system::core::event::Event::Window(window) => {
                     top_label_text.set(format!("{}x{}", window.width, window.height));
                 }
@@ -99,8 +99,8 @@ fn do_example<T: game_system::core::System>(
 
         // after gui update, use whatever events are left
         for e in events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                game_system::core::event::Event::Mouse(mouse_event) => {
+            match e.as_ref().unwrap() {
+                &game_system::core::event::Event::Mouse(mouse_event) => {
                     if !mouse_event.down && mouse_event.changed {
                         *e = None; // intentional redundant
                         println!(
@@ -1,7 +1,18 @@
-use std::{cell::Cell, num::NonZeroU32, path::Path, rc::Rc, time::Duration};
+use std::{
+    cell::{Cell, RefCell},
+    num::NonZeroU32,
+    path::Path,
+    rc::Rc,
+    time::Duration,
+};
 
 use game_system::{
-    core::{color::Color, texture_rect::TextureRect},
+    core::{
+        audio::{Bus, Priority},
+        color::Color,
+        input::{Hotkey, Input, InputMap},
+        texture_rect::TextureRect,
+    },
     ui::widget::{
         checkbox::CheckBox,
         gui_loop,
@@ -29,6 +40,10 @@ fn do_example<T: game_system::core::System>(
         (HEIGHT as u32).try_into().unwrap(),
     );
 
+    // hotkeys are actions, which can be rebound
+    let input_map = RefCell::new(InputMap::default());
+    input_map.borrow_mut().bind("toggle", Input::Key(b'a'));
+
     let mut system = T::new(Some(window_settings), font_file_content, false)?;
 
     let click_sound_path = Path::new(".")
@@ -72,7 +87,7 @@ fn do_example<T: game_system::core::System>(
             h: sixteen,
         },
     );
-    checkbox.hotkey = Some(b'a');
+    checkbox.hotkey = Some(Hotkey::new(&input_map, "toggle"));
 
     let drag_state = Rc::new(Cell::new(DragState::default()));
     let scroll_x = Rc::new(Cell::new(0i32));
@@ -92,7 +107,7 @@ fn do_example<T: game_system::core::System>(
         let r = update_gui(&mut scroller, events, system, dt)?;
 
         if changed.get() {
-            system.sound(&click_sound_path, 0., 0.)?;
+            system.sound(&click_sound_path, Bus::UI, Priority::NORMAL, 0., 0.)?;
             if checked.get() {
                 system.recreate_window(None)?;
             } else {
@@ -102,8 +117,8 @@ fn do_example<T: game_system::core::System>(
 
         // after gui update, use whatever events are left
         for e in events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                game_system::core::event::Event::Mouse(mouse_event) => {
+            match e.as_ref().unwrap() {
+                &game_system::core::event::Event::Mouse(mouse_event) => {
                     if !mouse_event.down && mouse_event.changed {
                         *e = None; // intentional redundant
                         println!(
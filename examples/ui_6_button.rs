@@ -1,7 +1,18 @@
-use std::{cell::Cell, num::NonZeroU32, path::Path, rc::Rc, time::Duration};
+use std::{
+    cell::{Cell, RefCell},
+    num::NonZeroU32,
+    path::Path,
+    rc::Rc,
+    time::Duration,
+};
 
 use game_system::{
-    core::{color::Color, texture_rect::TextureRect},
+    core::{
+        audio::{Bus, Priority},
+        color::Color,
+        input::{Hotkey, Input, InputMap},
+        texture_rect::TextureRect,
+    },
     ui::{
         util::length::{MaxLen, MaxLenFailPolicy, MinLenFailPolicy, PreferredPortion},
         widget::{
@@ -34,6 +45,10 @@ fn do_example<T: game_system::core::System>(
         (HEIGHT as u32).try_into().unwrap(),
     );
 
+    // hotkeys are actions, which can be rebound
+    let input_map = RefCell::new(InputMap::default());
+    input_map.borrow_mut().bind("press", Input::Key(b'a'));
+
     let mut system = T::new(Some(window_settings), font_file_content, false)?;
 
     // a button with a border and background
@@ -60,7 +75,7 @@ fn do_example<T: game_system::core::System>(
         Box::new(pressed),
     );
     button.sizing_inherit_choice = ButtonInheritSizing::Hovered;
-    button.hotkey = Some(b'a');
+    button.hotkey = Some(Hotkey::new(&input_map, "press"));
 
     let background_path = Path::new(".")
         .join("examples")
@@ -153,14 +168,14 @@ fn do_example<T: game_system::core::System>(
 
         if button_release.get() {
             button_release.set(false);
-            system.sound(&button_sound_path, 0., 0.)?;
+            system.sound(&button_sound_path, Bus::UI, Priority::NORMAL, 0., 0.)?;
             println!("button was pressed");
         }
 
         // after gui update, use whatever events are left
         for e in events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                game_system::core::event::Event::Mouse(mouse_event) => {
+            match e.as_ref().unwrap() {
+                &game_system::core::event::Event::Mouse(mouse_event) => {
                     if !mouse_event.down && mouse_event.changed {
                         *e = None; // intentional redundant
                         println!(
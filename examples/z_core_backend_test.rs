@@ -2,6 +2,7 @@
 
 use std::{num::NonZero, path::Path, time::Duration};
 
+use game_system::core::audio::{Bus, Priority, SampleData, Samples};
 use game_system::core::color::Color;
 use game_system::core::event::Event;
 use game_system::core::texture_rect::{TextureDestination, TextureRect, TextureSource};
@@ -109,12 +110,59 @@ fn do_test<T: game_system::core::System>(font_file_content: &'static [u8]) -> Re
 
     // twice of left ear, quite. and once on right ear, loud
     for _ in 0..3 {
-        system.sound(noise_sound, 0.75, 0.95)?;
+        system.sound(noise_sound, Bus::SFX, Priority::NORMAL, 0.75, 0.95)?;
         std::thread::sleep(Duration::from_millis(175));
-        system.sound(noise_sound, 0.25, 0.)?;
+        system.sound(noise_sound, Bus::SFX, Priority::NORMAL, 0.25, 0.)?;
         std::thread::sleep(Duration::from_millis(175));
     }
 
+    // a sound can be stopped early
+    if let Some(handle) = system.sound(noise_sound, Bus::SFX, Priority::HIGH, 0., 0.)? {
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(system.sound_playing(handle));
+        system.stop_sound(handle, Some(Duration::from_millis(100)));
+        std::thread::sleep(Duration::from_millis(175));
+        assert!(!system.sound_playing(handle));
+    }
+
+    // a generated blip, then a generated tone which is streamed
+    system.samples(
+        &b"blip"[..],
+        |_| {
+            let data = (0..4410)
+                .map(|i| (i as f32 * 880. * std::f32::consts::TAU / 44100.).sin() * 0.5)
+                .collect();
+            Ok(Samples {
+                sample_rate: 44100.try_into().unwrap(),
+                channels: 1.try_into().unwrap(),
+                data: SampleData::F32(data),
+            })
+        },
+        Bus::SFX,
+        Priority::NORMAL,
+        0.,
+        0.,
+    )?;
+    std::thread::sleep(Duration::from_millis(175));
+
+    let mut phase = 0f32;
+    let tone = system.stream_sound(
+        move |buffer, format| {
+            for frame in buffer.chunks_mut(format.channels as usize) {
+                phase += 440. / format.sample_rate as f32;
+                frame.fill((phase * std::f32::consts::TAU).sin() * 0.25);
+            }
+        },
+        Bus::SFX,
+        Priority::NORMAL,
+        0.,
+        0.,
+    )?;
+    std::thread::sleep(Duration::from_millis(350));
+    if let Some(tone) = tone {
+        system.stop_sound(tone, Some(Duration::from_millis(100)));
+    }
+
     let mut handle = T::LoopingSoundHandle::new(&noise_sound);
 
     let speed = 1000;
@@ -122,6 +170,7 @@ fn do_test<T: game_system::core::System>(font_file_content: &'static [u8]) -> Re
     for i in 0..speed {
         system.loop_sound(
             &mut handle,
+            Bus::SFX,
             0.25 + (i as f32) / (speed as f32 * 2.),
             0.5,
             None,
@@ -132,6 +181,7 @@ fn do_test<T: game_system::core::System>(font_file_content: &'static [u8]) -> Re
     for i in 0..1000 {
         system.loop_sound(
             &mut handle,
+            Bus::SFX,
             0.75 - (i as f32) / (speed as f32 * 2.),
             0.5,
             None,
@@ -145,7 +195,13 @@ fn do_test<T: game_system::core::System>(font_file_content: &'static [u8]) -> Re
 
     // fade in then out from center
     let mut handle = T::LoopingSoundHandle::new(&noise_sound);
-    system.loop_sound(&mut handle, 0., 0.5, Some(Duration::from_millis(1000)))?;
+    system.loop_sound(
+        &mut handle,
+        Bus::SFX,
+        0.,
+        0.5,
+        Some(Duration::from_millis(1000)),
+    )?;
     std::thread::sleep(Duration::from_millis(1000));
     system.stop_loop_sound(&mut handle, Some(Duration::from_millis(1000)));
     std::thread::sleep(Duration::from_millis(1000));
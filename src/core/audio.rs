@@ -0,0 +1,138 @@
+use std::{
+    num::{NonZeroU16, NonZeroU32},
+    time::Duration,
+};
+
+/// a category of audio, each with its own volume and mute. the volume a sound
+/// plays at is its bus volume multiplied by the master volume
+///
+/// any name can be used - these are only the common ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bus(pub &'static str);
+
+impl Bus {
+    /// applies to every other bus, including music
+    pub const MASTER: Bus = Bus("master");
+    /// used by System::music()
+    pub const MUSIC: Bus = Bus("music");
+    pub const SFX: Bus = Bus("sfx");
+    pub const UI: Bus = Bus("ui");
+    pub const AMBIENT: Bus = Bus("ambient");
+    pub const VOICE: Bus = Bus("voice");
+}
+
+/// when every sound channel is busy, a sound can take the channel of a sound
+/// with a lower priority instead of being dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Priority(pub u8);
+
+impl Priority {
+    pub const LOW: Priority = Priority(64);
+    pub const NORMAL: Priority = Priority(128);
+    pub const HIGH: Priority = Priority(192);
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority::NORMAL
+    }
+}
+
+/// refers to a sound started by System::sound(). stays valid after the sound
+/// ends; it just stops referring to anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SoundHandle {
+    channel: usize,
+    generation: u64,
+}
+
+impl SoundHandle {
+    /// for backends. generation distinguishes sounds played on the same
+    /// channel at different times
+    #[cfg(feature = "rust-sdl2")]
+    pub(crate) fn new(channel: usize, generation: u64) -> Self {
+        Self {
+            channel,
+            generation,
+        }
+    }
+
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleData {
+    I16(Vec<i16>),
+    /// from -1 to 1 inclusively
+    F32(Vec<f32>),
+}
+
+impl SampleData {
+    pub fn len(&self) -> usize {
+        match self {
+            SampleData::I16(v) => v.len(),
+            SampleData::F32(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// a PCM buffer generated in code. see System::samples()
+#[derive(Debug, Clone, PartialEq)]
+pub struct Samples {
+    /// per second, per channel
+    pub sample_rate: NonZeroU32,
+    pub channels: NonZeroU16,
+    /// interleaved by channel. must not be empty, and must be appropriate for
+    /// the number of channels
+    pub data: SampleData,
+}
+
+/// the output format given to a System::stream_sound() generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// where audio is played. see System::set_audio_output()
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum AudioOutput {
+    /// the system's default device
+    #[default]
+    Default,
+    /// by name, from System::audio_devices()
+    Device(String),
+    /// nothing is played. audio calls still check that files exist
+    Disabled,
+}
+
+/// bus volumes to blend to together. see System::apply_snapshot()
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// buses not listed are left as they are
+    pub volumes: Vec<(Bus, f32)>,
+}
+
+/// while anything plays on the trigger bus, the target buses dip in volume.
+/// they recover once it stops
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ducking {
+    pub trigger: Bus,
+    pub targets: Vec<Bus>,
+    /// from 0 to 1. multiplied with the target volumes while ducked
+    pub volume: f32,
+    /// how long to dip
+    pub attack: Duration,
+    /// how long to recover
+    pub release: Duration,
+}
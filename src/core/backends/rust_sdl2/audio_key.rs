@@ -0,0 +1,16 @@
+use std::path::{Path, PathBuf};
+
+/// identifies a decoded sound in the audio cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AudioKey {
+    /// from a file, relative to the audio path base
+    Path(PathBuf),
+    /// from System::samples()
+    User(Vec<u8>),
+}
+
+impl AudioKey {
+    pub fn from_path(path: &Path) -> Self {
+        AudioKey::Path(path.to_path_buf())
+    }
+}
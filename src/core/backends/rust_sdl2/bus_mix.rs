@@ -0,0 +1,238 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use super::bus_volumes::BusVolumes;
+use crate::core::audio::{Bus, Ducking, Snapshot};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fade {
+    from: f32,
+    to: f32,
+    start: Instant,
+    duration: Duration,
+}
+
+/// bus volumes with fades, snapshots and ducking applied over time
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BusMix {
+    volumes: BusVolumes,
+    fades: BTreeMap<Bus, Fade>,
+    snapshots: BTreeMap<String, Snapshot>,
+    /// each with its current multiplier, from 1 to its volume
+    ducking: Vec<(Ducking, f32)>,
+    last_tick: Option<Instant>,
+}
+
+impl BusMix {
+    /// clamped from 0 to 1 inclusively. stops any fade on the bus
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.fades.remove(&bus);
+        self.volumes.set_volume(bus, volume);
+    }
+
+    /// change the volume gradually. progresses with tick()
+    pub fn fade_volume(&mut self, bus: Bus, volume: f32, duration: Duration, now: Instant) {
+        if duration.is_zero() {
+            self.set_volume(bus, volume);
+            return;
+        }
+        self.fades.insert(
+            bus,
+            Fade {
+                from: self.volumes.volume(bus),
+                to: volume.clamp(0., 1.),
+                start: now,
+                duration,
+            },
+        );
+    }
+
+    pub fn add_snapshot(&mut self, name: &str, snapshot: Snapshot) {
+        self.snapshots.insert(name.to_owned(), snapshot);
+    }
+
+    pub fn apply_snapshot(
+        &mut self,
+        name: &str,
+        duration: Duration,
+        now: Instant,
+    ) -> Result<(), String> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .ok_or_else(|| format!("no snapshot named {}", name))?
+            .clone();
+        for (bus, volume) in snapshot.volumes {
+            self.fade_volume(bus, volume, duration, now);
+        }
+        Ok(())
+    }
+
+    pub fn add_ducking(&mut self, ducking: Ducking) {
+        self.ducking.push((ducking, 1.));
+    }
+
+    pub fn clear_ducking(&mut self) {
+        self.ducking.clear();
+    }
+
+    /// progress fades and ducking. playing indicates if anything is playing on
+    /// a bus. returns true if any effective volume might have changed
+    pub fn tick<F: Fn(Bus) -> bool>(&mut self, now: Instant, playing: F) -> bool {
+        let mut changed = !self.fades.is_empty();
+
+        let volumes = &mut self.volumes;
+        self.fades.retain(|bus, fade| {
+            let t = now.saturating_duration_since(fade.start).as_secs_f32()
+                / fade.duration.as_secs_f32();
+            if t >= 1. {
+                volumes.set_volume(*bus, fade.to);
+                false
+            } else {
+                volumes.set_volume(*bus, fade.from + (fade.to - fade.from) * t);
+                true
+            }
+        });
+
+        let dt = match self.last_tick {
+            Some(last_tick) => now.saturating_duration_since(last_tick),
+            None => Duration::ZERO,
+        };
+        self.last_tick = Some(now);
+        for (ducking, level) in self.ducking.iter_mut() {
+            let floor = ducking.volume.clamp(0., 1.);
+            let (target, duration) = if playing(ducking.trigger) {
+                (floor, ducking.attack)
+            } else {
+                (1., ducking.release)
+            };
+            if *level == target {
+                continue;
+            }
+            changed = true;
+            // the full range from 1 to the floor takes the duration
+            let step = if duration.is_zero() {
+                f32::INFINITY
+            } else {
+                (1. - floor) * dt.as_secs_f32() / duration.as_secs_f32()
+            };
+            *level = if target < *level {
+                (*level - step).max(target)
+            } else {
+                (*level + step).min(target)
+            };
+        }
+
+        changed
+    }
+
+    /// true while a fade is in progress or anything is ducked. ducking is
+    /// released once its trigger stops playing, which is only noticed by tick()
+    pub fn animating(&self) -> bool {
+        !self.fades.is_empty() || self.ducking.iter().any(|(_, level)| *level < 1.)
+    }
+
+    /// product of the ducking applied to the bus
+    fn ducked(&self, bus: Bus) -> f32 {
+        self.ducking
+            .iter()
+            .filter(|(ducking, _)| ducking.targets.contains(&bus))
+            .map(|(_, level)| level)
+            .product()
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volumes.volume(bus)
+    }
+
+    pub fn set_muted(&mut self, bus: Bus, muted: bool) {
+        self.volumes.set_muted(bus, muted);
+    }
+
+    pub fn muted(&self, bus: Bus) -> bool {
+        self.volumes.muted(bus)
+    }
+
+    /// the volume that sounds on this bus should actually play at, accounting
+    /// for mute, ducking and the master bus
+    pub fn effective(&self, bus: Bus) -> f32 {
+        let ducked = match bus {
+            Bus::MASTER => self.ducked(Bus::MASTER),
+            _ => self.ducked(Bus::MASTER) * self.ducked(bus),
+        };
+        self.volumes.effective(bus) * ducked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let mut buses = BusMix::default();
+        let start = Instant::now();
+        buses.add_snapshot(
+            "menu",
+            Snapshot {
+                volumes: vec![(Bus::MUSIC, 0.5), (Bus::SFX, 0.)],
+            },
+        );
+        assert!(buses
+            .apply_snapshot("missing", Duration::ZERO, start)
+            .is_err());
+        buses
+            .apply_snapshot("menu", Duration::from_secs(1), start)
+            .unwrap();
+
+        assert!(buses.tick(start + Duration::from_millis(500), |_| false));
+        assert_eq!(buses.volume(Bus::MUSIC), 0.75);
+        assert_eq!(buses.volume(Bus::SFX), 0.5);
+        assert_eq!(buses.volume(Bus::UI), 1.);
+
+        assert!(buses.tick(start + Duration::from_secs(2), |_| false));
+        assert_eq!(buses.volume(Bus::MUSIC), 0.5);
+        assert!(!buses.tick(start + Duration::from_secs(3), |_| false));
+        assert!(!buses.animating());
+
+        // setting the volume directly stops a fade
+        buses.fade_volume(Bus::MUSIC, 1., Duration::from_secs(1), start);
+        buses.set_volume(Bus::MUSIC, 0.25);
+        buses.tick(start + Duration::from_secs(1), |_| false);
+        assert_eq!(buses.volume(Bus::MUSIC), 0.25);
+    }
+
+    #[test]
+    fn test_ducking() {
+        let mut buses = BusMix::default();
+        buses.add_ducking(Ducking {
+            trigger: Bus::VOICE,
+            targets: vec![Bus::MUSIC, Bus::AMBIENT],
+            volume: 0.5,
+            attack: Duration::from_secs(1),
+            release: Duration::from_secs(2),
+        });
+        let start = Instant::now();
+        buses.tick(start, |_| false);
+        assert_eq!(buses.effective(Bus::MUSIC), 1.);
+        assert!(!buses.animating());
+
+        let voice = |bus: Bus| bus == Bus::VOICE;
+        buses.tick(start + Duration::from_millis(500), voice);
+        assert_eq!(buses.effective(Bus::MUSIC), 0.75);
+        assert_eq!(buses.effective(Bus::SFX), 1.);
+        buses.tick(start + Duration::from_secs(2), voice);
+        assert_eq!(buses.effective(Bus::AMBIENT), 0.5);
+        assert!(buses.animating());
+
+        // recovers more slowly
+        buses.tick(start + Duration::from_secs(3), |_| false);
+        assert_eq!(buses.effective(Bus::MUSIC), 0.75);
+        buses.tick(start + Duration::from_secs(5), |_| false);
+        assert_eq!(buses.effective(Bus::MUSIC), 1.);
+        assert!(!buses.tick(start + Duration::from_secs(6), |_| false));
+        assert!(!buses.animating());
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use crate::core::audio::Bus;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BusState {
+    volume: f32,
+    muted: bool,
+}
+
+impl Default for BusState {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            muted: false,
+        }
+    }
+}
+
+/// volume and mute state of each bus. buses not yet set have full volume
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BusVolumes {
+    buses: BTreeMap<Bus, BusState>,
+}
+
+impl BusVolumes {
+    fn get(&self, bus: Bus) -> BusState {
+        self.buses.get(&bus).copied().unwrap_or_default()
+    }
+
+    /// clamped from 0 to 1 inclusively
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.buses.entry(bus).or_default().volume = volume.clamp(0., 1.);
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.get(bus).volume
+    }
+
+    pub fn set_muted(&mut self, bus: Bus, muted: bool) {
+        self.buses.entry(bus).or_default().muted = muted;
+    }
+
+    pub fn muted(&self, bus: Bus) -> bool {
+        self.get(bus).muted
+    }
+
+    /// the volume that sounds on this bus should actually play at, accounting
+    /// for mute and the master bus
+    pub fn effective(&self, bus: Bus) -> f32 {
+        let master = self.get(Bus::MASTER);
+        let state = self.get(bus);
+        if master.muted || state.muted {
+            return 0.;
+        }
+        if bus == Bus::MASTER {
+            return master.volume;
+        }
+        master.volume * state.volume
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective() {
+        let mut buses = BusVolumes::default();
+        assert_eq!(buses.effective(Bus::SFX), 1.);
+        buses.set_volume(Bus::SFX, 0.5);
+        buses.set_volume(Bus::MASTER, 0.5);
+        assert_eq!(buses.effective(Bus::SFX), 0.25);
+        assert_eq!(buses.effective(Bus::UI), 0.5);
+        assert_eq!(buses.effective(Bus::MASTER), 0.5);
+
+        buses.set_muted(Bus::SFX, true);
+        assert_eq!(buses.effective(Bus::SFX), 0.);
+        assert_eq!(buses.volume(Bus::SFX), 0.5);
+        buses.set_muted(Bus::SFX, false);
+        buses.set_muted(Bus::MASTER, true);
+        assert_eq!(buses.effective(Bus::SFX), 0.);
+        assert_eq!(buses.effective(Bus::UI), 0.);
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut buses = BusVolumes::default();
+        buses.set_volume(Bus::VOICE, 2.);
+        assert_eq!(buses.volume(Bus::VOICE), 1.);
+        buses.set_volume(Bus::VOICE, -1.);
+        assert_eq!(buses.volume(Bus::VOICE), 0.);
+    }
+}
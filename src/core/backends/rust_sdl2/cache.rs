@@ -0,0 +1,239 @@
+use std::{borrow::Borrow, hash::Hash, num::NonZeroUsize};
+
+use lru::LruCache;
+
+use crate::core::cache::ResourceCacheStats;
+
+/// lru cache which also tracks the approximate memory used by each entry, and
+/// keeps counters for observability
+pub(crate) struct ResourceCache<K: Hash + Eq, V> {
+    lru: LruCache<K, (V, usize)>,
+    byte_budget: Option<usize>,
+    bytes_resident: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K: Hash + Eq, V> ResourceCache<K, V> {
+    pub fn new(cap: NonZeroUsize, byte_budget: Option<usize>) -> Self {
+        Self {
+            lru: LruCache::new(cap),
+            byte_budget,
+            bytes_resident: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn cap(&self) -> NonZeroUsize {
+        self.lru.cap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lru.len()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.lru.iter().map(|(k, _)| k)
+    }
+
+    /// doesn't count as a hit or miss
+    pub fn contains<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lru.contains(k)
+    }
+
+    /// mark as most recently used, without counting as a hit or miss. returns
+    /// false if not present
+    pub fn promote<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.lru.contains(k) {
+            return false;
+        }
+        self.lru.promote(k);
+        true
+    }
+
+    /// a miss is counted if not present
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.lru.get(k) {
+            Some((v, _)) => {
+                self.hits += 1;
+                Some(v)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// f returns the value and its size in bytes
+    pub fn try_get_or_insert_ref<'a, Q, F, E>(&'a mut self, k: &Q, f: F) -> Result<&'a V, E>
+    where
+        K: Borrow<Q> + Clone,
+        Q: Hash + Eq + ?Sized + ToOwned<Owned = K>,
+        F: FnOnce() -> Result<(V, usize), E>,
+    {
+        if self.lru.contains(k) {
+            self.hits += 1;
+            return Ok(&self.lru.get(k).unwrap().0);
+        }
+        self.misses += 1;
+        let (v, bytes) = f()?;
+        Ok(self.insert(k.to_owned(), v, bytes))
+    }
+
+    /// doesn't count as a hit or miss
+    pub fn insert(&mut self, k: K, v: V, bytes: usize) -> &V
+    where
+        K: Clone,
+    {
+        self.bytes_resident += bytes;
+        if let Some((old_k, (_, old_bytes))) = self.lru.push(k.clone(), (v, bytes)) {
+            self.bytes_resident -= old_bytes;
+            if !self.lru.contains(&old_k) {
+                // wasn't a replacement of the same key
+                self.evictions += 1;
+            }
+        }
+        self.enforce_byte_budget();
+        &self.lru.peek(&k).unwrap().0
+    }
+
+    /// explicit removal. not counted as an eviction
+    pub fn pop<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (v, bytes) = self.lru.pop(k)?;
+        self.bytes_resident -= bytes;
+        Some(v)
+    }
+
+    /// explicit removal. not counted as an eviction
+    pub fn clear(&mut self) {
+        self.lru.clear();
+        self.bytes_resident = 0;
+    }
+
+    pub fn resize(&mut self, cap: NonZeroUsize) {
+        while self.lru.len() > cap.get() {
+            self.pop_lru_evict();
+        }
+        self.lru.resize(cap);
+    }
+
+    pub fn set_byte_budget(&mut self, byte_budget: Option<usize>) {
+        self.byte_budget = byte_budget;
+        self.enforce_byte_budget();
+    }
+
+    pub fn stats(&self) -> ResourceCacheStats {
+        ResourceCacheStats {
+            capacity: self.lru.cap().get(),
+            len: self.lru.len(),
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            bytes_resident: self.bytes_resident,
+        }
+    }
+
+    fn pop_lru_evict(&mut self) {
+        if let Some((_, (_, bytes))) = self.lru.pop_lru() {
+            self.bytes_resident -= bytes;
+            self.evictions += 1;
+        }
+    }
+
+    /// the most recent entry is always kept, even if it alone is over budget
+    fn enforce_byte_budget(&mut self) {
+        let budget = match self.byte_budget {
+            Some(v) => v,
+            None => return,
+        };
+        while self.bytes_resident > budget && self.lru.len() > 1 {
+            self.pop_lru_evict();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let mut cache: ResourceCache<String, u32> = ResourceCache::new(2.try_into().unwrap(), None);
+        let v = cache.try_get_or_insert_ref("a", || Ok::<_, ()>((1, 10)));
+        assert_eq!(v, Ok(&1));
+        let v = cache.try_get_or_insert_ref("a", || Ok::<_, ()>((2, 10)));
+        assert_eq!(v, Ok(&1));
+        cache.insert("b".to_owned(), 3, 20);
+        cache.insert("c".to_owned(), 4, 30);
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.bytes_resident, 50);
+
+        // replacing the same key isn't an eviction
+        cache.insert("c".to_owned(), 5, 5);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().bytes_resident, 25);
+
+        assert_eq!(cache.pop("b"), Some(3));
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().bytes_resident, 5);
+    }
+
+    #[test]
+    fn test_byte_budget() {
+        let mut cache: ResourceCache<u32, ()> =
+            ResourceCache::new(10.try_into().unwrap(), Some(100));
+        cache.insert(0, (), 40);
+        cache.insert(1, (), 40);
+        cache.insert(2, (), 40);
+        assert!(!cache.contains(&0));
+        assert_eq!(cache.stats().bytes_resident, 80);
+        assert_eq!(cache.stats().evictions, 1);
+
+        // single large entry kept
+        cache.insert(3, (), 1000);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&3));
+
+        cache.set_byte_budget(None);
+        cache.insert(4, (), 1000);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut cache: ResourceCache<u32, ()> = ResourceCache::new(4.try_into().unwrap(), None);
+        for i in 0..4 {
+            cache.insert(i, (), 1);
+        }
+        assert!(cache.promote(&0));
+        cache.resize(2.try_into().unwrap());
+        assert!(cache.contains(&0));
+        assert!(cache.contains(&3));
+        assert_eq!(cache.stats().evictions, 2);
+        assert_eq!(cache.stats().bytes_resident, 2);
+    }
+}
@@ -0,0 +1,187 @@
+use std::{ffi::c_int, path::Path, ptr::NonNull};
+
+use sdl2::{get_error, mixer::Channel, rwops::RWops, sys::mixer::Mix_Chunk};
+
+use crate::core::audio::{SampleData, Samples};
+
+/// 16 bit PCM wav file content
+fn wav_bytes(samples: &Samples) -> Vec<u8> {
+    let channels = samples.channels.get();
+    let sample_rate = samples.sample_rate.get();
+    let block_align = channels as u32 * 2;
+    let data_len = samples.data.len() as u32 * 2;
+
+    let mut ret = Vec::with_capacity(44 + data_len as usize);
+    ret.extend_from_slice(b"RIFF");
+    ret.extend_from_slice(&(36 + data_len).to_le_bytes());
+    ret.extend_from_slice(b"WAVEfmt ");
+    ret.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    ret.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    ret.extend_from_slice(&channels.to_le_bytes());
+    ret.extend_from_slice(&sample_rate.to_le_bytes());
+    ret.extend_from_slice(&(sample_rate * block_align).to_le_bytes());
+    ret.extend_from_slice(&(block_align as u16).to_le_bytes());
+    ret.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    ret.extend_from_slice(b"data");
+    ret.extend_from_slice(&data_len.to_le_bytes());
+    match &samples.data {
+        SampleData::I16(data) => {
+            for sample in data {
+                ret.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        SampleData::F32(data) => {
+            for sample in data {
+                ret.extend_from_slice(&f32_to_i16(*sample).to_le_bytes());
+            }
+        }
+    }
+    ret
+}
+
+pub(crate) fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1., 1.) * i16::MAX as f32) as i16
+}
+
+/// a decoded sound. used instead of sdl2::mixer::Chunk since the decoded
+/// buffer needs to be accessed (e.g. for its size)
+pub(crate) struct AudioChunk {
+    raw: NonNull<Mix_Chunk>,
+}
+
+// the chunk isn't shared with anything else, so it can be moved to the thread
+// that plays it (e.g. after decoding on a worker thread)
+unsafe impl Send for AudioChunk {}
+
+impl Drop for AudioChunk {
+    fn drop(&mut self) {
+        unsafe { sdl2::sys::mixer::Mix_FreeChunk(self.raw.as_ptr()) }
+    }
+}
+
+impl AudioChunk {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        Self::from_rwops(RWops::from_file(path, "rb")?)
+    }
+
+    /// decode the content of an audio file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_rwops(RWops::from_bytes(bytes)?)
+    }
+
+    /// converted to the output format
+    pub fn from_samples(samples: &Samples) -> Result<Self, String> {
+        if samples.data.is_empty() {
+            return Err("samples were empty".to_owned());
+        }
+        if samples.data.len() % samples.channels.get() as usize != 0 {
+            return Err("samples have incorrect number of channels".to_owned());
+        }
+        Self::from_bytes(&wav_bytes(samples))
+    }
+
+    /// a copy played at a different speed (and pitch), via linear
+    /// interpolation
+    pub fn resampled(&self, speed: f32) -> Result<Self, String> {
+        if speed.is_nan() || speed <= 0. {
+            return Err("speed must be positive".to_owned());
+        }
+        let (frequency, _, channels) = sdl2::mixer::query_spec()?;
+        let channels = channels as usize;
+        // decoded chunks are in the output format, which is signed 16 bit
+        let input = unsafe {
+            let chunk = self.raw.as_ref();
+            std::slice::from_raw_parts(chunk.abuf as *const i16, chunk.alen as usize / 2)
+        };
+        let in_frames = input.len() / channels;
+        if in_frames == 0 {
+            return Err("sound was empty".to_owned());
+        }
+
+        let out_frames = ((in_frames as f64 / speed as f64) as usize).max(1);
+        let mut data = Vec::with_capacity(out_frames * channels);
+        for frame in 0..out_frames {
+            let position = frame as f64 * speed as f64;
+            let before = (position as usize).min(in_frames - 1);
+            let after = (before + 1).min(in_frames - 1);
+            let t = (position - before as f64) as f32;
+            for channel in 0..channels {
+                let a = input[before * channels + channel] as f32;
+                let b = input[after * channels + channel] as f32;
+                data.push((a + (b - a) * t).round() as i16);
+            }
+        }
+
+        Self::from_samples(&Samples {
+            sample_rate: (frequency as u32).try_into().map_err(|_| "no frequency")?,
+            channels: (channels as u16).try_into().map_err(|_| "no channels")?,
+            data: SampleData::I16(data),
+        })
+    }
+
+    fn from_rwops(rwops: RWops) -> Result<Self, String> {
+        let raw = unsafe { sdl2::sys::mixer::Mix_LoadWAV_RW(rwops.raw(), 0) };
+        match NonNull::new(raw) {
+            Some(raw) => Ok(Self { raw }),
+            None => Err(get_error()),
+        }
+    }
+
+    /// size of the decoded buffer
+    pub fn byte_len(&self) -> usize {
+        unsafe { self.raw.as_ref().alen as usize }
+    }
+
+    /// a chunk which plays this chunk's buffer starting from byte_offset
+    ///
+    /// safety: the returned chunk references this chunk's buffer without
+    /// copying it. it must be dropped first, and must not be playing after
+    /// this chunk is dropped
+    pub unsafe fn slice(&self, byte_offset: usize) -> Result<Self, String> {
+        let chunk = self.raw.as_ref();
+        let byte_offset = byte_offset.min(chunk.alen as usize);
+        let raw = sdl2::sys::mixer::Mix_QuickLoad_RAW(
+            chunk.abuf.add(byte_offset),
+            chunk.alen - byte_offset as u32,
+        );
+        match NonNull::new(raw) {
+            Some(raw) => Ok(Self { raw }),
+            None => Err(get_error()),
+        }
+    }
+
+    /// loops is the number of additional times to play. -1 is forever
+    pub fn play(&self, channel: Channel, loops: i32) -> Result<(), String> {
+        let ret = unsafe {
+            sdl2::sys::mixer::Mix_PlayChannelTimed(
+                channel.0 as c_int,
+                self.raw.as_ptr(),
+                loops as c_int,
+                -1,
+            )
+        };
+        if ret == -1 {
+            Err(get_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// see play()
+    pub fn fade_in(&self, channel: Channel, loops: i32, ms: i32) -> Result<(), String> {
+        let ret = unsafe {
+            sdl2::sys::mixer::Mix_FadeInChannelTimed(
+                channel.0 as c_int,
+                self.raw.as_ptr(),
+                loops as c_int,
+                ms as c_int,
+                -1,
+            )
+        };
+        if ret == -1 {
+            Err(get_error())
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::GameControllerSubsystem;
+
+use crate::core::event::{ControllerAxis, ControllerButton};
+
+/// controllers must stay open to send events
+pub(super) struct Controllers {
+    /// None if the subsystem is unavailable
+    subsystem: Option<GameControllerSubsystem>,
+    open: Vec<GameController>,
+}
+
+impl Controllers {
+    pub fn new(subsystem: Option<GameControllerSubsystem>) -> Self {
+        Self {
+            subsystem,
+            open: Vec::new(),
+        }
+    }
+
+    /// sent for each controller already connected at startup too
+    pub fn added(&mut self, joystick_index: u32) {
+        let subsystem = match self.subsystem.as_ref() {
+            Some(v) => v,
+            None => return,
+        };
+        // not worth failing over. the controller just won't send events
+        if let Ok(controller) = subsystem.open(joystick_index) {
+            self.open.push(controller);
+        }
+    }
+
+    pub fn removed(&mut self, instance_id: u32) {
+        self.open.retain(|c| c.instance_id() != instance_id);
+    }
+}
+
+pub(super) fn translate_button(button: Button) -> ControllerButton {
+    match button {
+        Button::A => ControllerButton::A,
+        Button::B => ControllerButton::B,
+        Button::X => ControllerButton::X,
+        Button::Y => ControllerButton::Y,
+        Button::Back => ControllerButton::Back,
+        Button::Guide => ControllerButton::Guide,
+        Button::Start => ControllerButton::Start,
+        Button::LeftStick => ControllerButton::LeftStick,
+        Button::RightStick => ControllerButton::RightStick,
+        Button::LeftShoulder => ControllerButton::LeftShoulder,
+        Button::RightShoulder => ControllerButton::RightShoulder,
+        Button::DPadUp => ControllerButton::DPadUp,
+        Button::DPadDown => ControllerButton::DPadDown,
+        Button::DPadLeft => ControllerButton::DPadLeft,
+        Button::DPadRight => ControllerButton::DPadRight,
+        _ => ControllerButton::Other,
+    }
+}
+
+pub(super) fn translate_axis(axis: Axis) -> ControllerAxis {
+    match axis {
+        Axis::LeftX => ControllerAxis::LeftX,
+        Axis::LeftY => ControllerAxis::LeftY,
+        Axis::RightX => ControllerAxis::RightX,
+        Axis::RightY => ControllerAxis::RightY,
+        Axis::TriggerLeft => ControllerAxis::TriggerLeft,
+        Axis::TriggerRight => ControllerAxis::TriggerRight,
+    }
+}
+
+/// from -1 to 1
+pub(super) fn axis_value(value: i16) -> f32 {
+    (value as f32 / i16::MAX as f32).max(-1.)
+}
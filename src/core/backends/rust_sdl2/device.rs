@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use sdl2::mixer::{AllowChangeFlag, Channel};
+
+use super::{music_finished_hook, RustSDL2System, RustSDL2SystemOtherMembers, MUSIC_CONTEXT};
+use crate::core::{audio::AudioOutput, Event};
+
+/// the output format. changes aren't allowed when opening a device, so that
+/// decoded chunks stay valid when switching devices
+pub(super) const FREQUENCY: i32 = 44_100;
+pub(super) const CHANNELS: i32 = sdl2::mixer::DEFAULT_CHANNELS;
+/// bytes per frame, in the output format (signed 16 bit)
+pub(super) const FRAME_BYTES: u32 = 2 * CHANNELS as u32;
+
+/// open the mixer on the output. returns false if audio is disabled
+pub(super) fn open_mixer(output: &AudioOutput, channels: i32) -> Result<bool, String> {
+    let device = match output {
+        AudioOutput::Disabled => return Ok(false),
+        AudioOutput::Default => None,
+        AudioOutput::Device(name) => Some(name.as_str()),
+    };
+    sdl2::mixer::open_audio_device(
+        FREQUENCY,
+        sdl2::mixer::AUDIO_S16LSB,
+        CHANNELS,
+        1_024,
+        device,
+        AllowChangeFlag::empty(),
+    )?;
+    sdl2::mixer::Music::hook_finished(music_finished_hook);
+    sdl2::mixer::allocate_channels(channels);
+    Ok(true)
+}
+
+impl RustSDL2SystemOtherMembers {
+    /// while audio is unavailable, calls still check that files exist
+    pub(super) fn validate_audio_path(&self, path: &Path) -> Result<(), String> {
+        let full_path = self.audio_path_base.join(path);
+        std::fs::metadata(&full_path)
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", full_path.display(), e))
+    }
+}
+
+impl RustSDL2System {
+    /// stop everything playing and close the mixer
+    fn close_mixer(&mut self) {
+        if !self.s.audio_open {
+            return;
+        }
+        {
+            let mut ctx = MUSIC_CONTEXT.lock().unwrap();
+            ctx.next_music = None;
+            ctx.current_music = None;
+        }
+        Channel::all().halt();
+        self.s.decks.cancel_timer();
+        self.s.decks.halt();
+        for state in self.s.channels.iter_mut() {
+            state.chunk = None;
+        }
+        sdl2::mixer::close_audio();
+        self.s.audio_open = false;
+    }
+
+    /// reopen the mixer on the requested output, falling back to the default
+    /// device. the playlist track restarts where it was
+    pub(super) fn reopen_audio(&mut self) -> Result<(), String> {
+        let track_position = self.s.decks.position().map(|(elapsed, _)| elapsed);
+        let paused = self.s.decks.paused();
+        self.close_mixer();
+
+        let channels = super::DECK_CHANNELS + self.s.channels.len() as i32;
+        let mut result = open_mixer(&self.s.audio_output, channels);
+        if result.is_err() && matches!(self.s.audio_output, AudioOutput::Device(_)) {
+            result = open_mixer(&AudioOutput::Default, channels);
+        }
+        self.s.audio_error = result.as_ref().err().cloned();
+        self.s.audio_open = result?;
+        if !self.s.audio_open {
+            return Ok(());
+        }
+
+        self.apply_bus_volumes(crate::core::audio::Bus::MASTER);
+        if let Some(position) = track_position {
+            self.play_current_track()?;
+            self.s.decks.seek(position)?;
+            if paused {
+                self.s.decks.pause();
+            }
+        }
+        Ok(())
+    }
+
+    /// the device was unplugged, or one was plugged in
+    pub(super) fn audio_device_event(&mut self, removed: bool) -> Option<Event> {
+        if removed {
+            // only sent for opened devices. the mixer's is the only one
+            if !self.s.audio_open {
+                return None;
+            }
+        } else if self.s.audio_open || self.s.audio_output == AudioOutput::Disabled {
+            return None; // nothing to recover
+        }
+        // on failure, audio stays unavailable until another device is added
+        let _ = self.reopen_audio();
+        Some(Event::AudioDeviceChanged)
+    }
+}
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::texture_key::TextureKey;
+
+/// how often the modification times of loaded files are checked
+pub(crate) const ASSET_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// a resource which was created from a file on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WatchedAsset {
+    Texture(TextureKey),
+    Audio(PathBuf),
+    /// music isn't cached - the new version is used the next time
+    /// System::music() is called
+    Music,
+}
+
+/// polls the modification time of every file loaded by the system. this is
+/// meant for development only; artists iterating on textures and sounds don't
+/// need to restart the game to see their changes
+///
+/// a native file watcher would be more responsive, but polling doesn't add a
+/// dep and behaves the same on every platform
+#[derive(Default)]
+pub(crate) struct AssetWatcher {
+    /// full path (base path included) -> last seen modification time, and the
+    /// resources which were loaded from that file
+    watched: HashMap<PathBuf, (Option<SystemTime>, Vec<WatchedAsset>)>,
+    last_poll: Option<Instant>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl AssetWatcher {
+    /// call after a resource is successfully loaded from a file
+    pub fn watch(&mut self, full_path: &Path, asset: WatchedAsset) {
+        let entry = self
+            .watched
+            .entry(full_path.to_path_buf())
+            .or_insert_with(|| (modified_time(full_path), Vec::new()));
+        if !entry.1.contains(&asset) {
+            entry.1.push(asset);
+        }
+    }
+
+    /// returns the resources whose file changed since the previous poll. they
+    /// are no longer watched - they'll be watched again once they're reloaded
+    ///
+    /// does nothing if the previous poll was too recent
+    pub fn poll(&mut self) -> Vec<WatchedAsset> {
+        let now = Instant::now();
+        if let Some(last_poll) = self.last_poll {
+            if now - last_poll < ASSET_POLL_INTERVAL {
+                return Vec::new();
+            }
+        }
+        self.last_poll = Some(now);
+
+        let mut ret = Vec::new();
+        for (path, (time, assets)) in self.watched.iter_mut() {
+            let new_time = modified_time(path);
+            if new_time != *time {
+                *time = new_time;
+                ret.append(assets);
+            }
+        }
+        self.watched.retain(|_, (_, assets)| !assets.is_empty());
+        ret
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::JoinHandle,
+};
+
+use sdl2::{image::LoadSurface, pixels::PixelFormatEnum, surface::Surface};
+
+use super::{chunk::AudioChunk, playlist::decode_track};
+use crate::core::cache::LoadProgress;
+
+/// identifies a resource by its path relative to the base path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum LoadRequest {
+    Image(PathBuf),
+    Sound(PathBuf),
+    /// a playlist track, decoded ahead of time. not counted in the progress
+    Track(PathBuf),
+}
+
+impl LoadRequest {
+    /// included in the progress and errors
+    fn counted(&self) -> bool {
+        !matches!(self, LoadRequest::Track(_))
+    }
+}
+
+/// the part of the loading done on the worker thread. anything which needs the
+/// renderer is done afterward on the main thread
+pub(crate) enum Loaded {
+    /// tightly packed RGBA32
+    Image {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    /// decoding only needs the opened audio device's format, so sounds and
+    /// tracks are decoded here and playback doesn't stutter
+    Sound(AudioChunk),
+    Track(AudioChunk),
+}
+
+type Job = (LoadRequest, PathBuf);
+type JobResult = (LoadRequest, Result<Loaded, String>);
+
+/// loads files on a worker thread. the thread is started the first time
+/// something is queued
+#[derive(Default)]
+pub(crate) struct Loader {
+    /// both dropped before thread is joined
+    sender: Option<Sender<Job>>,
+    receiver: Option<Receiver<JobResult>>,
+    thread: Option<JoinHandle<()>>,
+
+    pending: HashSet<LoadRequest>,
+    /// kept until retrieved by take_error
+    failed: HashMap<LoadRequest, String>,
+    progress: LoadProgress,
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        // worker stops after its current job, once it can't send the result
+        self.sender = None;
+        self.receiver = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn load_image(full_path: &Path) -> Result<Loaded, String> {
+    let surface = Surface::from_file(full_path)?.convert_format(PixelFormatEnum::RGBA32)?;
+    let width = surface.width();
+    let height = surface.height();
+    let pitch = surface.pitch() as usize;
+    let row_len = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    surface.with_lock(|buffer| {
+        for row in 0..height as usize {
+            let start = row * pitch;
+            pixels.extend_from_slice(&buffer[start..start + row_len]);
+        }
+    });
+    Ok(Loaded::Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn worker(jobs: Receiver<Job>, results: Sender<JobResult>) {
+    for (request, full_path) in jobs {
+        let result = match request {
+            LoadRequest::Image(_) => load_image(&full_path),
+            LoadRequest::Sound(_) => AudioChunk::from_file(&full_path).map(Loaded::Sound),
+            LoadRequest::Track(_) => decode_track(&full_path).map(Loaded::Track),
+        };
+        if results.send((request, result)).is_err() {
+            return;
+        }
+    }
+}
+
+impl Loader {
+    pub fn progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    /// returns the error if the request previously failed
+    pub fn take_error(&mut self, request: &LoadRequest) -> Option<String> {
+        self.failed.remove(request)
+    }
+
+    fn begin_request(&mut self) {
+        if self.progress.done() {
+            // everything from before is done. start a new batch
+            self.progress = Default::default();
+        }
+        self.progress.queued += 1;
+    }
+
+    /// the resource is already in the cache, but the caller still wants it
+    /// counted in the progress
+    pub fn already_loaded(&mut self) {
+        self.begin_request();
+        self.progress.loaded += 1;
+    }
+
+    /// does nothing if the request is already pending
+    pub fn queue(&mut self, request: LoadRequest, full_path: PathBuf) -> Result<(), String> {
+        if self.pending.contains(&request) {
+            return Ok(());
+        }
+
+        if self.sender.is_none() {
+            let (job_sender, job_receiver) = channel();
+            let (result_sender, result_receiver) = channel();
+            let thread = std::thread::Builder::new()
+                .name("asset loader".to_owned())
+                .spawn(move || worker(job_receiver, result_sender))
+                .map_err(|e| e.to_string())?;
+            self.sender = Some(job_sender);
+            self.receiver = Some(result_receiver);
+            self.thread = Some(thread);
+        }
+
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send((request.clone(), full_path))
+            .map_err(|e| e.to_string())?;
+        self.failed.remove(&request);
+        if request.counted() {
+            self.begin_request();
+        }
+        self.pending.insert(request);
+        Ok(())
+    }
+
+    /// non blocking. returns a result from the worker thread. the caller must
+    /// then call finish()
+    pub fn recv(&mut self) -> Option<JobResult> {
+        self.receiver.as_ref()?.try_recv().ok()
+    }
+
+    /// the main thread is done with this request
+    pub fn finish(&mut self, request: LoadRequest, result: Result<(), String>) {
+        if !self.pending.remove(&request) || !request.counted() {
+            return;
+        }
+        match result {
+            Ok(()) => self.progress.loaded += 1,
+            Err(e) => {
+                self.progress.failed += 1;
+                self.failed.insert(request, e);
+            }
+        }
+    }
+}
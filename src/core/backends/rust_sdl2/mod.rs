@@ -1,6 +1,19 @@
+mod audio_key;
+mod bus_mix;
+mod bus_volumes;
+mod cache;
+mod chunk;
+mod controller;
+mod device;
 mod font;
+#[cfg(feature = "dev")]
+mod hot_reload;
+mod loader;
 mod math;
+mod playlist;
+mod stream;
 mod texture_key;
+mod user_event;
 
 use std::{
     collections::BTreeMap,
@@ -12,14 +25,22 @@ use std::{
     time::{Duration, Instant},
 };
 
+use audio_key::AudioKey;
+use bus_mix::BusMix;
+use cache::ResourceCache;
+use chunk::AudioChunk;
+use controller::Controllers;
 use font::Font;
-use lru::LruCache;
+#[cfg(feature = "dev")]
+use hot_reload::{AssetWatcher, WatchedAsset, ASSET_POLL_INTERVAL};
+use loader::{LoadRequest, Loaded, Loader};
 use math::capped_next_power_of_two;
+use playlist::Decks;
 use sdl2::{
     get_error,
     image::{LoadTexture, Sdl2ImageContext},
-    keyboard::Mod,
-    mixer::{Channel, Chunk, Music, Sdl2MixerContext},
+    keyboard::{Keycode, Mod},
+    mixer::{Channel, Music, Sdl2MixerContext},
     mouse::MouseButton,
     pixels,
     rect::Rect,
@@ -28,17 +49,28 @@ use sdl2::{
     sys::mixer::MIX_MAX_VOLUME,
     ttf::Sdl2TtfContext,
     video::{Window, WindowContext},
-    AudioSubsystem, EventPump, Sdl, VideoSubsystem,
+    AudioSubsystem, EventPump, Sdl, TimerSubsystem, VideoSubsystem,
 };
-use texture_key::TextureKey;
+use texture_key::{TextureKey, TextureKeyKind};
 
 use crate::core::{
+    audio::{AudioOutput, Bus, Ducking, Priority, Samples, Snapshot, SoundHandle, StreamFormat},
+    cache::{CacheConfig, CacheStats, LoadProgress},
     color::{Color, Surface},
-    event::{ascii_more_to_upper, MouseWheelEvent},
+    event::{
+        ascii_more_to_upper, ControllerAxisEvent, ControllerButtonEvent, EventSender, KeyMods,
+        MouseWheelEvent, NavKey, NavKeyEvent,
+    },
+    playlist::Playlist,
+    sound_set::Variation,
     texture_rect::{TextureDestinationF, TextureRect, TextureSource, TextureSourceF},
     BytesLike, Event, NonEmptyStr, PathLike, System, TextureDestination,
 };
 
+/// max time spent each frame finishing background loads (texture upload,
+/// sound decoding). at least one is always finished per frame
+const LOAD_FRAME_BUDGET: Duration = Duration::from_millis(4);
+
 /// there's only one sdl mixer music callback globally which accepts a function
 /// pointer. so there has to be a global state :(
 struct MusicContext {
@@ -71,8 +103,65 @@ fn music_finished_hook() {
     }
 }
 
+/// mixer channels reserved for the playlist. sound channels come after
+const DECK_CHANNELS: i32 = 2;
+
+/// the mixer channel of a sound channel
+fn sound_channel(index: usize) -> Channel {
+    Channel(DECK_CHANNELS + index as i32)
+}
+
+/// from 0 to 1 inclusively, to the mixer's range
+fn mix_volume(volume: f32) -> i32 {
+    (volume * MIX_MAX_VOLUME as f32).round() as i32
+}
+
+/// what is playing on a mixer channel
+struct ChannelState {
+    /// if a chunk is pushed out of the audio cache (causing the chunk to drop)
+    /// then it will stop playing even if it shouldn't stop. by keeping a ref on
+    /// the channel it is played on, this prevents this
+    ///
+    /// minor side-effect: up to one copy of the chunk per channel could exist,
+    /// since this isn't consulted when the audio_cache is looked up. this worst
+    /// case is fine
+    chunk: Option<Rc<AudioChunk>>,
+    bus: Bus,
+    /// None for looping sounds, which can't be stolen
+    priority: Option<Priority>,
+    /// from 0 to 1, from the distance. the bus volume is applied separately
+    gain: f32,
+    /// from 0 to 1. multiplied with the bus volume
+    volume: f32,
+    started: Instant,
+    /// matched against handles to this channel
+    generation: u64,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            chunk: None,
+            bus: Bus::SFX,
+            priority: None,
+            gain: 1.,
+            volume: 1.,
+            started: Instant::now(),
+            generation: 0,
+        }
+    }
+}
+
 struct TextureWrapper(pub sdl2::render::Texture);
 
+impl TextureWrapper {
+    /// approximate size of the texture in video memory
+    fn byte_len(&self) -> usize {
+        let query = self.0.query();
+        query.width as usize * query.height as usize * std::mem::size_of::<Color>()
+    }
+}
+
 impl Drop for TextureWrapper {
     // safe destroy(), since these Textures will be dropped before the parent
     // canvas + creator is dropped
@@ -83,7 +172,7 @@ impl Drop for TextureWrapper {
 
 pub struct RustSDL2System {
     /// used for both image textures and text textures
-    texture_cache: LruCache<TextureKey, TextureWrapper>,
+    texture_cache: ResourceCache<TextureKey, TextureWrapper>,
 
     /// dropped last  
     /// members grouped together. consider it the same struct; useful for borrow
@@ -92,22 +181,36 @@ pub struct RustSDL2System {
 }
 
 struct RustSDL2SystemOtherMembers {
-    audio_cache: LruCache<PathBuf, Rc<Chunk>>,
+    audio_cache: ResourceCache<AudioKey, Rc<AudioChunk>>,
 
-    /// if a chunk is pushed out of the audio cache (causing the chunk to drop)
-    /// then it will stop playing even if it shouldn't stop. by keeping a ref on
-    /// the channel it is played on, this prevents this
-    ///
-    /// minor side-effect: up to MIX_CHANNELS (8) different copies of the chunk
-    /// could exist, since channel_refs isn't consulted when the audio_cache is
-    /// looked up. this worst case is fine
-    channel_refs: [Option<Rc<Chunk>>; sdl2::sys::mixer::MIX_CHANNELS as usize],
+    cache_config: CacheConfig,
+
+    /// indexed by sound channel. see sound_channel()
+    channels: Vec<ChannelState>,
+    /// for the next sound started on any channel
+    next_generation: u64,
+
+    buses: BusMix,
+
+    /// playlist playback, on the mixer channels before the sound channels
+    decks: Decks,
+
+    /// false if no output device could be opened, or audio is disabled. calls
+    /// which play audio do nothing instead
+    audio_open: bool,
+    /// why audio couldn't be opened
+    audio_error: Option<String>,
+    audio_output: AudioOutput,
 
     /// associates a point size with a loaded font. discretized (there can only
     /// be a handful of elements)
     loaded_fonts: BTreeMap<NonZeroU16, Font>,
 
     event_pump: EventPump,
+    /// for Event::User, from an EventSender
+    user_event_type: u32,
+    /// closed before sdl is quit
+    controllers: Controllers,
 
     /// canvas and creator dropped after all textures (unsafe-textures feature)
     creator: TextureCreator<WindowContext>,
@@ -116,18 +219,129 @@ struct RustSDL2SystemOtherMembers {
     texture_path_base: PathBuf,
     audio_path_base: PathBuf,
 
+    /// files loaded from disk are checked for changes
+    #[cfg(feature = "dev")]
+    asset_watcher: AssetWatcher,
+
+    /// background loading. worker thread joined before sdl is quit
+    loader: Loader,
+
     // dropped in member order stated
     ttf_context: Sdl2TtfContext,
     _image: Sdl2ImageContext,
     _mixer: Sdl2MixerContext,
     _video: VideoSubsystem,
-    _audio: AudioSubsystem,
+    /// None if the audio subsystem is unavailable
+    _audio: Option<AudioSubsystem>,
+    _timer: TimerSubsystem,
     // dropped last
     _sdl: Sdl,
     font_file_data: &'static [u8],
     text_texture_interpolate: bool,
 }
 
+impl RustSDL2SystemOtherMembers {
+    /// a free sound channel, or one to steal
+    fn reserve_channel(&self, priority: Priority) -> Option<usize> {
+        self.free_channel()
+            .or_else(|| self.channel_to_steal(priority))
+    }
+
+    /// play on a channel from reserve_channel(). with a stream, the chunk
+    /// loops and its output is replaced by the stream
+    #[allow(clippy::too_many_arguments)]
+    fn play_one_shot(
+        &mut self,
+        index: usize,
+        chunk: Rc<AudioChunk>,
+        stream: Option<stream::Generator>,
+        volume: f32,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<SoundHandle, String> {
+        // if a chunk's volume is changed, it applies retroactively to any
+        // currently playing chunks as well. I don't like this. instead, setting
+        // volume, etc, by channel is better
+        let channel = sound_channel(index);
+        channel.halt(); // if stolen
+
+        let generation = self.take_generation();
+        self.channels[index] = ChannelState {
+            chunk: Some(chunk.clone()),
+            bus,
+            priority: Some(priority),
+            gain: 1. - distance,
+            volume,
+            started: Instant::now(),
+            generation,
+        };
+
+        // registered before the position effect, so the position is applied
+        // to the stream's output
+        let loops = match stream {
+            Some(stream) => {
+                stream::register_stream(channel, stream)?;
+                -1
+            }
+            None => 0,
+        };
+
+        let angle = (direction * 360.0).round() as i16;
+        let distance = (distance * 0xFF as f32).round() as u8;
+        channel.set_position(angle, distance)?;
+        channel.set_volume(mix_volume(volume * self.buses.effective(bus)));
+        chunk.play(channel, loops)?;
+        Ok(SoundHandle::new(index, generation))
+    }
+
+    fn free_channel(&self) -> Option<usize> {
+        (0..self.channels.len()).find(|&index| !sound_channel(index).is_playing())
+    }
+
+    /// the lowest priority one-shot sound below the priority, preferring the
+    /// quietest and then the oldest
+    fn channel_to_steal(&self, priority: Priority) -> Option<usize> {
+        let loudness =
+            |state: &ChannelState| state.gain * state.volume * self.buses.effective(state.bus);
+        self.channels
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| state.priority.is_some_and(|p| p < priority))
+            .min_by(|(_, a), (_, b)| {
+                a.priority
+                    .cmp(&b.priority)
+                    .then(loudness(a).total_cmp(&loudness(b)))
+                    .then(a.started.cmp(&b.started))
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn take_generation(&mut self) -> u64 {
+        let ret = self.next_generation;
+        self.next_generation += 1;
+        ret
+    }
+
+    /// load sound from file or reuse from cache
+    fn sound_chunk(&mut self, sound: &Path) -> Result<Rc<AudioChunk>, String> {
+        let chunk = self.audio_cache.try_get_or_insert_ref(
+            &AudioKey::from_path(sound),
+            || -> Result<(Rc<AudioChunk>, usize), String> {
+                let full_path = self.audio_path_base.join(sound);
+                let chunk = AudioChunk::from_file(&full_path)?;
+                #[cfg(feature = "dev")]
+                self.asset_watcher
+                    .watch(&full_path, WatchedAsset::Audio(sound.to_path_buf()));
+                let bytes = chunk.byte_len();
+                Ok((Rc::new(chunk), bytes))
+            },
+        )?;
+        Ok(chunk.clone())
+    }
+}
+
 impl Drop for RustSDL2System {
     fn drop(&mut self) {
         // just REALLY being sure here. I don't want any surprises later.
@@ -136,12 +350,37 @@ impl Drop for RustSDL2System {
         let mut music_context = MUSIC_CONTEXT.lock().unwrap();
         music_context.current_music = None;
         music_context.next_music = None;
+        // chunks must not be playing once they're dropped
+        Channel::all().halt();
+        // free the payloads of user events that weren't received
+        let user_event_type = self.s.user_event_type;
+        for e in self.s.event_pump.poll_iter() {
+            if let sdl2::event::Event::User { type_, data1, .. } = e {
+                if type_ == user_event_type {
+                    drop(unsafe { user_event::take_payload(data1) });
+                }
+            }
+        }
     }
 }
 
 pub struct TextureHandle<'sys> {
     txt: &'sys sdl2::render::Texture,
     sys: &'sys mut RustSDL2SystemOtherMembers,
+    /// textures are shared via the cache, so the tint is undone on drop
+    tinted: bool,
+}
+
+impl<'sys> Drop for TextureHandle<'sys> {
+    fn drop(&mut self) {
+        if self.tinted {
+            let raw = self.txt.raw();
+            unsafe {
+                sdl2::sys::SDL_SetTextureColorMod(raw, 0xFF, 0xFF, 0xFF);
+                sdl2::sys::SDL_SetTextureAlphaMod(raw, 0xFF);
+            }
+        }
+    }
 }
 
 impl<'sys> crate::core::TextureHandle<'sys> for TextureHandle<'sys> {
@@ -195,6 +434,19 @@ impl<'sys> crate::core::TextureHandle<'sys> for TextureHandle<'sys> {
         ret
     }
 
+    fn tint(&mut self, color: Color) -> Result<(), String> {
+        let raw = self.txt.raw();
+        self.tinted = true;
+        let r = unsafe {
+            sdl2::sys::SDL_SetTextureColorMod(raw, color.r, color.g, color.b) != 0
+                || sdl2::sys::SDL_SetTextureAlphaMod(raw, color.a) != 0
+        };
+        if r {
+            return Err(sdl2::get_error());
+        }
+        Ok(())
+    }
+
     fn size(&self) -> Result<(NonZeroU32, NonZeroU32), String> {
         let query = self.txt.query();
         let width = NonZeroU32::new(query.width).ok_or("texture width zero")?;
@@ -351,14 +603,30 @@ impl System for RustSDL2System {
     ) -> Result<Self, String> {
         let sdl = sdl2::init()?;
         let video = sdl.video()?;
-        let audio = sdl.audio()?;
-        sdl2::mixer::open_audio(
-            44_100,
-            sdl2::mixer::AUDIO_S16LSB,
-            sdl2::mixer::DEFAULT_CHANNELS,
-            1_024,
-        )?;
-        sdl2::mixer::Music::hook_finished(music_finished_hook);
+        // a machine without audio can still run the game, silently. the reason
+        // is kept for audio_error()
+        let audio = sdl.audio();
+        // the two playlist decks, then the sound channels
+        let sound_channels = sdl2::sys::mixer::MIX_CHANNELS as usize;
+        let opened = match &audio {
+            Ok(_) => {
+                device::open_mixer(&AudioOutput::Default, DECK_CHANNELS + sound_channels as i32)
+            }
+            Err(e) => Err(e.clone()),
+        };
+        let audio_open = opened.as_ref().is_ok_and(|open| *open);
+        let audio_error = opened.err();
+        let audio = audio.ok();
+        let timer = sdl.timer()?;
+        let event_subsystem = sdl.event()?;
+        let music_event_type = unsafe { event_subsystem.register_event()? };
+        let user_event_type = unsafe { event_subsystem.register_event()? };
+        let decks = Decks::new(
+            0,
+            music_event_type,
+            device::FREQUENCY as u32,
+            device::FRAME_BYTES,
+        );
 
         let window = match size {
             Some(size) => {
@@ -381,6 +649,7 @@ impl System for RustSDL2System {
             .build()
             .map_err(|e| e.to_string())?;
         let creator = canvas.texture_creator();
+        let cache_config = CacheConfig::default();
 
         Ok(RustSDL2System {
             // texture cache has a dynamically increasing capacity with some
@@ -391,27 +660,42 @@ impl System for RustSDL2System {
             // this occurs through a dummy key. a dummy key is inserted at the
             // beginning of each frame. if at the end of the frame it got pushed
             // out of the cache (meaning the entire cache was replaced within
-            // that frame) then the cache capacity is doubled
-            texture_cache: LruCache::new(16.try_into().unwrap()),
+            // that frame) then the cache capacity is doubled, up to the
+            // configured limit
+            texture_cache: ResourceCache::new(
+                cache_config.texture_capacity,
+                cache_config.texture_byte_budget,
+            ),
 
             s: RustSDL2SystemOtherMembers {
-                // audio cache capacity is fixed. from the POV of gameplay, if too
+                // audio cache capacity doesn't grow. from the POV of gameplay, if too
                 // many different types of sounds are playing rapidly then that
                 // would be quite weird. keep in mind too, no sound is played if no
                 // channel is available (and it wouldn't try to load anything in
                 // that case), so there is a limit already
-                audio_cache: LruCache::new(
-                    (sdl2::sys::mixer::MIX_CHANNELS as usize * 4)
-                        .try_into()
-                        .unwrap(),
+                audio_cache: ResourceCache::new(
+                    cache_config.audio_capacity,
+                    cache_config.audio_byte_budget,
                 ),
-                channel_refs: Default::default(),
+                cache_config,
+                channels: (0..sound_channels).map(|_| Default::default()).collect(),
+                next_generation: 1,
+                buses: Default::default(),
+                decks,
+                audio_open,
+                audio_error,
+                audio_output: Default::default(),
                 loaded_fonts: Default::default(),
                 event_pump: sdl.event_pump()?,
+                user_event_type,
+                controllers: Controllers::new(sdl.game_controller().ok()),
                 creator,
                 canvas,
                 texture_path_base: Default::default(),
                 audio_path_base: Default::default(),
+                #[cfg(feature = "dev")]
+                asset_watcher: Default::default(),
+                loader: Default::default(),
                 ttf_context: sdl2::ttf::init().map_err(|e| e.to_string())?,
                 // empty flags - don't load any dynamic libs up front. they will be
                 // loaded as needed the first time the respective file format is loaded
@@ -419,6 +703,7 @@ impl System for RustSDL2System {
                 _mixer: sdl2::mixer::init(sdl2::mixer::InitFlag::empty())?,
                 _video: video,
                 _audio: audio,
+                _timer: timer,
                 _sdl: sdl,
                 font_file_data,
                 text_texture_interpolate,
@@ -491,25 +776,61 @@ impl System for RustSDL2System {
 
         let txt = self.texture_cache.try_get_or_insert_ref(
             &texture_key,
-            || -> Result<TextureWrapper, String> {
-                self.s
+            || -> Result<(TextureWrapper, usize), String> {
+                let full_path = self.s.texture_path_base.join(image_path);
+                let txt = self
+                    .s
                     .creator
-                    .load_texture(self.s.texture_path_base.join(image_path))
+                    .load_texture(&full_path)
                     .map(|txt| TextureWrapper(txt)) // safety - immediately put in wrapper
                     .map(|mut txt| {
                         // Nearest scale mode is the default for sdl2 (but not sdl3!)
                         txt.0.set_blend_mode(sdl2::render::BlendMode::Blend);
                         txt
-                    })
+                    })?;
+                #[cfg(feature = "dev")]
+                self.s
+                    .asset_watcher
+                    .watch(&full_path, WatchedAsset::Texture(texture_key.clone()));
+                let bytes = txt.byte_len();
+                Ok((txt, bytes))
             },
         )?;
 
         Ok(TextureHandle {
             txt: &txt.0,
             sys: &mut self.s,
+            tinted: false,
         })
     }
 
+    fn try_image<'a, P>(
+        &mut self,
+        image_path: P,
+    ) -> Result<Option<Self::ImageTextureHandle<'_>>, String>
+    where
+        P: Into<PathLike<'a>>,
+    {
+        let image_path: PathLike = image_path.into();
+        let mut maybe_buf: Option<PathBuf> = None;
+        let image_path = image_path.get_path(&mut maybe_buf);
+
+        if self
+            .texture_cache
+            .contains(&TextureKey::from_path(image_path))
+        {
+            return self.image(image_path).map(Some);
+        }
+
+        let request = LoadRequest::Image(image_path.to_path_buf());
+        if let Some(e) = self.s.loader.take_error(&request) {
+            return Err(e);
+        }
+        let full_path = self.s.texture_path_base.join(image_path);
+        self.s.loader.queue(request, full_path)?;
+        Ok(None)
+    }
+
     fn clear(&mut self, c: Color) -> Result<(), String> {
         self.s
             .canvas
@@ -521,17 +842,19 @@ impl System for RustSDL2System {
     fn present(&mut self) -> Result<(), String> {
         self.s.canvas.present();
 
+        self.finish_loads();
+        self.update_mix();
+
         let cache_fully_replaced_this_frame = self.txt_cache_fully_replaced_this_frame()?;
 
-        if cache_fully_replaced_this_frame {
-            // sane upper bound.
-            // it is possible but not likely that this is exceeded
-            debug_assert!(self.texture_cache.cap().get() < 8000);
-            self.texture_cache.resize(
-                (self.texture_cache.cap().get() * 2usize)
-                    .try_into()
-                    .unwrap(),
-            );
+        // the marker could have instead been pushed out by the byte budget, in
+        // which case more entries wouldn't help
+        let cache_full = self.texture_cache.len() >= self.texture_cache.cap().get();
+
+        if cache_fully_replaced_this_frame && cache_full {
+            let cap = self.texture_cache.cap().get() * 2usize;
+            let cap = cap.min(self.s.cache_config.texture_capacity_limit.get());
+            self.texture_cache.resize(cap.try_into().unwrap());
         }
 
         Ok(())
@@ -565,7 +888,7 @@ impl System for RustSDL2System {
 
         let txt = self.texture_cache.try_get_or_insert_ref(
             &texture_key,
-            || -> Result<TextureWrapper, String> {
+            || -> Result<(TextureWrapper, usize), String> {
                 // must recreate the texture as it is not in the cache.
                 let font = match self.s.loaded_fonts.get(&point_size) {
                     Some(v) => v, // point size is available
@@ -594,13 +917,15 @@ impl System for RustSDL2System {
                 if self.s.text_texture_interpolate {
                     texture.0.set_scale_mode(sdl2::render::ScaleMode::Linear);
                 }
-                Ok(texture)
+                let bytes = texture.byte_len();
+                Ok((texture, bytes))
             },
         )?;
 
         Ok(TextureHandle {
             txt: &txt.0,
             sys: &mut self.s,
+            tinted: false,
         })
     }
 
@@ -629,6 +954,7 @@ impl System for RustSDL2System {
             return Ok(TextureHandle {
                 txt: txt_ptr,
                 sys: sys_ptr,
+                tinted: false,
             });
         }
 
@@ -660,13 +986,7 @@ impl System for RustSDL2System {
             .map(|txt| TextureWrapper(txt))
             .map_err(|e| e.to_string())?; // safety - immediately put in wrapper
 
-        let mut must_be_true = false;
-        let txt = self.texture_cache.get_or_insert(key, || {
-            must_be_true = true;
-            txt
-        });
-
-        if !must_be_true {
+        if self.texture_cache.contains(&key) {
             // it was not in the cache above, but now it is here? the only time
             // this could change is inside the generation function. perhaps
             // generate recursed? not ok. should always be generated from
@@ -674,9 +994,13 @@ impl System for RustSDL2System {
             return Err("generated surface recursed".to_owned());
         }
 
+        let bytes = txt.byte_len();
+        let txt = self.texture_cache.insert(key, txt, bytes);
+
         Ok(TextureHandle {
             txt: &txt.0,
             sys: &mut self.s,
+            tinted: false,
         })
     }
 
@@ -710,7 +1034,37 @@ impl System for RustSDL2System {
         }
     }
 
-    fn sound<'a, 's, P>(&'s mut self, sound: P, direction: f32, distance: f32) -> Result<(), String>
+    fn sound<'a, 's, P>(
+        &'s mut self,
+        sound: P,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        P: Into<PathLike<'a>>,
+        's: 'a,
+    {
+        self.sound_variation(
+            sound,
+            Variation::default(),
+            bus,
+            priority,
+            direction,
+            distance,
+        )
+    }
+
+    fn sound_variation<'a, 's, P>(
+        &'s mut self,
+        sound: P,
+        variation: Variation,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
     where
         P: Into<PathLike<'a>>,
         's: 'a,
@@ -718,102 +1072,216 @@ impl System for RustSDL2System {
         let sound: PathLike = sound.into();
         let mut maybe_buf: Option<PathBuf> = None;
         let sound = sound.get_path(&mut maybe_buf);
+        if !self.s.audio_open {
+            return self.s.validate_audio_path(sound).map(|_| None);
+        }
 
-        // if a chunk's volume is changed, it applies retroactively to any
-        // currently playing chunks as well. I don't like this. instead, setting
-        // volume, etc, by channel is better
-        let mut channel: Option<Channel> = None;
+        let index = match self.s.reserve_channel(priority) {
+            Some(v) => v,
+            None => return Ok(None), // don't do anything but don't give error
+        };
+        let mut chunk = self.s.sound_chunk(sound)?;
+        if variation.speed != 1. {
+            // not cached. the speeds are typically random
+            chunk = Rc::new(chunk.resampled(variation.speed)?);
+        }
+        self.s
+            .play_one_shot(
+                index,
+                chunk,
+                None,
+                variation.volume.clamp(0., 1.),
+                bus,
+                priority,
+                direction,
+                distance,
+            )
+            .map(Some)
+    }
 
-        for i in 0..sdl2::sys::mixer::MIX_CHANNELS as i32 {
-            let ch = sdl2::mixer::Channel(i);
-            if !ch.is_playing() {
-                channel = Some(ch);
-                break;
-            }
+    fn samples<'a, K, G>(
+        &mut self,
+        key: K,
+        generation_function: G,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        K: Into<BytesLike<'a>>,
+        G: Fn(&mut Self) -> Result<Samples, String>,
+    {
+        if !self.s.audio_open {
+            return Ok(None);
         }
+        let key: Vec<u8> = key.into().into();
+        let key = AudioKey::User(key);
 
-        let channel = match channel {
+        let chunk = match self.s.audio_cache.get(&key) {
+            Some(v) => v.clone(),
+            None => {
+                let samples = generation_function(self)?;
+                if self.s.audio_cache.contains(&key) {
+                    // same as pixels()
+                    return Err("generated samples recursed".to_owned());
+                }
+                let chunk = Rc::new(AudioChunk::from_samples(&samples)?);
+                let bytes = chunk.byte_len();
+                self.s.audio_cache.insert(key, chunk.clone(), bytes);
+                chunk
+            }
+        };
+
+        // after generating, since that could have played sounds
+        let index = match self.s.reserve_channel(priority) {
             Some(v) => v,
-            None => return Ok(()), // don't do anything but don't give error
+            None => return Ok(None),
         };
+        self.s
+            .play_one_shot(index, chunk, None, 1., bus, priority, direction, distance)
+            .map(Some)
+    }
 
-        let chunk =
-            self.s
-                .audio_cache
-                .try_get_or_insert_ref(sound, || -> Result<Rc<Chunk>, String> {
-                    Ok(Rc::new(Chunk::from_file(
-                        self.s.audio_path_base.join(sound),
-                    )?))
-                })?;
+    fn stream_sound<F>(
+        &mut self,
+        generator: F,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        F: FnMut(&mut [f32], StreamFormat) + Send + 'static,
+    {
+        if !self.s.audio_open {
+            return Ok(None);
+        }
+        let index = match self.s.reserve_channel(priority) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let chunk = Rc::new(stream::silence()?);
+        self.s
+            .play_one_shot(
+                index,
+                chunk,
+                Some(Box::new(generator)),
+                1.,
+                bus,
+                priority,
+                direction,
+                distance,
+            )
+            .map(Some)
+    }
 
-        self.s.channel_refs[channel.0 as usize] = Some(chunk.clone());
+    fn stop_sound(&mut self, handle: SoundHandle, fade_out_duration: Option<Duration>) {
+        if !self.sound_playing(handle) {
+            return;
+        }
+        // the chunk is kept on the channel while fading out
+        let channel = sound_channel(handle.channel());
+        match fade_out_duration {
+            Some(fade_out_duration) => {
+                channel.fade_out(fade_out_duration.as_millis() as i32);
+            }
+            None => channel.halt(),
+        }
+    }
 
-        let angle = (direction * 360.0).round() as i16;
-        let distance = (distance * 0xFF as f32).round() as u8;
-        channel.set_position(angle, distance)?;
-        channel.play(&chunk, 0)?;
-        Ok(())
+    fn sound_playing(&self, handle: SoundHandle) -> bool {
+        match self.s.channels.get(handle.channel()) {
+            Some(state) => {
+                state.generation == handle.generation()
+                    && sound_channel(handle.channel()).is_playing()
+            }
+            None => false,
+        }
+    }
+
+    fn set_sound_channels(&mut self, count: usize) {
+        for index in count..self.s.channels.len() {
+            sound_channel(index).halt();
+        }
+        if self.s.audio_open {
+            sdl2::mixer::allocate_channels(DECK_CHANNELS + count as i32);
+        }
+        self.s.channels.resize_with(count, Default::default);
+    }
+
+    fn sound_channels(&self) -> usize {
+        self.s.channels.len()
     }
 
     fn loop_sound<'a>(
         &mut self,
         handle: &mut LoopingSoundHandle<'a>,
+        bus: Bus,
         direction: f32,
         distance: f32,
         fade_in_duration: Option<Duration>,
     ) -> Result<(), String> {
-        let (channel, newly_playing) = match handle.channel {
-            Some(v) => (v, false), // already playing
-            None => {
-                // need to reserve a channel to play on it
-                let channel_to_use = (|| {
-                    for i in 0..sdl2::sys::mixer::MIX_CHANNELS as i32 {
-                        let ch = sdl2::mixer::Channel(i);
-                        if !ch.is_playing() {
-                            return Some(ch);
-                        }
-                    }
-                    None
-                })();
+        if !self.s.audio_open {
+            return self.s.validate_audio_path(handle.path);
+        }
+        // the channel could have been removed by set_sound_channels()
+        let still_playing = |(index, generation): (usize, u64)| match self.s.channels.get(index) {
+            Some(state) => state.generation == generation,
+            None => false,
+        };
 
-                match channel_to_use {
+        let (index, newly_playing) = match handle.channel {
+            Some(v) if still_playing(v) => (v.0, false), // already playing
+            _ => {
+                // need to reserve a channel to play on it
+                match self.s.free_channel() {
                     // no available channels. since loop_sound is called
                     // frequently, it will try again soon
                     None => return Ok(()),
-                    Some(ch) => (ch, true),
+                    Some(index) => (index, true),
                 }
             }
         };
+        let channel = sound_channel(index);
 
         let angle = (direction * 360.0).round() as i16;
-        let distance = (distance * 0xFF as f32).round() as u8;
-        channel.set_position(angle, distance)?;
+        let distance_byte = (distance * 0xFF as f32).round() as u8;
+        channel.set_position(angle, distance_byte)?;
+        channel.set_volume(mix_volume(self.s.buses.effective(bus)));
 
         if newly_playing {
-            let chunk = self.s.audio_cache.try_get_or_insert_ref(
-                handle.path,
-                || -> Result<Rc<Chunk>, String> {
-                    Ok(Rc::new(Chunk::from_file(
-                        self.s.audio_path_base.join(handle.path),
-                    )?))
-                },
-            )?;
-
-            self.s.channel_refs[channel.0 as usize] = Some(chunk.clone());
+            let chunk = self.s.sound_chunk(handle.path)?;
+
+            let generation = self.s.take_generation();
+            self.s.channels[index] = ChannelState {
+                chunk: Some(chunk.clone()),
+                bus,
+                priority: None,
+                gain: 1. - distance,
+                volume: 1.,
+                started: Instant::now(),
+                generation,
+            };
             match fade_in_duration {
                 Some(fade_in_duration) => {
-                    channel.fade_in(&chunk, -1, fade_in_duration.as_millis() as i32)
+                    chunk.fade_in(channel, -1, fade_in_duration.as_millis() as i32)
                 }
-                None => channel.play(&chunk, -1),
+                None => chunk.play(channel, -1),
             }?;
-            handle.channel = Some(channel); // last step
+            handle.channel = Some((index, generation)); // last step
         } else {
+            let state = &mut self.s.channels[index];
+            state.bus = bus;
+            state.gain = 1. - distance;
+
             // refresh the entry in the cache even if already playing
-            let _ = self.s.audio_cache.try_get_or_insert_ref(handle.path, || {
+            let key = AudioKey::from_path(handle.path);
+            let _ = self.s.audio_cache.try_get_or_insert_ref(&key, || {
                 // it was pushed out of the cache (unlikely if adjust_sound is
-                // frequent). however, it is still in the channel_refs
-                let maybe_ref = &self.s.channel_refs[channel.0 as usize];
-                // unwrap guaranteed ok since channel_refs at index was set to
+                // frequent). however, it is still in the channels
+                let maybe_ref = &self.s.channels[index].chunk;
+                // unwrap guaranteed ok since channels at index was set to
                 // Some() above when newly_playing. but __just in case__, doing
                 // a try_get here instead. if failed, does not refresh entry
                 let chunk_rc = match maybe_ref.as_ref() {
@@ -823,7 +1291,7 @@ impl System for RustSDL2System {
                         return Err(());
                     }
                 };
-                Ok(chunk_rc.clone())
+                Ok((chunk_rc.clone(), chunk_rc.byte_len()))
             });
         }
 
@@ -835,12 +1303,17 @@ impl System for RustSDL2System {
         handle: &mut Self::LoopingSoundHandle<'a>,
         fade_out_duration: Option<Duration>,
     ) {
-        let channel = match handle.channel {
-            Some(v) => v,
+        let index = match handle.channel.take() {
+            Some((index, generation)) => match self.s.channels.get_mut(index) {
+                Some(state) if state.generation == generation => {
+                    state.chunk = None;
+                    index
+                }
+                _ => return,
+            },
             None => return,
         };
-
-        self.s.channel_refs[channel.0 as usize] = None;
+        let channel = sound_channel(index);
 
         match fade_out_duration {
             Some(fade_out_duration) => {
@@ -851,23 +1324,56 @@ impl System for RustSDL2System {
     }
 
     fn event(&mut self) -> Event {
+        // the asset watcher must get a chance to poll while waiting
+        #[cfg(feature = "dev")]
         loop {
-            let maybe_e = translate_sdl_event(self.s.event_pump.wait_event());
+            if let Some(e) = self.event_timeout(ASSET_POLL_INTERVAL) {
+                return e;
+            }
+        }
+
+        #[cfg(not(feature = "dev"))]
+        loop {
+            let e = self.s.event_pump.wait_event();
+            let maybe_e = self.handle_sdl_event(e);
             if let Some(e) = maybe_e {
                 return e;
             }
         }
     }
 
+    fn event_sender(&self) -> EventSender {
+        user_event::event_sender(self.s.user_event_type)
+    }
+
+    fn clipboard(&mut self) -> Result<String, String> {
+        let clipboard = self.s._video.clipboard();
+        if !clipboard.has_clipboard_text() {
+            return Ok(String::new());
+        }
+        clipboard.clipboard_text()
+    }
+
+    fn set_clipboard(&mut self, text: &str) -> Result<(), String> {
+        self.s._video.clipboard().set_clipboard_text(text)
+    }
+
     fn event_timeout(&mut self, timeout: Duration) -> Option<crate::core::Event> {
         let start_time = Instant::now();
         loop {
+            #[cfg(feature = "dev")]
+            if self.reload_changed_assets() {
+                return Some(Event::AssetReload);
+            }
+
             let duration_since_start = Instant::now() - start_time;
             if duration_since_start >= timeout {
                 return None;
             }
 
             let duration_remaining = timeout - duration_since_start;
+            #[cfg(feature = "dev")]
+            let duration_remaining = duration_remaining.min(ASSET_POLL_INTERVAL);
             let duration_remaining = duration_remaining.as_millis() as u32;
             if duration_remaining == 0 {
                 return None; // just in case
@@ -876,18 +1382,24 @@ impl System for RustSDL2System {
             let event_in = self.s.event_pump.wait_event_timeout(duration_remaining);
             match event_in {
                 Some(e) => {
-                    let maybe_e = translate_sdl_event(e);
+                    let maybe_e = self.handle_sdl_event(e);
                     if let Some(e) = maybe_e {
                         return Some(e);
                     }
                     // do another iteration
                 }
-                None => return None,
+                None => {
+                    // when polling for assets, the wait is shortened. the full
+                    // timeout is checked on the next iteration instead
+                    #[cfg(not(feature = "dev"))]
+                    return None;
+                }
             }
         }
     }
 
     fn stop_music(&mut self, fade_out_duration: Option<Duration>) -> Result<(), String> {
+        self.stop_playlist();
         let mut ctx = MUSIC_CONTEXT.lock().unwrap();
         ctx.next_music = None;
         match fade_out_duration {
@@ -913,7 +1425,15 @@ impl System for RustSDL2System {
         let mut maybe_buf: Option<PathBuf> = None;
         let music = music.get_path(&mut maybe_buf);
 
-        let music = sdl2::mixer::Music::from_file(self.s.audio_path_base.join(music))?;
+        if !self.s.audio_open {
+            self.stop_playlist();
+            return self.s.validate_audio_path(music);
+        }
+        let full_path = self.s.audio_path_base.join(music);
+        let music = sdl2::mixer::Music::from_file(&full_path)?;
+        #[cfg(feature = "dev")]
+        self.s.asset_watcher.watch(&full_path, WatchedAsset::Music);
+        self.stop_playlist();
         let mut ctx = MUSIC_CONTEXT.lock().unwrap();
 
         if let Some(_) = ctx.current_music.as_ref() {
@@ -934,48 +1454,470 @@ impl System for RustSDL2System {
         Ok(())
     }
 
-    fn set_music_volume(&mut self, volume: f32) {
-        sdl2::mixer::Music::set_volume((volume * MIX_MAX_VOLUME as f32).round() as i32);
+    fn playlist(&mut self) -> &mut Playlist {
+        &mut self.s.decks.playlist
     }
 
-    fn music_volume(&self) -> f32 {
-        sdl2::mixer::Music::get_volume() as f32 / MIX_MAX_VOLUME as f32
+    fn play_playlist(&mut self) -> Result<(), String> {
+        {
+            let mut ctx = MUSIC_CONTEXT.lock().unwrap();
+            ctx.next_music = None;
+            ctx.current_music = None;
+        }
+        self.s.decks.playlist.start();
+        self.play_current_track()
+    }
+
+    fn skip_track(&mut self) -> Result<(), String> {
+        if self.s.decks.playlist.current().is_none() {
+            return Ok(());
+        }
+        self.s.decks.playlist.skip();
+        self.play_current_track()
+    }
+
+    fn set_crossfade(&mut self, duration: Duration) {
+        self.s.decks.crossfade = duration;
+    }
+
+    fn pause_music(&mut self) {
+        sdl2::mixer::Music::pause();
+        self.s.decks.pause();
+    }
+
+    fn resume_music(&mut self) -> Result<(), String> {
+        sdl2::mixer::Music::resume();
+        self.s.decks.resume()
+    }
+
+    fn music_paused(&self) -> bool {
+        sdl2::mixer::Music::is_paused() || self.s.decks.paused()
+    }
+
+    fn track_position(&self) -> Option<(Duration, Duration)> {
+        self.s.decks.position()
+    }
+
+    fn seek_track(&mut self, position: Duration) -> Result<(), String> {
+        self.s.decks.seek(position)
+    }
+
+    fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.s.buses.set_volume(bus, volume);
+        self.apply_bus_volumes(bus);
+    }
+
+    fn bus_volume(&self, bus: Bus) -> f32 {
+        self.s.buses.volume(bus)
+    }
+
+    fn set_bus_muted(&mut self, bus: Bus, muted: bool) {
+        self.s.buses.set_muted(bus, muted);
+        self.apply_bus_volumes(bus);
+    }
+
+    fn bus_muted(&self, bus: Bus) -> bool {
+        self.s.buses.muted(bus)
+    }
+
+    fn fade_bus_volume(&mut self, bus: Bus, volume: f32, duration: Duration) {
+        self.s
+            .buses
+            .fade_volume(bus, volume, duration, Instant::now());
+        self.update_mix();
+    }
+
+    fn add_snapshot(&mut self, name: &str, snapshot: Snapshot) {
+        self.s.buses.add_snapshot(name, snapshot);
+    }
+
+    fn apply_snapshot(&mut self, name: &str, duration: Duration) -> Result<(), String> {
+        self.s
+            .buses
+            .apply_snapshot(name, duration, Instant::now())?;
+        self.update_mix();
+        Ok(())
+    }
+
+    fn add_ducking(&mut self, ducking: Ducking) {
+        self.s.buses.add_ducking(ducking);
+    }
+
+    fn clear_ducking(&mut self) {
+        self.s.buses.clear_ducking();
+        self.apply_bus_volumes(Bus::MASTER);
+    }
+
+    fn mix_animating(&self) -> bool {
+        self.s.buses.animating()
+    }
+
+    fn audio_devices(&self) -> Vec<String> {
+        let audio = match self.s._audio.as_ref() {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+        let count = audio.num_audio_playback_devices().unwrap_or(0);
+        (0..count)
+            .filter_map(|index| audio.audio_playback_device_name(index).ok())
+            .collect()
+    }
+
+    fn set_audio_output(&mut self, output: AudioOutput) -> Result<(), String> {
+        self.s.audio_output = output;
+        self.reopen_audio()
+    }
+
+    fn audio_output(&self) -> AudioOutput {
+        self.s.audio_output.clone()
+    }
+
+    fn audio_available(&self) -> bool {
+        self.s.audio_open
+    }
+
+    fn audio_error(&self) -> Option<&str> {
+        self.s.audio_error.as_deref()
+    }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        let texture_capacity = config.texture_capacity.min(config.texture_capacity_limit);
+        self.texture_cache.resize(texture_capacity);
+        self.texture_cache
+            .set_byte_budget(config.texture_byte_budget);
+        self.s.audio_cache.resize(config.audio_capacity);
+        self.s.audio_cache.set_byte_budget(config.audio_byte_budget);
+        self.s.cache_config = config;
+    }
+
+    fn cache_config(&self) -> CacheConfig {
+        self.s.cache_config
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        let mut ret = CacheStats {
+            textures: self.texture_cache.stats(),
+            audio: self.s.audio_cache.stats(),
+            ..Default::default()
+        };
+        ret.user_samples = self
+            .s
+            .audio_cache
+            .keys()
+            .filter(|key| matches!(key, AudioKey::User(_)))
+            .count();
+        for key in self.texture_cache.keys() {
+            match key.kind() {
+                TextureKeyKind::Path => ret.images += 1,
+                TextureKeyKind::Text => ret.text += 1,
+                TextureKeyKind::User => ret.user_pixels += 1,
+                TextureKeyKind::CacheMarker => {}
+            }
+        }
+        ret
+    }
+
+    fn preload_image<'a, P>(&mut self, image_path: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>,
+    {
+        self.image(image_path).map(|_| ())
+    }
+
+    fn preload_sound<'a, P>(&mut self, sound: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>,
+    {
+        let sound: PathLike = sound.into();
+        let mut maybe_buf: Option<PathBuf> = None;
+        let sound = sound.get_path(&mut maybe_buf);
+        if !self.s.audio_open {
+            return self.s.validate_audio_path(sound);
+        }
+        self.s.sound_chunk(sound).map(|_| ())
+    }
+
+    fn evict<'a, P>(&mut self, path: P)
+    where
+        P: Into<PathLike<'a>>,
+    {
+        let path: PathLike = path.into();
+        let mut maybe_buf: Option<PathBuf> = None;
+        let path = path.get_path(&mut maybe_buf);
+        self.texture_cache.pop(&TextureKey::from_path(path));
+        // playing channels keep their own ref (channels)
+        self.s.audio_cache.pop(&AudioKey::from_path(path));
+    }
+
+    fn clear_cache(&mut self) {
+        self.texture_cache.clear();
+        self.s.audio_cache.clear();
+    }
+
+    fn queue_image<'a, P>(&mut self, image_path: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>,
+    {
+        let image_path: PathLike = image_path.into();
+        let mut maybe_buf: Option<PathBuf> = None;
+        let image_path = image_path.get_path(&mut maybe_buf);
+
+        if self
+            .texture_cache
+            .promote(&TextureKey::from_path(image_path))
+        {
+            self.s.loader.already_loaded();
+            return Ok(());
+        }
+
+        let full_path = self.s.texture_path_base.join(image_path);
+        self.s
+            .loader
+            .queue(LoadRequest::Image(image_path.to_path_buf()), full_path)
+    }
+
+    fn queue_sound<'a, P>(&mut self, sound: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>,
+    {
+        let sound: PathLike = sound.into();
+        let mut maybe_buf: Option<PathBuf> = None;
+        let sound = sound.get_path(&mut maybe_buf);
+
+        if self.s.audio_cache.promote(&AudioKey::from_path(sound)) {
+            self.s.loader.already_loaded();
+            return Ok(());
+        }
+
+        if !self.s.audio_open {
+            // can't be decoded without a mixer
+            self.s.validate_audio_path(sound)?;
+            self.s.loader.already_loaded();
+            return Ok(());
+        }
+
+        let full_path = self.s.audio_path_base.join(sound);
+        self.s
+            .loader
+            .queue(LoadRequest::Sound(sound.to_path_buf()), full_path)
+    }
+
+    fn load_progress(&self) -> LoadProgress {
+        self.s.loader.progress()
     }
 }
 
 impl RustSDL2System {
-    fn txt_cache_fully_replaced_this_frame(&mut self) -> Result<bool, String> {
-        let texture_key = TextureKey::cache_marker_key();
+    /// evicts cache entries whose file changed on disk, so they're reloaded the
+    /// next time they are used. returns true if anything changed
+    #[cfg(feature = "dev")]
+    fn reload_changed_assets(&mut self) -> bool {
+        let changed = self.s.asset_watcher.poll();
+        for asset in changed.iter() {
+            match asset {
+                WatchedAsset::Texture(texture_key) => {
+                    self.texture_cache.pop(texture_key);
+                }
+                WatchedAsset::Audio(path) => {
+                    self.s.audio_cache.pop(&AudioKey::from_path(path));
+                }
+                WatchedAsset::Music => {}
+            }
+        }
+        !changed.is_empty()
+    }
 
-        let mut ret = false;
-
-        self.texture_cache.try_get_or_insert(
-            texture_key,
-            || -> Result<TextureWrapper, String> {
-                // recreate the texture since it's not in the cache
-                ret = true;
-                let mut surface =
-                    sdl2::surface::Surface::new(1, 1, sdl2::pixels::PixelFormatEnum::RGBA32)
-                        .unwrap();
-                surface.with_lock_mut(|buffer| {
-                    buffer[0] = 0xFF;
-                    buffer[1] = 0xFF;
-                    buffer[2] = 0xFF;
-                    buffer[3] = 0xFF;
-                });
+    /// progress bus fades and ducking
+    fn update_mix(&mut self) {
+        let channels = &self.s.channels;
+        let music_playing =
+            self.s.audio_open && (sdl2::mixer::Music::is_playing() || self.s.decks.is_playing());
+        let playing = |bus: Bus| {
+            (bus == Bus::MUSIC && music_playing)
+                || channels.iter().enumerate().any(|(i, state)| {
+                    state.bus == bus && state.chunk.is_some() && sound_channel(i).is_playing()
+                })
+        };
+        if self.s.buses.tick(Instant::now(), playing) {
+            self.apply_bus_volumes(Bus::MASTER);
+        }
+    }
 
-                self.s
+    /// after a bus changes, update everything playing on it
+    fn apply_bus_volumes(&mut self, changed: Bus) {
+        let applies = |bus: Bus| changed == Bus::MASTER || changed == bus;
+        for (i, state) in self.s.channels.iter().enumerate() {
+            if state.chunk.is_some() && applies(state.bus) {
+                let volume = state.volume * self.s.buses.effective(state.bus);
+                sound_channel(i).set_volume(mix_volume(volume));
+            }
+        }
+        if applies(Bus::MUSIC) {
+            let volume = self.s.buses.effective(Bus::MUSIC);
+            sdl2::mixer::Music::set_volume(mix_volume(volume));
+            self.s.decks.set_volume(volume);
+        }
+    }
+
+    /// finish what was loaded on the worker thread, within the time budget
+    fn finish_loads(&mut self) {
+        let start = Instant::now();
+        while let Some((request, result)) = self.s.loader.recv() {
+            let result = result.and_then(|loaded| self.finish_load(&request, loaded));
+            self.s.loader.finish(request, result);
+            if start.elapsed() >= LOAD_FRAME_BUDGET {
+                break;
+            }
+        }
+    }
+
+    fn finish_load(&mut self, request: &LoadRequest, loaded: Loaded) -> Result<(), String> {
+        match (request, loaded) {
+            (
+                LoadRequest::Image(image_path),
+                Loaded::Image {
+                    width,
+                    height,
+                    mut pixels,
+                },
+            ) => {
+                let surface = sdl2::surface::Surface::from_data(
+                    &mut pixels,
+                    width,
+                    height,
+                    width * std::mem::size_of::<Color>() as u32,
+                    sdl2::pixels::PixelFormatEnum::RGBA32,
+                )?;
+                let mut txt = self
+                    .s
                     .creator
                     .create_texture_from_surface(surface)
                     .map(|txt| TextureWrapper(txt)) // safety - immediately put in wrapper
-                    .map_err(|e| e.to_string())
-            },
-        )?;
-        Ok(ret)
+                    .map_err(|e| e.to_string())?;
+                txt.0.set_blend_mode(sdl2::render::BlendMode::Blend);
+
+                let texture_key = TextureKey::from_path(image_path);
+                #[cfg(feature = "dev")]
+                self.s.asset_watcher.watch(
+                    &self.s.texture_path_base.join(image_path),
+                    WatchedAsset::Texture(texture_key.clone()),
+                );
+
+                // queued images are expected to be used soon. they shouldn't
+                // push each other out
+                let cap = self.texture_cache.cap().get();
+                if self.texture_cache.len() >= cap {
+                    let cap = (cap * 2).min(self.s.cache_config.texture_capacity_limit.get());
+                    self.texture_cache.resize(cap.try_into().unwrap());
+                }
+
+                let bytes = txt.byte_len();
+                self.texture_cache.insert(texture_key, txt, bytes);
+                Ok(())
+            }
+            (LoadRequest::Sound(_), Loaded::Sound(_)) if !self.s.audio_open => {
+                Ok(()) // decoded for a mixer which has since closed
+            }
+            (LoadRequest::Sound(sound), Loaded::Sound(chunk)) => {
+                #[cfg(feature = "dev")]
+                self.s.asset_watcher.watch(
+                    &self.s.audio_path_base.join(sound),
+                    WatchedAsset::Audio(sound.clone()),
+                );
+                let bytes = chunk.byte_len();
+                self.s
+                    .audio_cache
+                    .insert(AudioKey::Path(sound.clone()), Rc::new(chunk), bytes);
+                Ok(())
+            }
+            (LoadRequest::Track(track), Loaded::Track(chunk)) => {
+                self.track_prepared(track.clone(), chunk);
+                Ok(())
+            }
+            _ => Err("load result doesn't match request".to_owned()),
+        }
+    }
+
+    fn txt_cache_fully_replaced_this_frame(&mut self) -> Result<bool, String> {
+        let texture_key = TextureKey::cache_marker_key();
+
+        // not counted as a cache hit or miss
+        if self.texture_cache.promote(&texture_key) {
+            return Ok(false);
+        }
+
+        // recreate the texture since it's not in the cache
+        let mut surface =
+            sdl2::surface::Surface::new(1, 1, sdl2::pixels::PixelFormatEnum::RGBA32).unwrap();
+        surface.with_lock_mut(|buffer| {
+            buffer[0] = 0xFF;
+            buffer[1] = 0xFF;
+            buffer[2] = 0xFF;
+            buffer[3] = 0xFF;
+        });
+
+        let txt = self
+            .s
+            .creator
+            .create_texture_from_surface(surface)
+            .map(|txt| TextureWrapper(txt)) // safety - immediately put in wrapper
+            .map_err(|e| e.to_string())?;
+        let bytes = txt.byte_len();
+        self.texture_cache.insert(texture_key, txt, bytes);
+        Ok(true)
     }
 }
 
-fn translate_sdl_event(i: sdl2::event::Event) -> Option<Event> {
+impl RustSDL2System {
+    /// handles the backend's own events, and translates the rest
+    fn handle_sdl_event(&mut self, e: sdl2::event::Event) -> Option<Event> {
+        match e {
+            sdl2::event::Event::User { type_, code, .. } if type_ == self.s.decks.event_type() => {
+                self.music_event(code)
+            }
+            sdl2::event::Event::User { type_, data1, .. } if type_ == self.s.user_event_type => {
+                Some(Event::User(unsafe { user_event::take_payload(data1) }))
+            }
+            sdl2::event::Event::ControllerDeviceAdded { which, .. } => {
+                self.s.controllers.added(which);
+                None
+            }
+            sdl2::event::Event::ControllerDeviceRemoved { which, .. } => {
+                self.s.controllers.removed(which);
+                None
+            }
+            sdl2::event::Event::AudioDeviceRemoved {
+                iscapture: false, ..
+            } => self.audio_device_event(true),
+            sdl2::event::Event::AudioDeviceAdded {
+                iscapture: false, ..
+            } => self.audio_device_event(false),
+            e => translate_sdl_event(e),
+        }
+    }
+}
+
+fn translate_key(keycode: Keycode, keymod: Mod, down: bool) -> Option<Event> {
+    let mods = KeyMods {
+        shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+        alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        gui: keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+    };
+    let nav = match keycode {
+        Keycode::LEFT => Some(NavKey::Left),
+        Keycode::RIGHT => Some(NavKey::Right),
+        Keycode::UP => Some(NavKey::Up),
+        Keycode::DOWN => Some(NavKey::Down),
+        Keycode::HOME => Some(NavKey::Home),
+        Keycode::END => Some(NavKey::End),
+        Keycode::PAGEUP => Some(NavKey::PageUp),
+        Keycode::PAGEDOWN => Some(NavKey::PageDown),
+        _ => None,
+    };
+    if let Some(key) = nav {
+        return Some(Event::NavKey(NavKeyEvent { key, down, mods }));
+    }
     let i32_to_byte = |i: i32| -> Option<u8> {
         if (0..=255).contains(&i) {
             Some(i as u8)
@@ -983,6 +1925,14 @@ fn translate_sdl_event(i: sdl2::event::Event) -> Option<Event> {
             None
         }
     };
+    let mut key = i32_to_byte(*keycode)?;
+    if mods.shift {
+        key = ascii_more_to_upper(key);
+    }
+    Some(Event::Key(crate::core::event::KeyEvent { key, down, mods }))
+}
+
+fn translate_sdl_event(i: sdl2::event::Event) -> Option<Event> {
     match i {
         sdl2::event::Event::Quit { .. } => return Some(Event::Quit),
         sdl2::event::Event::Window { win_event, .. } => match win_event {
@@ -1001,50 +1951,16 @@ fn translate_sdl_event(i: sdl2::event::Event) -> Option<Event> {
             _ => {}
         },
         sdl2::event::Event::KeyDown {
-            keycode, keymod, ..
-        } => {
-            let keycode = match keycode {
-                Some(v) => {
-                    let v: i32 = *v;
-                    i32_to_byte(v)
-                }
-                None => None,
-            };
-            match keycode {
-                Some(mut key) => {
-                    let shift_pressed = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
-                    if shift_pressed {
-                        key = ascii_more_to_upper(key);
-                    }
-                    return Some(Event::Key(crate::core::event::KeyEvent { key, down: true }));
-                }
-                None => {}
-            }
-        }
+            keycode: Some(keycode),
+            keymod,
+            ..
+        } => return translate_key(keycode, keymod, true),
         sdl2::event::Event::KeyUp {
-            keycode, keymod, ..
-        } => {
-            let keycode = match keycode {
-                Some(v) => {
-                    let v: i32 = *v;
-                    i32_to_byte(v)
-                }
-                None => None,
-            };
-            match keycode {
-                Some(mut key) => {
-                    let shift_pressed = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
-                    if shift_pressed {
-                        key = ascii_more_to_upper(key);
-                    }
-                    return Some(Event::Key(crate::core::event::KeyEvent {
-                        key,
-                        down: false,
-                    }));
-                }
-                None => {}
-            }
-        }
+            keycode: Some(keycode),
+            keymod,
+            ..
+        } => return translate_key(keycode, keymod, false),
+        sdl2::event::Event::TextInput { text, .. } => return Some(Event::Text(text)),
         sdl2::event::Event::MouseMotion {
             mousestate, x, y, ..
         } => {
@@ -1100,13 +2016,37 @@ fn translate_sdl_event(i: sdl2::event::Event) -> Option<Event> {
                 wheel_dy: y * multiplier,
             }));
         }
+        sdl2::event::Event::ControllerButtonDown { which, button, .. } => {
+            return Some(Event::ControllerButton(ControllerButtonEvent {
+                controller: which,
+                button: controller::translate_button(button),
+                down: true,
+            }));
+        }
+        sdl2::event::Event::ControllerButtonUp { which, button, .. } => {
+            return Some(Event::ControllerButton(ControllerButtonEvent {
+                controller: which,
+                button: controller::translate_button(button),
+                down: false,
+            }));
+        }
+        sdl2::event::Event::ControllerAxisMotion {
+            which, axis, value, ..
+        } => {
+            return Some(Event::ControllerAxis(ControllerAxisEvent {
+                controller: which,
+                axis: controller::translate_axis(axis),
+                value: controller::axis_value(value),
+            }));
+        }
         _ => {}
     }
     return None;
 }
 
 pub struct LoopingSoundHandle<'a> {
-    channel: Option<Channel>,
+    /// sound channel index and generation
+    channel: Option<(usize, u64)>,
     path: &'a Path,
 }
 
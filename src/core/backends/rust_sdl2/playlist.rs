@@ -0,0 +1,460 @@
+use std::{
+    ffi::c_void,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+use sdl2::mixer::Channel;
+
+use super::{chunk::AudioChunk, loader::LoadRequest, mix_volume, RustSDL2System};
+use crate::core::{audio::Bus, playlist::Playlist, Event};
+
+/// user event code which only notifies that the track changed. other codes are
+/// the generation of a transition timer
+const TRACK_CHANGED_CODE: i32 = -1;
+
+/// tracks are held decoded in memory, so longer ones are refused
+const MAX_TRACK_DURATION: Duration = Duration::from_secs(10 * 60);
+
+extern "C" {
+    // SDL_mixer 2.6. not in the sdl2 bindings
+    fn Mix_MusicDuration(music: *mut sdl2::sys::mixer::Mix_Music) -> f64;
+}
+
+/// the length of an audio file, from its decoder's metadata. nothing is
+/// decoded. None if the decoder doesn't know it
+fn file_duration(path: &Path) -> Option<Duration> {
+    let music = sdl2::mixer::Music::from_file(path).ok()?;
+    let secs = unsafe { Mix_MusicDuration(music.raw) };
+    if secs.is_finite() && secs >= 0. {
+        Some(Duration::from_secs_f64(secs))
+    } else {
+        None
+    }
+}
+
+fn check_track_duration(duration: Duration) -> Result<(), String> {
+    if duration > MAX_TRACK_DURATION {
+        return Err(format!(
+            "playlist tracks can be at most {} minutes",
+            MAX_TRACK_DURATION.as_secs() / 60
+        ));
+    }
+    Ok(())
+}
+
+fn decode_track_with<T>(
+    path: &Path,
+    duration: impl FnOnce(&Path) -> Option<Duration>,
+    decode: impl FnOnce(&Path) -> Result<T, String>,
+) -> Result<T, String> {
+    if let Some(duration) = duration(path) {
+        check_track_duration(duration)?;
+    }
+    decode(path)
+}
+
+/// tracks which are too long are refused before they're decoded, if their
+/// length is known from the file
+pub(crate) fn decode_track(path: &Path) -> Result<AudioChunk, String> {
+    decode_track_with(path, file_duration, AudioChunk::from_file)
+}
+
+/// the user event type of the transition timer. read from the timer thread
+static TIMER_EVENT_TYPE: AtomicU32 = AtomicU32::new(0);
+
+/// pushed from the timer thread
+fn push_music_event(event_type: u32, code: i32) {
+    let mut event: sdl2::sys::SDL_Event = unsafe { std::mem::zeroed() };
+    event.user = sdl2::sys::SDL_UserEvent {
+        type_: event_type,
+        timestamp: 0,
+        windowID: 0,
+        code,
+        data1: std::ptr::null_mut(),
+        data2: std::ptr::null_mut(),
+    };
+    unsafe { sdl2::sys::SDL_PushEvent(&mut event) };
+}
+
+/// param is the generation, not a pointer. nothing is freed when the timer is
+/// removed, since sdl doesn't wait for a running callback
+extern "C" fn transition_timer_callback(_interval: u32, param: *mut c_void) -> u32 {
+    let generation = param as usize as i32;
+    push_music_event(TIMER_EVENT_TYPE.load(Ordering::Relaxed), generation);
+    0 // don't repeat
+}
+
+/// a track playing on a deck
+struct DeckTrack {
+    /// set if playback didn't start from the beginning. dropped before chunk
+    slice: Option<AudioChunk>,
+    chunk: AudioChunk,
+    duration: Duration,
+    /// position in the track when playback last (re)started
+    offset: Duration,
+    /// when playback last (re)started. None while paused
+    started: Option<Instant>,
+}
+
+impl DeckTrack {
+    fn elapsed(&self) -> Duration {
+        let ret = match self.started {
+            Some(started) => self.offset + started.elapsed(),
+            None => self.offset,
+        };
+        ret.min(self.duration)
+    }
+}
+
+/// playlist tracks are decoded fully and played on two reserved mixer
+/// channels (decks), instead of with sdl mixer music. this allows true
+/// crossfades (sdl mixer only has one music stream) and seeking. at most the
+/// current, outgoing and prepared tracks are held at once, each limited to
+/// MAX_TRACK_DURATION
+pub(crate) struct Decks {
+    pub playlist: Playlist,
+    pub crossfade: Duration,
+    /// the mixer channel of the first deck. the second is the next channel
+    first_channel: i32,
+    tracks: [Option<DeckTrack>; 2],
+    /// index of the deck with the current track
+    active: usize,
+    /// the next track, decoded ahead of time
+    prepared: Option<(PathBuf, AudioChunk)>,
+
+    event_type: u32,
+    timer: Option<sdl2::sys::SDL_TimerID>,
+    generation: i32,
+
+    /// of the opened audio device
+    frequency: u32,
+    frame_bytes: u32,
+}
+
+impl Drop for Decks {
+    fn drop(&mut self) {
+        self.cancel_timer();
+        self.halt();
+    }
+}
+
+impl Decks {
+    /// frequency and frame_bytes are of the output format
+    pub fn new(first_channel: i32, event_type: u32, frequency: u32, frame_bytes: u32) -> Self {
+        TIMER_EVENT_TYPE.store(event_type, Ordering::Relaxed);
+        Self {
+            playlist: Default::default(),
+            crossfade: Default::default(),
+            first_channel,
+            tracks: Default::default(),
+            active: 0,
+            prepared: None,
+            event_type,
+            timer: None,
+            generation: 0,
+            frequency,
+            frame_bytes,
+        }
+    }
+
+    pub fn event_type(&self) -> u32 {
+        self.event_type
+    }
+
+    fn channel(&self, deck: usize) -> Channel {
+        Channel(self.first_channel + deck as i32)
+    }
+
+    fn duration_of(&self, byte_len: usize) -> Duration {
+        let frames = byte_len as u64 / self.frame_bytes as u64;
+        Duration::from_secs_f64(frames as f64 / self.frequency as f64)
+    }
+
+    fn byte_offset_of(&self, position: Duration) -> usize {
+        let frames = (position.as_secs_f64() * self.frequency as f64) as usize;
+        frames * self.frame_bytes as usize
+    }
+
+    pub fn paused(&self) -> bool {
+        match &self.tracks[self.active] {
+            Some(track) => track.started.is_none(),
+            None => false,
+        }
+    }
+
+    /// either deck has a track, including one fading out
+    pub fn is_playing(&self) -> bool {
+        (0..self.tracks.len())
+            .any(|deck| self.tracks[deck].is_some() && self.channel(deck).is_playing())
+    }
+
+    pub fn position(&self) -> Option<(Duration, Duration)> {
+        let track = self.tracks[self.active].as_ref()?;
+        Some((track.elapsed(), track.duration))
+    }
+
+    pub fn cancel_timer(&mut self) {
+        if let Some(id) = self.timer.take() {
+            unsafe { sdl2::sys::SDL_RemoveTimer(id) };
+        }
+        // any already pushed timer event is stale
+        self.generation = self.generation.wrapping_add(1) & i32::MAX;
+    }
+
+    /// arrange for the transition to the next track
+    fn schedule_timer(&mut self) -> Result<(), String> {
+        self.cancel_timer();
+        let track = match &self.tracks[self.active] {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        if track.started.is_none() {
+            return Ok(()); // paused
+        }
+        let remaining = track.duration.saturating_sub(track.elapsed());
+        // the last track plays out fully
+        let due = match self.playlist.peek_next() {
+            Some(_) => remaining.saturating_sub(self.crossfade),
+            None => remaining,
+        };
+        let id = unsafe {
+            sdl2::sys::SDL_AddTimer(
+                (due.as_millis() as u32).max(1),
+                Some(transition_timer_callback),
+                self.generation as usize as *mut c_void,
+            )
+        };
+        if id == 0 {
+            return Err(sdl2::get_error());
+        }
+        self.timer = Some(id);
+        Ok(())
+    }
+
+    /// is this timer event for the current transition
+    pub fn is_current(&self, code: i32) -> bool {
+        code == self.generation
+    }
+
+    pub fn notify_track_changed(&self) {
+        push_music_event(self.event_type, TRACK_CHANGED_CODE);
+    }
+
+    /// stop both decks immediately
+    pub fn halt(&mut self) {
+        for deck in 0..self.tracks.len() {
+            self.channel(deck).halt();
+            self.tracks[deck] = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.cancel_timer();
+        self.halt();
+        self.prepared = None;
+        self.playlist.stop();
+    }
+
+    pub fn pause(&mut self) {
+        self.cancel_timer();
+        for deck in 0..self.tracks.len() {
+            if let Some(track) = self.tracks[deck].as_mut() {
+                if track.started.is_some() {
+                    track.offset = track.elapsed();
+                    track.started = None;
+                }
+            }
+            self.channel(deck).pause();
+        }
+    }
+
+    pub fn resume(&mut self) -> Result<(), String> {
+        for deck in 0..self.tracks.len() {
+            if let Some(track) = self.tracks[deck].as_mut() {
+                if track.started.is_none() {
+                    track.started = Some(Instant::now());
+                }
+            }
+            self.channel(deck).resume();
+        }
+        self.schedule_timer()
+    }
+
+    /// fade out the active deck, and start the chunk on the other one
+    fn start(&mut self, chunk: AudioChunk, volume: f32) -> Result<(), String> {
+        let duration = self.duration_of(chunk.byte_len());
+        // in case the length wasn't known before decoding
+        check_track_duration(duration)?;
+        let fade_ms = self.crossfade.as_millis() as i32;
+        let outgoing = self.channel(self.active);
+        if fade_ms > 0 {
+            outgoing.fade_out(fade_ms);
+        } else {
+            outgoing.halt();
+        }
+
+        self.active = (self.active + 1) % self.tracks.len();
+        let channel = self.channel(self.active);
+        // the deck might still be fading out from a transition before
+        channel.halt();
+        self.tracks[self.active] = None;
+
+        channel.set_volume(mix_volume(volume));
+        if fade_ms > 0 {
+            chunk.fade_in(channel, 0, fade_ms)?;
+        } else {
+            chunk.play(channel, 0)?;
+        }
+        self.tracks[self.active] = Some(DeckTrack {
+            slice: None,
+            duration,
+            chunk,
+            offset: Duration::ZERO,
+            started: Some(Instant::now()),
+        });
+        self.schedule_timer()
+    }
+
+    pub fn seek(&mut self, position: Duration) -> Result<(), String> {
+        let byte_offset = self.byte_offset_of(position);
+        let channel = self.channel(self.active);
+        let track = match self.tracks[self.active].as_mut() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let position = position.min(track.duration);
+        let paused = track.started.is_none();
+
+        channel.halt();
+        track.slice = None;
+        // safety: the slice is dropped before the chunk (DeckTrack member
+        // order), and the channel is halted before the track is dropped
+        let slice = unsafe { track.chunk.slice(byte_offset)? };
+        slice.play(channel, 0)?;
+        if paused {
+            channel.pause();
+        }
+        track.slice = Some(slice);
+        track.offset = position;
+        track.started = if paused { None } else { Some(Instant::now()) };
+        self.schedule_timer()
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        for deck in 0..self.tracks.len() {
+            self.channel(deck).set_volume(mix_volume(volume));
+        }
+    }
+}
+
+impl RustSDL2System {
+    /// decode the track, using the one prepared ahead of time if it matches
+    fn track_chunk(&mut self, track: &Path) -> Result<AudioChunk, String> {
+        if let Some((path, chunk)) = self.s.decks.prepared.take() {
+            if path == track {
+                return Ok(chunk);
+            }
+        }
+        decode_track(&self.s.audio_path_base.join(track))
+    }
+
+    /// decode the track after the current one on the worker thread
+    fn prepare_next_track(&mut self) -> Result<(), String> {
+        let next = match self.s.decks.playlist.peek_next() {
+            Some(v) => v.to_path_buf(),
+            None => return Ok(()),
+        };
+        let full_path = self.s.audio_path_base.join(&next);
+        self.s.loader.queue(LoadRequest::Track(next), full_path)
+    }
+
+    /// a decoded track arrived from the worker thread
+    pub(super) fn track_prepared(&mut self, track: PathBuf, chunk: AudioChunk) {
+        if self.s.decks.playlist.peek_next() == Some(track.as_path()) {
+            self.s.decks.prepared = Some((track, chunk));
+        }
+    }
+
+    /// start whatever is current in the playlist. stops if nothing is
+    pub(super) fn play_current_track(&mut self) -> Result<(), String> {
+        let track = match self.s.decks.playlist.current() {
+            Some(v) => v.to_path_buf(),
+            None => {
+                self.s.decks.stop();
+                self.s.decks.notify_track_changed();
+                return Ok(());
+            }
+        };
+        if !self.s.audio_open {
+            return self.s.validate_audio_path(&track);
+        }
+        // anything prepared is handled before the result is used
+        self.finish_loads();
+        let chunk = self.track_chunk(&track)?;
+        let volume = self.s.buses.effective(Bus::MUSIC);
+        self.s.decks.start(chunk, volume)?;
+        self.s.decks.notify_track_changed();
+        self.prepare_next_track()
+    }
+
+    /// the transition timer fired
+    fn advance_track(&mut self) -> Result<(), String> {
+        self.s.decks.playlist.advance();
+        self.play_current_track()
+    }
+
+    pub(super) fn stop_playlist(&mut self) {
+        if self.s.decks.playlist.current().is_some() {
+            self.s.decks.stop();
+            self.s.decks.notify_track_changed();
+        }
+    }
+
+    /// handles the playlist's own user events
+    pub(super) fn music_event(&mut self, code: i32) -> Option<Event> {
+        if code == TRACK_CHANGED_CODE {
+            return Some(Event::TrackChanged);
+        }
+        if !self.s.decks.is_current(code) {
+            return None; // the timer was cancelled after it fired
+        }
+        if self.advance_track().is_err() {
+            // a track that can't be played ends the playlist
+            self.stop_playlist();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_track_too_long() {
+        let mut decoded = false;
+        let result = decode_track_with(
+            Path::new("long.ogg"),
+            |_| Some(MAX_TRACK_DURATION + Duration::from_secs(1)),
+            |_| {
+                decoded = true;
+                Ok(())
+            },
+        );
+        assert!(result.is_err());
+        assert!(!decoded);
+
+        let result = decode_track_with(
+            Path::new("short.ogg"),
+            |_| Some(MAX_TRACK_DURATION),
+            |_| Ok(()),
+        );
+        assert!(result.is_ok());
+
+        // unknown length is checked after decoding instead
+        let result = decode_track_with(Path::new("unknown.ogg"), |_| None, |_| Ok(()));
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    ffi::{c_int, c_void},
+    num::{NonZeroU16, NonZeroU32},
+    panic::AssertUnwindSafe,
+};
+
+use sdl2::mixer::Channel;
+
+use super::chunk::{f32_to_i16, AudioChunk};
+use crate::core::audio::{SampleData, Samples, StreamFormat};
+
+pub(crate) type Generator = Box<dyn FnMut(&mut [f32], StreamFormat) + Send>;
+
+/// owned by the mixer while the effect is registered
+struct StreamState {
+    generator: Generator,
+    format: StreamFormat,
+    /// reused between calls
+    buffer: Vec<f32>,
+}
+
+/// replaces the channel's output (silence) with the generated samples
+unsafe extern "C" fn stream_effect(
+    _chan: c_int,
+    stream: *mut c_void,
+    len: c_int,
+    udata: *mut c_void,
+) {
+    let state = &mut *(udata as *mut StreamState);
+    let out = std::slice::from_raw_parts_mut(stream as *mut u8, len as usize);
+
+    state.buffer.clear();
+    state.buffer.resize(out.len() / 2, 0.);
+    // can't unwind into C. output silence instead
+    let generated = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        (state.generator)(&mut state.buffer, state.format)
+    }));
+    if generated.is_err() {
+        state.buffer.fill(0.);
+    }
+
+    // the output format is always signed 16 bit little endian. see open_audio
+    for (sample, out) in state.buffer.iter().zip(out.chunks_exact_mut(2)) {
+        out.copy_from_slice(&f32_to_i16(*sample).to_le_bytes());
+    }
+}
+
+/// called once the channel stops, or the mixer closes
+unsafe extern "C" fn stream_done(_chan: c_int, udata: *mut c_void) {
+    drop(Box::from_raw(udata as *mut StreamState));
+}
+
+/// a short silent chunk. looped on the channel to keep it playing
+pub(crate) fn silence() -> Result<AudioChunk, String> {
+    AudioChunk::from_samples(&Samples {
+        sample_rate: NonZeroU32::new(44_100).unwrap(),
+        channels: NonZeroU16::new(1).unwrap(),
+        data: SampleData::I16(vec![0; 1_024]),
+    })
+}
+
+/// the channel must be stopped. the generator is dropped once the channel
+/// stops playing again
+pub(crate) fn register_stream(channel: Channel, generator: Generator) -> Result<(), String> {
+    let (frequency, _, channels) = sdl2::mixer::query_spec()?;
+    let state = Box::into_raw(Box::new(StreamState {
+        generator,
+        format: StreamFormat {
+            sample_rate: frequency as u32,
+            channels: channels as u16,
+        },
+        buffer: Vec::new(),
+    }));
+    let ret = unsafe {
+        sdl2::sys::mixer::Mix_RegisterEffect(
+            channel.0,
+            Some(stream_effect),
+            Some(stream_done),
+            state as *mut c_void,
+        )
+    };
+    if ret == 0 {
+        drop(unsafe { Box::from_raw(state) });
+        return Err(sdl2::get_error());
+    }
+    Ok(())
+}
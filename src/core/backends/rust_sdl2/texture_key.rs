@@ -30,7 +30,25 @@ pub struct TextureKey {
     data: Vec<u8>,
 }
 
+/// which variant a TextureKey is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureKeyKind {
+    Path,
+    Text,
+    User,
+    CacheMarker,
+}
+
 impl TextureKey {
+    pub fn kind(&self) -> TextureKeyKind {
+        match self.data.last() {
+            Some(b'\x00') => TextureKeyKind::Path,
+            Some(b'\x01') | Some(b'\x02') => TextureKeyKind::Text,
+            Some(b'\x03') => TextureKeyKind::User,
+            _ => TextureKeyKind::CacheMarker,
+        }
+    }
+
     pub fn cache_marker_key() -> Self {
         Self { data: vec![0xff] }
     }
@@ -207,4 +225,18 @@ mod tests {
         rhs.push(b'\x02');
         assert_eq!(s.data, rhs);
     }
+
+    #[test]
+    fn test_kind() {
+        let path = PathBuf::from("abc");
+        assert_eq!(TextureKey::from_path(&path).kind(), TextureKeyKind::Path);
+        let s = TextureKey::from_rendered_text("text", Color::WHITE, 16);
+        assert_eq!(s.kind(), TextureKeyKind::Text);
+        let s = TextureKey::from_rendered_wrapped_text("text", Color::WHITE, 16, 100);
+        assert_eq!(s.kind(), TextureKeyKind::Text);
+        let s = TextureKey::from_user_defined_key(vec![0xFF]);
+        assert_eq!(s.kind(), TextureKeyKind::User);
+        let s = TextureKey::cache_marker_key();
+        assert_eq!(s.kind(), TextureKeyKind::CacheMarker);
+    }
 }
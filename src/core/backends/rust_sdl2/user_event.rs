@@ -0,0 +1,33 @@
+use std::ffi::c_void;
+
+use crate::core::event::{EventSender, UserPayload};
+
+/// the payload is boxed and owned by the event until take_payload()
+pub(super) fn event_sender(event_type: u32) -> EventSender {
+    EventSender::new(move |payload| {
+        let data1 = Box::into_raw(Box::new(payload)) as *mut c_void;
+        let mut event: sdl2::sys::SDL_Event = unsafe { std::mem::zeroed() };
+        event.user = sdl2::sys::SDL_UserEvent {
+            type_: event_type,
+            timestamp: 0,
+            windowID: 0,
+            code: 0,
+            data1,
+            data2: std::ptr::null_mut(),
+        };
+        // thread safe. fails once sdl is quit
+        if unsafe { sdl2::sys::SDL_PushEvent(&mut event) } == 1 {
+            Ok(())
+        } else {
+            drop(unsafe { take_payload(data1) });
+            Err(sdl2::get_error())
+        }
+    })
+}
+
+/// # Safety
+///
+/// data1 of an event pushed by event_sender(), taken once
+pub(super) unsafe fn take_payload(data1: *mut c_void) -> UserPayload {
+    *Box::from_raw(data1 as *mut UserPayload)
+}
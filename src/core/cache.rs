@@ -0,0 +1,87 @@
+use std::num::NonZeroUsize;
+
+/// controls how many resources the system keeps loaded. entries are evicted
+/// least recently used first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// starting number of textures (images, text, user pixels) kept
+    pub texture_capacity: NonZeroUsize,
+    /// if the entire texture cache is replaced within a single frame, then
+    /// the capacity is doubled, up to this limit. set equal to
+    /// texture_capacity to disable growth
+    pub texture_capacity_limit: NonZeroUsize,
+    /// approximate bytes of texture memory kept. None is unbounded
+    pub texture_byte_budget: Option<usize>,
+    /// number of decoded sounds kept
+    pub audio_capacity: NonZeroUsize,
+    /// approximate bytes of decoded audio kept. None is unbounded
+    pub audio_byte_budget: Option<usize>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            texture_capacity: 16.try_into().unwrap(),
+            texture_capacity_limit: 8192.try_into().unwrap(),
+            texture_byte_budget: None,
+            audio_capacity: 32.try_into().unwrap(),
+            audio_byte_budget: None,
+        }
+    }
+}
+
+/// counters for a single cache. counters accumulate from when the system was
+/// created
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceCacheStats {
+    /// current max number of entries
+    pub capacity: usize,
+    /// current number of entries
+    pub len: usize,
+    /// the resource was requested and already loaded
+    pub hits: u64,
+    /// the resource was requested and had to be loaded
+    pub misses: u64,
+    /// entries pushed out because of the capacity or byte budget. explicit
+    /// evict or clear calls aren't counted
+    pub evictions: u64,
+    /// approximate memory used by the entries
+    pub bytes_resident: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub textures: ResourceCacheStats,
+    /// number of texture entries from image files
+    pub images: usize,
+    /// number of texture entries from rendered text
+    pub text: usize,
+    /// number of texture entries from System::pixels()
+    pub user_pixels: usize,
+    pub audio: ResourceCacheStats,
+    /// number of audio entries from System::samples()
+    pub user_samples: usize,
+}
+
+/// progress of the images and sounds queued for background loading. the
+/// counters restart when something is queued after everything finished
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub queued: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    pub fn done(&self) -> bool {
+        self.loaded + self.failed >= self.queued
+    }
+
+    /// from 0 to 1 inclusively. e.g. for a loading bar
+    pub fn fraction(&self) -> f32 {
+        if self.queued == 0 {
+            return 1.;
+        }
+        (self.loaded + self.failed) as f32 / self.queued as f32
+    }
+}
@@ -0,0 +1,347 @@
+use std::time::Duration;
+
+use super::{
+    shuffle::random_unit,
+    texture_rect::{
+        NonNaNFinite, TextureDestinationF, TextureRect, TextureRectF, TextureRotationF,
+        TextureSourceF,
+    },
+    TextureHandle,
+};
+
+/// an axis aligned area in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WorldRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl WorldRect {
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
+    }
+
+    pub fn intersects(&self, other: &WorldRect) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+}
+
+/// views the world through an area of the screen. world coordinates are
+/// y-down, same as the screen
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// world position shown at the center of the viewport
+    pub x: f32,
+    pub y: f32,
+    /// screen pixels per world unit
+    pub zoom: f32,
+    /// degrees clockwise that the camera is turned. the world appears turned
+    /// the other way
+    pub rotation: f32,
+    /// area of the screen drawn to. the camera doesn't set the clipping rect
+    pub viewport: TextureRect,
+    /// if set, the camera is kept from showing anything outside this area. if
+    /// the area is smaller than what's visible, it's centered instead
+    pub bounds: Option<WorldRect>,
+    /// world units from the camera center, horizontally and vertically, that
+    /// a followed target can move without the camera moving
+    pub dead_zone: (f32, f32),
+    /// how quickly follow() catches up. the fraction of the remaining
+    /// distance covered per second is 1 - e^-rate. zero or less snaps
+    pub follow_rate: f32,
+
+    shake_magnitude: f32,
+    shake_duration: Duration,
+    shake_remaining: Duration,
+    /// screen pixels
+    shake_offset: (f32, f32),
+    seed: u64,
+}
+
+impl Camera {
+    pub fn new(viewport: TextureRect) -> Self {
+        Self {
+            x: 0.,
+            y: 0.,
+            zoom: 1.,
+            rotation: 0.,
+            viewport,
+            bounds: None,
+            dead_zone: (0., 0.),
+            follow_rate: 0.,
+            shake_magnitude: 0.,
+            shake_duration: Duration::ZERO,
+            shake_remaining: Duration::ZERO,
+            shake_offset: (0., 0.),
+            seed: 0,
+        }
+    }
+
+    fn viewport_center(&self) -> (f32, f32) {
+        (
+            self.viewport.x as f32 + self.viewport.w.get() as f32 / 2.,
+            self.viewport.y as f32 + self.viewport.h.get() as f32 / 2.,
+        )
+    }
+
+    /// sin and cos of the rotation applied to the world
+    fn sin_cos(&self) -> (f32, f32) {
+        (-self.rotation).to_radians().sin_cos()
+    }
+
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.sin_cos();
+        let (dx, dy) = (x - self.x, y - self.y);
+        let (rx, ry) = (dx * cos - dy * sin, dx * sin + dy * cos);
+        let (cx, cy) = self.viewport_center();
+        (
+            cx + rx * self.zoom + self.shake_offset.0,
+            cy + ry * self.zoom + self.shake_offset.1,
+        )
+    }
+
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.sin_cos();
+        let (cx, cy) = self.viewport_center();
+        let rx = (x - cx - self.shake_offset.0) / self.zoom;
+        let ry = (y - cy - self.shake_offset.1) / self.zoom;
+        // inverse rotation
+        (self.x + rx * cos + ry * sin, self.y - rx * sin + ry * cos)
+    }
+
+    /// half of the visible world width and height, accounting for rotation
+    fn half_extents(&self) -> (f32, f32) {
+        let hw = self.viewport.w.get() as f32 / 2. / self.zoom;
+        let hh = self.viewport.h.get() as f32 / 2. / self.zoom;
+        let (sin, cos) = self.sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        (hw * cos + hh * sin, hw * sin + hh * cos)
+    }
+
+    /// the bounding box of everything visible. useful for culling
+    pub fn visible_area(&self) -> WorldRect {
+        let (hw, hh) = self.half_extents();
+        WorldRect {
+            x: self.x - hw,
+            y: self.y - hh,
+            w: hw * 2.,
+            h: hh * 2.,
+        }
+    }
+
+    /// move the camera so it stays within the bounds
+    pub fn clamp_to_bounds(&mut self) {
+        let bounds = match self.bounds {
+            Some(v) => v,
+            None => return,
+        };
+        let (hw, hh) = self.half_extents();
+        self.x = clamp_axis(self.x, hw, bounds.x, bounds.w);
+        self.y = clamp_axis(self.y, hh, bounds.y, bounds.h);
+    }
+
+    /// move towards keeping the target within the dead zone
+    pub fn follow(&mut self, target_x: f32, target_y: f32, dt: Duration) {
+        let goal_x = dead_zone_axis(self.x, target_x, self.dead_zone.0);
+        let goal_y = dead_zone_axis(self.y, target_y, self.dead_zone.1);
+        let t = if self.follow_rate <= 0. {
+            1.
+        } else {
+            1. - (-self.follow_rate * dt.as_secs_f32()).exp()
+        };
+        self.x += (goal_x - self.x) * t;
+        self.y += (goal_y - self.y) * t;
+        self.clamp_to_bounds();
+    }
+
+    /// shake the view by up to magnitude screen pixels, fading out over the
+    /// duration. replaces a weaker shake in progress
+    pub fn shake(&mut self, magnitude: f32, duration: Duration) {
+        if magnitude < self.current_shake_magnitude() {
+            return;
+        }
+        self.shake_magnitude = magnitude;
+        self.shake_duration = duration;
+        self.shake_remaining = duration;
+    }
+
+    fn current_shake_magnitude(&self) -> f32 {
+        if self.shake_duration.is_zero() {
+            return 0.;
+        }
+        self.shake_magnitude * self.shake_remaining.as_secs_f32()
+            / self.shake_duration.as_secs_f32()
+    }
+
+    /// advance the screen shake. call once per frame or step
+    pub fn update(&mut self, dt: Duration) {
+        self.shake_remaining = self.shake_remaining.saturating_sub(dt);
+        let magnitude = self.current_shake_magnitude();
+        if magnitude <= 0. {
+            self.shake_offset = (0., 0.);
+            return;
+        }
+        let x = random_unit(&mut self.seed) * 2. - 1.;
+        let y = random_unit(&mut self.seed) * 2. - 1.;
+        self.shake_offset = (x * magnitude, y * magnitude);
+    }
+
+    /// where a world area should be drawn on the screen. None if it has no
+    /// area on the screen
+    pub fn apply(&self, dst: TextureDestinationF) -> Option<TextureDestinationF> {
+        let TextureDestinationF(rect, rotation) = dst;
+        let (x, y, w, h) = (rect.x.get(), rect.y.get(), rect.w.get(), rect.h.get());
+
+        // rotation is about this point, relative to the rect
+        let pivot = rotation
+            .and_then(|r| r.point)
+            .map(|(px, py)| (px.get(), py.get()))
+            .unwrap_or((w / 2., h / 2.));
+        let (sx, sy) = self.world_to_screen(x + pivot.0, y + pivot.1);
+
+        let screen_rect = TextureRectF::new(
+            sx - pivot.0 * self.zoom,
+            sy - pivot.1 * self.zoom,
+            w * self.zoom,
+            h * self.zoom,
+        )?;
+
+        let angle = rotation.map(|r| r.angle.get()).unwrap_or(0.) - self.rotation;
+        let screen_rotation = if rotation.is_none() && angle == 0. {
+            None
+        } else {
+            Some(TextureRotationF {
+                angle: NonNaNFinite::<f32>::new(angle).ok()?,
+                point: Some((
+                    NonNaNFinite::<f32>::new(pivot.0 * self.zoom).ok()?,
+                    NonNaNFinite::<f32>::new(pivot.1 * self.zoom).ok()?,
+                )),
+                flip_horizontal: rotation.map(|r| r.flip_horizontal).unwrap_or(false),
+                flip_vertical: rotation.map(|r| r.flip_vertical).unwrap_or(false),
+            })
+        };
+        Some(TextureDestinationF(screen_rect, screen_rotation))
+    }
+
+    /// draw a texture using world coordinates
+    pub fn copy_f<'system, H, Src, Dst>(
+        &self,
+        texture: &mut H,
+        src: Src,
+        dst: Dst,
+    ) -> Result<(), String>
+    where
+        H: TextureHandle<'system>,
+        Src: Into<TextureSourceF>,
+        Dst: Into<TextureDestinationF>,
+    {
+        match self.apply(dst.into()) {
+            Some(dst) => texture.copy_f(src, dst),
+            None => Ok(()), // zero area
+        }
+    }
+
+    /// wraps a texture so its draws are in world coordinates
+    pub fn texture<'system, H: TextureHandle<'system>>(&self, texture: H) -> CameraTexture<'_, H> {
+        CameraTexture {
+            camera: self,
+            texture,
+        }
+    }
+}
+
+/// a texture drawn through a camera
+pub struct CameraTexture<'camera, H> {
+    pub camera: &'camera Camera,
+    pub texture: H,
+}
+
+impl<'camera, H> CameraTexture<'camera, H> {
+    /// copy texture to window, in world coordinates. applies alpha blending
+    pub fn copy_f<'system, Src, Dst>(&mut self, src: Src, dst: Dst) -> Result<(), String>
+    where
+        H: TextureHandle<'system>,
+        Src: Into<TextureSourceF>,
+        Dst: Into<TextureDestinationF>,
+    {
+        self.camera.copy_f(&mut self.texture, src, dst)
+    }
+}
+
+/// position along one axis which keeps [pos - half, pos + half] inside
+/// [start, start + len]
+fn clamp_axis(pos: f32, half: f32, start: f32, len: f32) -> f32 {
+    if half * 2. >= len {
+        return start + len / 2.;
+    }
+    pos.clamp(start + half, start + len - half)
+}
+
+/// position along one axis which puts the target at most dead_zone away
+fn dead_zone_axis(pos: f32, target: f32, dead_zone: f32) -> f32 {
+    let dead_zone = dead_zone.max(0.);
+    if target > pos + dead_zone {
+        target - dead_zone
+    } else if target < pos - dead_zone {
+        target + dead_zone
+    } else {
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    fn camera() -> Camera {
+        Camera::new(TextureRect::new(0, 0, 200, 100).unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut camera = camera();
+        camera.x = 10.;
+        camera.y = -5.;
+        camera.zoom = 2.;
+        assert_eq!(camera.world_to_screen(10., -5.), (100., 50.));
+        assert_eq!(camera.world_to_screen(11., -5.), (102., 50.));
+
+        camera.rotation = 90.;
+        // turning the camera clockwise turns the world counter clockwise
+        let (x, y) = camera.world_to_screen(11., -5.);
+        assert!(close(x, 100.) && close(y, 48.));
+
+        let (x, y) = camera.screen_to_world(37., 81.);
+        let (x, y) = camera.world_to_screen(x, y);
+        assert!(close(x, 37.) && close(y, 81.));
+    }
+
+    #[test]
+    fn test_follow() {
+        let mut camera = camera();
+        camera.dead_zone = (10., 10.);
+        camera.follow(5., -8., Duration::from_millis(16));
+        assert_eq!((camera.x, camera.y), (0., 0.));
+        camera.follow(25., 0., Duration::from_millis(16));
+        assert_eq!((camera.x, camera.y), (15., 0.));
+
+        camera.bounds = Some(WorldRect {
+            x: 0.,
+            y: 0.,
+            w: 1000.,
+            h: 50.,
+        });
+        camera.follow(1000., 0., Duration::from_millis(16));
+        // view is 200 wide, and taller than the bounds
+        assert_eq!((camera.x, camera.y), (900., 25.));
+    }
+}
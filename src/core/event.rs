@@ -1,3 +1,5 @@
+use std::{any::Any, sync::Arc};
+
 /// window size change. indicates the new size
 #[derive(Debug, Clone, Copy)]
 pub struct Window {
@@ -25,12 +27,104 @@ pub struct MouseWheelEvent {
     pub wheel_dy: i32,
 }
 
+/// modifier keys held during a key event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyMods {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// windows / command key
+    pub gui: bool,
+}
+
+impl KeyMods {
+    /// ctrl, or command on mac. for shortcuts like copy and paste
+    pub fn shortcut(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.gui
+        } else {
+            self.ctrl
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct KeyEvent {
-    /// the key that was typed, accounting for keyboard layout
+    /// the key that was typed, accounting for keyboard layout. shift is
+    /// already applied (e.g. 'A' rather than 'a')
     pub key: u8,
     /// indicates if this key is up or down
     pub down: bool,
+    pub mods: KeyMods,
+}
+
+/// keys which aren't ascii, used to move around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavKey {
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NavKeyEvent {
+    pub key: NavKey,
+    /// indicates if this key is up or down
+    pub down: bool,
+    pub mods: KeyMods,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ControllerButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// more buttons might be mapped later
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ControllerAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerButtonEvent {
+    /// distinguishes connected controllers
+    pub controller: u32,
+    pub button: ControllerButton,
+    pub down: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerAxisEvent {
+    /// distinguishes connected controllers
+    pub controller: u32,
+    pub axis: ControllerAxis,
+    /// from -1 to 1. down and right are positive. triggers are from 0 to 1
+    pub value: f32,
 }
 
 pub(crate) fn ascii_more_to_upper(i: u8) -> u8 {
@@ -63,7 +157,40 @@ pub(crate) fn ascii_more_to_upper(i: u8) -> u8 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// a payload sent from another thread. see EventSender
+pub type UserPayload = Arc<dyn Any + Send + Sync>;
+
+/// pushes Event::User from any thread, waking a loop that is waiting for
+/// events. see System::event_sender()
+#[derive(Clone)]
+pub struct EventSender {
+    push: Arc<dyn Fn(UserPayload) -> Result<(), String> + Send + Sync>,
+}
+
+impl std::fmt::Debug for EventSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventSender")
+    }
+}
+
+impl EventSender {
+    /// for backends. push gives the payload to the event queue
+    pub fn new<F>(push: F) -> Self
+    where
+        F: Fn(UserPayload) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            push: Arc::new(push),
+        }
+    }
+
+    /// fails if the system was dropped
+    pub fn send<T: Any + Send + Sync>(&self, payload: T) -> Result<(), String> {
+        (self.push)(Arc::new(payload))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Event {
     /// more variants might be added. this is a forward compatibility
     /// placeholder!
@@ -73,4 +200,21 @@ pub enum Event {
     Mouse(MouseEvent),
     MouseWheel(MouseWheelEvent),
     Key(KeyEvent),
+    NavKey(NavKeyEvent),
+    /// text typed, after the keyboard layout and input method. a key press
+    /// can give both a Key event and a Text event
+    Text(String),
+    ControllerButton(ControllerButtonEvent),
+    ControllerAxis(ControllerAxisEvent),
+    /// an asset file changed on disk and was evicted from the cache. anything
+    /// drawn from it should be redrawn. only produced with the dev feature
+    AssetReload,
+    /// the playlist moved to another track or ended. see System::playlist()
+    TrackChanged,
+    /// the audio device was lost (e.g. unplugged) or became available again,
+    /// and audio was reopened. music from System::music() must be started
+    /// again, but the playlist continues
+    AudioDeviceChanged,
+    /// from an EventSender. use downcast_ref() on the payload
+    User(UserPayload),
 }
@@ -0,0 +1,529 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use super::event::{ascii_more_to_upper, ControllerAxis, ControllerButton, Event};
+
+/// a physical input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    /// matched regardless of shift
+    Key(u8),
+    /// the primary mouse button
+    MouseLeft,
+    /// an axis. each wheel step counts for the frame it happened in
+    MouseWheelX,
+    MouseWheelY,
+    /// from any controller
+    ControllerButton(ControllerButton),
+    /// from any controller
+    ControllerAxis(ControllerAxis),
+}
+
+/// an input which triggers an action
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Binding {
+    pub input: Input,
+    /// the input's value is multiplied by this. e.g. -1 for a key which moves
+    /// left on an axis action
+    pub scale: f32,
+}
+
+impl From<Input> for Binding {
+    fn from(input: Input) -> Self {
+        Self { input, scale: 1. }
+    }
+}
+
+const BUTTON_NAMES: [(ControllerButton, &str); 16] = [
+    (ControllerButton::A, "a"),
+    (ControllerButton::B, "b"),
+    (ControllerButton::X, "x"),
+    (ControllerButton::Y, "y"),
+    (ControllerButton::Back, "back"),
+    (ControllerButton::Guide, "guide"),
+    (ControllerButton::Start, "start"),
+    (ControllerButton::LeftStick, "left_stick"),
+    (ControllerButton::RightStick, "right_stick"),
+    (ControllerButton::LeftShoulder, "left_shoulder"),
+    (ControllerButton::RightShoulder, "right_shoulder"),
+    (ControllerButton::DPadUp, "dpad_up"),
+    (ControllerButton::DPadDown, "dpad_down"),
+    (ControllerButton::DPadLeft, "dpad_left"),
+    (ControllerButton::DPadRight, "dpad_right"),
+    (ControllerButton::Other, "other"),
+];
+
+const AXIS_NAMES: [(ControllerAxis, &str); 6] = [
+    (ControllerAxis::LeftX, "left_x"),
+    (ControllerAxis::LeftY, "left_y"),
+    (ControllerAxis::RightX, "right_x"),
+    (ControllerAxis::RightY, "right_y"),
+    (ControllerAxis::TriggerLeft, "trigger_left"),
+    (ControllerAxis::TriggerRight, "trigger_right"),
+];
+
+fn name_of<T: PartialEq + Copy>(names: &[(T, &'static str)], value: T) -> &'static str {
+    names
+        .iter()
+        .find(|(v, _)| *v == value)
+        .map(|(_, n)| *n)
+        .unwrap()
+}
+
+fn from_name<T: Copy>(names: &[(T, &'static str)], name: &str) -> Option<T> {
+    names.iter().find(|(_, n)| *n == name).map(|(v, _)| *v)
+}
+
+impl Input {
+    /// keys are compared without shift
+    fn normalized(self) -> Self {
+        match self {
+            Input::Key(key) => Input::Key(ascii_more_to_upper(key)),
+            v => v,
+        }
+    }
+
+    /// as written in the config. e.g. "key a", "key 32", "button start"
+    fn to_config(self) -> String {
+        match self {
+            Input::Key(key) => {
+                if key.is_ascii_graphic() && !key.is_ascii_digit() {
+                    format!("key {}", key as char)
+                } else {
+                    format!("key {}", key)
+                }
+            }
+            Input::MouseLeft => "mouse left".to_owned(),
+            Input::MouseWheelX => "wheel x".to_owned(),
+            Input::MouseWheelY => "wheel y".to_owned(),
+            Input::ControllerButton(b) => format!("button {}", name_of(&BUTTON_NAMES, b)),
+            Input::ControllerAxis(a) => format!("axis {}", name_of(&AXIS_NAMES, a)),
+        }
+    }
+
+    fn from_config(kind: &str, name: &str) -> Option<Self> {
+        Some(match (kind, name) {
+            ("key", name) => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_graphic() && !c.is_ascii_digit() => {
+                        Input::Key(c as u8)
+                    }
+                    _ => Input::Key(name.parse().ok()?),
+                }
+            }
+            ("mouse", "left") => Input::MouseLeft,
+            ("wheel", "x") => Input::MouseWheelX,
+            ("wheel", "y") => Input::MouseWheelY,
+            ("button", name) => Input::ControllerButton(from_name(&BUTTON_NAMES, name)?),
+            ("axis", name) => Input::ControllerAxis(from_name(&AXIS_NAMES, name)?),
+            _ => return None,
+        })
+    }
+}
+
+/// turns events into named actions, e.g. "jump", "confirm", or "move_x" as an
+/// axis. an action can have several bindings
+///
+/// update() is called once per frame with the frame's events. then actions
+/// are queried
+#[derive(Debug, Clone)]
+pub struct InputMap {
+    bindings: BTreeMap<String, Vec<Binding>>,
+    /// controller axes closer to 0 than this are 0
+    pub deadzone: f32,
+    /// an action is held once its value's magnitude reaches this
+    pub threshold: f32,
+
+    keys: BTreeSet<u8>,
+    mouse_left: bool,
+    /// by controller
+    buttons: BTreeSet<(u32, ControllerButton)>,
+    axes: BTreeMap<(u32, ControllerAxis), f32>,
+    /// this frame
+    wheel: (i32, i32),
+    /// inputs that went down or up this frame, so a quick tap is still seen
+    went_down: Vec<Input>,
+    went_up: Vec<Input>,
+    /// held state of each action at the end of the previous frame
+    previous: BTreeSet<String>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: Default::default(),
+            deadzone: 0.2,
+            threshold: 0.5,
+            keys: Default::default(),
+            mouse_left: false,
+            buttons: Default::default(),
+            axes: Default::default(),
+            wheel: (0, 0),
+            went_down: Default::default(),
+            went_up: Default::default(),
+            previous: Default::default(),
+        }
+    }
+}
+
+impl InputMap {
+    pub fn bind<B: Into<Binding>>(&mut self, action: &str, binding: B) {
+        self.bindings
+            .entry(action.to_owned())
+            .or_default()
+            .push(binding.into());
+    }
+
+    /// remove all of the action's bindings
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.bindings
+            .get(action)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(|k| k.as_str())
+    }
+
+    /// once per frame. events are read but not consumed
+    pub fn update(&mut self, events: &[Option<Event>]) {
+        self.previous = self
+            .bindings
+            .keys()
+            .filter(|action| self.held(action))
+            .cloned()
+            .collect();
+        self.wheel = (0, 0);
+        self.went_down.clear();
+        self.went_up.clear();
+
+        for e in events.iter().flatten() {
+            let (input, down) = match e {
+                Event::Key(k) => {
+                    let key = ascii_more_to_upper(k.key);
+                    let changed = if k.down {
+                        self.keys.insert(key)
+                    } else {
+                        self.keys.remove(&key)
+                    };
+                    if !changed {
+                        continue; // key repeat
+                    }
+                    (Input::Key(key), k.down)
+                }
+                Event::Mouse(m) => {
+                    if !m.changed {
+                        continue;
+                    }
+                    self.mouse_left = m.down;
+                    (Input::MouseLeft, m.down)
+                }
+                Event::MouseWheel(w) => {
+                    self.wheel.0 += w.wheel_dx;
+                    self.wheel.1 += w.wheel_dy;
+                    continue;
+                }
+                Event::ControllerButton(b) => {
+                    if b.down {
+                        self.buttons.insert((b.controller, b.button));
+                    } else {
+                        self.buttons.remove(&(b.controller, b.button));
+                    }
+                    (Input::ControllerButton(b.button), b.down)
+                }
+                Event::ControllerAxis(a) => {
+                    self.axes.insert((a.controller, a.axis), a.value);
+                    continue;
+                }
+                _ => continue,
+            };
+            if down {
+                self.went_down.push(input);
+            } else {
+                self.went_up.push(input);
+            }
+        }
+    }
+
+    /// the input's current value, from -1 to 1
+    fn input_value(&self, input: Input) -> f32 {
+        let button = |held: bool| if held { 1. } else { 0. };
+        match input {
+            Input::Key(key) => button(self.keys.contains(&ascii_more_to_upper(key))),
+            Input::MouseLeft => button(self.mouse_left),
+            Input::MouseWheelX => self.wheel.0.clamp(-1, 1) as f32,
+            Input::MouseWheelY => self.wheel.1.clamp(-1, 1) as f32,
+            Input::ControllerButton(b) => button(self.buttons.iter().any(|(_, held)| *held == b)),
+            Input::ControllerAxis(axis) => self
+                .axes
+                .iter()
+                .filter(|((_, a), _)| *a == axis)
+                .map(|(_, v)| *v)
+                .filter(|v| v.abs() >= self.deadzone)
+                // the controller pushed furthest
+                .fold(0., |acc: f32, v| if v.abs() > acc.abs() { v } else { acc }),
+        }
+    }
+
+    /// from -1 to 1. the sum of the action's bindings
+    pub fn value(&self, action: &str) -> f32 {
+        self.bindings(action)
+            .iter()
+            .map(|b| self.input_value(b.input) * b.scale)
+            .sum::<f32>()
+            .clamp(-1., 1.)
+    }
+
+    pub fn held(&self, action: &str) -> bool {
+        self.value(action).abs() >= self.threshold
+    }
+
+    /// this frame
+    pub fn pressed(&self, action: &str) -> bool {
+        let tapped = self
+            .bindings(action)
+            .iter()
+            .any(|b| self.went_down.contains(&b.input.normalized()));
+        tapped || (self.held(action) && !self.previous.contains(action))
+    }
+
+    /// this frame
+    pub fn released(&self, action: &str) -> bool {
+        let held = self.held(action);
+        let lifted = self
+            .bindings(action)
+            .iter()
+            .any(|b| self.went_up.contains(&b.input.normalized()));
+        !held && (lifted || self.previous.contains(action))
+    }
+
+    /// if the event is a key or controller button bound to the action, whether
+    /// it's down. e.g. for a widget's hotkey. mouse bindings are ignored, since
+    /// a click is positional and belongs to whatever is under it
+    pub fn matches(&self, action: &str, event: &Event) -> Option<bool> {
+        let (input, down) = match event {
+            Event::Key(k) => (Input::Key(ascii_more_to_upper(k.key)), k.down),
+            Event::ControllerButton(b) => (Input::ControllerButton(b.button), b.down),
+            _ => return None,
+        };
+        let bound = self
+            .bindings(action)
+            .iter()
+            .any(|b| b.input.normalized() == input);
+        if bound {
+            Some(down)
+        } else {
+            None
+        }
+    }
+
+    /// one binding per line: action, input, then scale if it isn't 1
+    ///
+    /// ```text
+    /// jump key 32
+    /// move_x key a -1
+    /// move_x axis left_x
+    /// confirm button a
+    /// ```
+    pub fn save_bindings(&self) -> String {
+        let mut ret = String::new();
+        for (action, bindings) in self.bindings.iter() {
+            for binding in bindings {
+                ret += &format!("{} {}", action, binding.input.to_config());
+                if binding.scale != 1. {
+                    ret += &format!(" {}", binding.scale);
+                }
+                ret += "\n";
+            }
+        }
+        ret
+    }
+
+    /// replaces all bindings. see save_bindings(). blank lines and lines
+    /// starting with # are ignored
+    pub fn load_bindings(&mut self, text: &str) -> Result<(), String> {
+        let mut bindings: BTreeMap<String, Vec<Binding>> = Default::default();
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = || format!("line {}: invalid binding \"{}\"", line_number + 1, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (action, kind, name, scale) = match parts.as_slice() {
+                [action, kind, name] => (*action, *kind, *name, 1.),
+                [action, kind, name, scale] => {
+                    (*action, *kind, *name, scale.parse().map_err(|_| err())?)
+                }
+                _ => return Err(err()),
+            };
+            let input = Input::from_config(kind, name).ok_or_else(err)?;
+            bindings
+                .entry(action.to_owned())
+                .or_default()
+                .push(Binding { input, scale });
+        }
+        self.bindings = bindings;
+        Ok(())
+    }
+
+    pub fn save_bindings_file(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.save_bindings()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn load_bindings_file(&mut self, path: &Path) -> Result<(), String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.load_bindings(&text)
+    }
+}
+
+/// an action used as a widget's hotkey. the map is shared so it can be rebound
+/// while the widget exists
+#[derive(Debug, Clone)]
+pub struct Hotkey<'state> {
+    pub map: &'state RefCell<InputMap>,
+    pub action: String,
+}
+
+impl<'state> Hotkey<'state> {
+    pub fn new(map: &'state RefCell<InputMap>, action: &str) -> Self {
+        Self {
+            map,
+            action: action.to_owned(),
+        }
+    }
+
+    /// see InputMap::matches()
+    pub fn matches(&self, event: &Event) -> Option<bool> {
+        self.map.borrow().matches(&self.action, event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::{ControllerAxisEvent, KeyEvent, MouseEvent};
+
+    fn key(key: u8, down: bool) -> Option<Event> {
+        Some(Event::Key(KeyEvent {
+            key,
+            down,
+            mods: Default::default(),
+        }))
+    }
+
+    #[test]
+    fn test_pressed_held_released() {
+        let mut map = InputMap::default();
+        map.bind("jump", Input::Key(b' '));
+        map.bind("jump", Input::ControllerButton(ControllerButton::A));
+
+        map.update(&[key(b' ', true)]);
+        assert!(map.pressed("jump") && map.held("jump") && !map.released("jump"));
+
+        map.update(&[key(b' ', true)]); // repeat
+        assert!(!map.pressed("jump") && map.held("jump"));
+
+        map.update(&[key(b' ', false)]);
+        assert!(map.released("jump") && !map.held("jump"));
+
+        // tapped within one frame
+        map.update(&[key(b' ', true), key(b' ', false)]);
+        assert!(map.pressed("jump") && map.released("jump") && !map.held("jump"));
+
+        // letters match regardless of shift
+        map.bind("fire", Input::Key(b'f'));
+        map.update(&[key(b'F', true), key(b'F', false)]);
+        assert!(map.pressed("fire") && map.released("fire"));
+    }
+
+    #[test]
+    fn test_axis() {
+        let mut map = InputMap::default();
+        map.bind(
+            "move_x",
+            Binding {
+                input: Input::Key(b'a'),
+                scale: -1.,
+            },
+        );
+        map.bind("move_x", Input::Key(b'd'));
+        map.bind("move_x", Input::ControllerAxis(ControllerAxis::LeftX));
+
+        map.update(&[key(b'A', true)]); // with shift
+        assert_eq!(map.value("move_x"), -1.);
+        map.update(&[key(b'd', true)]);
+        assert_eq!(map.value("move_x"), 0.);
+        map.update(&[key(b'a', false), key(b'd', false)]);
+
+        let axis = |value| {
+            Some(Event::ControllerAxis(ControllerAxisEvent {
+                controller: 0,
+                axis: ControllerAxis::LeftX,
+                value,
+            }))
+        };
+        map.update(&[axis(0.1)]);
+        assert_eq!(map.value("move_x"), 0.); // deadzone
+        map.update(&[axis(0.75)]);
+        assert_eq!(map.value("move_x"), 0.75);
+        assert!(map.pressed("move_x"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut map = InputMap::default();
+        map.bind("confirm", Input::Key(b'e'));
+        map.bind("confirm", Input::MouseLeft);
+        assert_eq!(
+            map.matches("confirm", &key(b'E', true).unwrap()),
+            Some(true)
+        );
+        assert_eq!(map.matches("cancel", &key(b'e', true).unwrap()), None);
+        let click = Event::Mouse(MouseEvent {
+            x: 0,
+            y: 0,
+            down: false,
+            changed: true,
+        });
+        assert_eq!(map.matches("confirm", &click), None);
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut map = InputMap::default();
+        map.bind("jump", Input::Key(b' '));
+        map.bind("jump", Input::Key(b'1'));
+        map.bind(
+            "move_x",
+            Binding {
+                input: Input::Key(b'a'),
+                scale: -1.,
+            },
+        );
+        map.bind("confirm", Input::ControllerButton(ControllerButton::Start));
+        map.bind("confirm", Input::MouseLeft);
+        let text = map.save_bindings();
+
+        let mut loaded = InputMap::default();
+        loaded.load_bindings(&text).unwrap();
+        assert_eq!(loaded.save_bindings(), text);
+        assert_eq!(loaded.bindings("jump"), map.bindings("jump"));
+        assert_eq!(loaded.bindings("move_x")[0].scale, -1.);
+
+        assert!(loaded.load_bindings("jump key").is_err());
+        assert!(loaded.load_bindings("jump button nope").is_err());
+        // unchanged on error
+        assert_eq!(loaded.save_bindings(), text);
+    }
+}
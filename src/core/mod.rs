@@ -1,8 +1,18 @@
+pub mod audio;
 pub mod backends;
+pub mod cache;
+pub mod camera;
 pub mod clipping_rect;
 pub mod color;
 pub mod event;
+pub mod input;
+pub mod particles;
+pub mod playlist;
+pub(crate) mod shuffle;
+pub mod sound_set;
+pub mod spatial_audio;
 pub mod texture_rect;
+pub mod tilemap;
 
 use std::num::NonZeroU16;
 use std::num::NonZeroU32;
@@ -10,10 +20,24 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use audio::AudioOutput;
+use audio::Bus;
+use audio::Ducking;
+use audio::Priority;
+use audio::Samples;
+use audio::Snapshot;
+use audio::SoundHandle;
+use audio::StreamFormat;
+use cache::CacheConfig;
+use cache::CacheStats;
+use cache::LoadProgress;
 use clipping_rect::ClippingRect;
 use color::Color;
 use color::Surface;
 use event::Event;
+use event::EventSender;
+use playlist::Playlist;
+use sound_set::{SoundSet, Variation};
 use texture_rect::TextureDestination;
 use texture_rect::TextureDestinationF;
 use texture_rect::TextureRect;
@@ -37,6 +61,10 @@ pub trait TextureHandle<'system>: Sized {
         Src: Into<TextureSourceF>,
         Dst: Into<TextureDestinationF>;
 
+    /// multiply the color and alpha of copies made with this handle. white
+    /// is unchanged. it doesn't outlive the handle
+    fn tint(&mut self, color: Color) -> Result<(), String>;
+
     /// get the size of this texture; width, height.
     fn size(&self) -> Result<(NonZeroU32, NonZeroU32), String>;
 
@@ -117,6 +145,18 @@ pub trait System: Sized {
     where
         P: Into<PathLike<'a>>;
 
+    /// non blocking version of image(). if the texture isn't loaded yet, then
+    /// it's queued (see queue_image()) and None is returned
+    ///
+    /// if a queued load failed then the error is returned once, and the next
+    /// call queues it again
+    fn try_image<'a, P>(
+        &mut self,
+        image_path: P,
+    ) -> Result<Option<Self::ImageTextureHandle<'_>>, String>
+    where
+        P: Into<PathLike<'a>>;
+
     /// render text or reuse from (unspecified) cache
     ///
     /// there is no guarantee that the provided point size will be the one that
@@ -155,9 +195,15 @@ pub trait System: Sized {
 
     /// non blocking
     ///
-    /// load sound from file or reuse from cache and play it. the backend may
-    /// choose to silently do nothing, for example if too many sounds are
-    /// playing concurrently
+    /// load sound from file or reuse from cache and play it. returns None if
+    /// the sound wasn't played, for example if every sound channel is busy
+    /// with sounds of the same or higher priority
+    ///
+    /// if every channel is busy, the lowest priority sound is stopped to make
+    /// room, preferring the quietest and then the oldest. looping sounds are
+    /// never stopped this way
+    ///
+    /// the sound's volume is controlled by its bus
     ///
     /// direction: from 0 to 1. 0 is north, increasing rotates clockwise. 1
     /// wraps back to north
@@ -167,13 +213,103 @@ pub trait System: Sized {
     fn sound<'a, 's, P>(
         &'s mut self,
         sound: P,
+        bus: Bus,
+        priority: Priority,
         direction: f32,
         distance: f32,
-    ) -> Result<(), String>
+    ) -> Result<Option<SoundHandle>, String>
     where
         P: Into<PathLike<'a>>,
         's: 'a;
 
+    /// non blocking
+    ///
+    /// same as sound(), but with a different volume and speed (pitch) for this
+    /// play only
+    #[allow(clippy::too_many_arguments)]
+    fn sound_variation<'a, 's, P>(
+        &'s mut self,
+        sound: P,
+        variation: Variation,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        P: Into<PathLike<'a>>,
+        's: 'a;
+
+    /// non blocking
+    ///
+    /// play the next sound from the set, varied by the set's jitter. otherwise
+    /// the same as sound()
+    fn sound_set(
+        &mut self,
+        set: &mut SoundSet,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String> {
+        match set.next_variation() {
+            Some((sound, variation)) => {
+                self.sound_variation(sound, variation, bus, priority, direction, distance)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// non blocking
+    ///
+    /// the audio equivalent of pixels(). the samples are generated once and
+    /// cached by key, then played the same way as sound()
+    fn samples<'a, K, G>(
+        &mut self,
+        key: K,
+        generation_function: G,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        K: Into<BytesLike<'a>>,
+        G: Fn(&mut Self) -> Result<Samples, String>;
+
+    /// non blocking
+    ///
+    /// play audio produced as it's needed, e.g. by a synthesizer. the generator
+    /// is called from the audio thread and fills interleaved samples from -1 to
+    /// 1, in the given output format. it plays until stopped with stop_sound()
+    ///
+    /// otherwise the same as sound()
+    fn stream_sound<F>(
+        &mut self,
+        generator: F,
+        bus: Bus,
+        priority: Priority,
+        direction: f32,
+        distance: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        F: FnMut(&mut [f32], StreamFormat) + Send + 'static;
+
+    /// non blocking
+    ///
+    /// stop a sound from sound() (or similar), fading it out if a duration is given. does
+    /// nothing if it already ended
+    fn stop_sound(&mut self, handle: SoundHandle, fade_out_duration: Option<Duration>);
+
+    /// if the sound from sound() (or similar) hasn't ended yet
+    fn sound_playing(&self, handle: SoundHandle) -> bool;
+
+    /// how many sounds (including looping sounds) can play at once. sounds
+    /// beyond the new count are stopped
+    fn set_sound_channels(&mut self, count: usize);
+
+    fn sound_channels(&self) -> usize;
+
     /// non blocking
     ///
     /// load sound from file or reuse from cache and play it looping forever.
@@ -186,10 +322,11 @@ pub trait System: Sized {
     /// fade_in_duration, if set, will only be applied if this looping sound
     /// just started playing
     ///
-    /// direction and distance is described in sound()
+    /// bus, direction and distance is described in sound()
     fn loop_sound<'a>(
         &mut self,
         handle: &mut Self::LoopingSoundHandle<'a>,
+        bus: Bus,
         direction: f32,
         distance: f32,
         fade_in_duration: Option<Duration>,
@@ -225,13 +362,156 @@ pub trait System: Sized {
         's: 'a;
 
     /// non blocking
+    ///
+    /// also stops the playlist
     fn stop_music(&mut self, fade_out_duration: Option<Duration>) -> Result<(), String>;
 
-    /// from 0 to 1 inclusively
-    fn set_music_volume(&mut self, volume: f32);
+    /// tracks played by play_playlist(). use this to queue tracks, or set the
+    /// shuffle and repeat modes
+    fn playlist(&mut self) -> &mut Playlist;
+
+    /// non blocking
+    ///
+    /// play the playlist from the first track, replacing any music. an
+    /// Event::TrackChanged is produced each time the track changes
+    ///
+    /// tracks are decoded fully into memory (about 10MB per minute of 16 bit
+    /// stereo), so tracks longer than 10 minutes give an error. use music()
+    /// for longer ones
+    fn play_playlist(&mut self) -> Result<(), String>;
+
+    /// non blocking
+    ///
+    /// go to the next track now, ignoring RepeatMode::One
+    fn skip_track(&mut self) -> Result<(), String>;
+
+    /// how long the current track fades out while the next fades in. zero
+    /// plays tracks back to back
+    fn set_crossfade(&mut self, duration: Duration);
+
+    /// pauses music and the playlist
+    fn pause_music(&mut self);
+
+    fn resume_music(&mut self) -> Result<(), String>;
+
+    fn music_paused(&self) -> bool;
+
+    /// elapsed and total duration of the current playlist track
+    fn track_position(&self) -> Option<(Duration, Duration)>;
+
+    /// jump to a position in the current playlist track
+    fn seek_track(&mut self, position: Duration) -> Result<(), String>;
+
+    /// from 0 to 1 inclusively. same as the volume of Bus::MUSIC
+    fn set_music_volume(&mut self, volume: f32) {
+        self.set_bus_volume(Bus::MUSIC, volume);
+    }
 
     /// from 0 to 1 inclusively
-    fn music_volume(&self) -> f32;
+    fn music_volume(&self) -> f32 {
+        self.bus_volume(Bus::MUSIC)
+    }
+
+    /// from 0 to 1 inclusively. applies immediately to sounds playing on the
+    /// bus. Bus::MASTER applies to every bus
+    fn set_bus_volume(&mut self, bus: Bus, volume: f32);
+
+    /// from 0 to 1 inclusively. buses start at full volume
+    fn bus_volume(&self, bus: Bus) -> f32;
+
+    /// muting doesn't change the bus volume
+    fn set_bus_muted(&mut self, bus: Bus, muted: bool);
+
+    fn bus_muted(&self, bus: Bus) -> bool;
+
+    /// change the bus volume gradually. like other mixer changes over time,
+    /// this progresses each frame with present(). see mix_animating()
+    fn fade_bus_volume(&mut self, bus: Bus, volume: f32, duration: Duration);
+
+    /// store a snapshot under a name, replacing any with the same name
+    fn add_snapshot(&mut self, name: &str, snapshot: Snapshot);
+
+    /// blend the bus volumes to the named snapshot, e.g. when pausing or going
+    /// underwater
+    fn apply_snapshot(&mut self, name: &str, duration: Duration) -> Result<(), String>;
+
+    /// dip other buses while a bus is playing, e.g. music during dialogue
+    fn add_ducking(&mut self, ducking: Ducking);
+
+    fn clear_ducking(&mut self);
+
+    /// true while bus fades, snapshots or ducking are still changing volumes.
+    /// these only progress with present(), so frames must keep being presented
+    /// meanwhile. gui_loop does this
+    fn mix_animating(&self) -> bool;
+
+    /// names of the audio output devices
+    fn audio_devices(&self) -> Vec<String>;
+
+    /// reopen audio on a different output. everything playing is stopped,
+    /// except the playlist which continues. if the device can't be opened then
+    /// the default device is used instead
+    ///
+    /// if no device can be opened then audio is unavailable (see
+    /// audio_available()) and the error is returned. it's retried when a
+    /// device is plugged in
+    fn set_audio_output(&mut self, output: AudioOutput) -> Result<(), String>;
+
+    /// the output last requested. initially AudioOutput::Default
+    fn audio_output(&self) -> AudioOutput;
+
+    /// false if audio is disabled or no device could be opened, in which case
+    /// audio calls do nothing except check that files exist. the system is
+    /// still created without an audio device
+    fn audio_available(&self) -> bool;
+
+    /// why no device could be opened, the last time one was. None if audio is
+    /// available or disabled
+    fn audio_error(&self) -> Option<&str>;
+
+    /// entries may be evicted immediately if the new config is smaller
+    fn set_cache_config(&mut self, config: CacheConfig);
+
+    fn cache_config(&self) -> CacheConfig;
+
+    fn cache_stats(&self) -> CacheStats;
+
+    /// load image into the cache without drawing it. e.g. for loading screens
+    fn preload_image<'a, P>(&mut self, image_path: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>;
+
+    /// load sound into the cache without playing it
+    fn preload_sound<'a, P>(&mut self, sound: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>;
+
+    /// remove the image and sound loaded from this path from the cache, if
+    /// present. a sound which is currently playing continues to play
+    fn evict<'a, P>(&mut self, path: P)
+    where
+        P: Into<PathLike<'a>>;
+
+    /// remove everything from the cache. e.g. between levels
+    fn clear_cache(&mut self);
+
+    /// non blocking
+    ///
+    /// read and decode the image on a background thread. it's put in the
+    /// cache over the following frames (during present()). see load_progress()
+    fn queue_image<'a, P>(&mut self, image_path: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>;
+
+    /// non blocking
+    ///
+    /// see queue_image()
+    fn queue_sound<'a, P>(&mut self, sound: P) -> Result<(), String>
+    where
+        P: Into<PathLike<'a>>;
+
+    /// for loading screens
+    fn load_progress(&self) -> LoadProgress;
 
     /// receive input from the user. wait forever until that happens
     fn event(&mut self) -> Event;
@@ -239,6 +519,14 @@ pub trait System: Sized {
     /// receive input from the user. wait a max amount of time to wait in
     /// milliseconds
     fn event_timeout(&mut self, timeout: Duration) -> Option<Event>;
+
+    /// for other threads to send Event::User, which wakes event()
+    fn event_sender(&self) -> EventSender;
+
+    /// text on the system clipboard. empty if there is none
+    fn clipboard(&mut self) -> Result<String, String>;
+
+    fn set_clipboard(&mut self, text: &str) -> Result<(), String>;
 }
 
 // =============================================================================
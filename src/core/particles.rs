@@ -0,0 +1,430 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    camera::Camera,
+    color::Color,
+    shuffle::random_unit,
+    texture_rect::{
+        NonNaNFinite, TextureDestinationF, TextureRect, TextureRectF, TextureRotationF,
+        TextureSource, TextureSourceF,
+    },
+    PathLike, System, TextureHandle,
+};
+
+/// can be interpolated by a Curve
+pub trait Lerp: Copy {
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let c = |a: u8, b: u8| (a as f32).lerp(b as f32, t).round() as u8;
+        Color {
+            r: c(self.r, other.r),
+            g: c(self.g, other.g),
+            b: c(self.b, other.b),
+            a: c(self.a, other.a),
+        }
+    }
+}
+
+/// a value over a particle's life
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve<V: Lerp> {
+    /// (life from 0 to 1, value), sorted by life. linear between keys
+    pub keys: Vec<(f32, V)>,
+}
+
+impl<V: Lerp> Curve<V> {
+    pub fn constant(v: V) -> Self {
+        Self {
+            keys: vec![(0., v)],
+        }
+    }
+
+    pub fn linear(start: V, end: V) -> Self {
+        Self {
+            keys: vec![(0., start), (1., end)],
+        }
+    }
+
+    /// None if there are no keys
+    pub fn sample(&self, t: f32) -> Option<V> {
+        let after = self.keys.iter().position(|(k, _)| *k > t);
+        match after {
+            None => self.keys.last().map(|(_, v)| *v),
+            Some(0) => Some(self.keys[0].1),
+            Some(i) => {
+                let (t0, v0) = self.keys[i - 1];
+                let (t1, v1) = self.keys[i];
+                Some(v0.lerp(v1, (t - t0) / (t1 - t0)))
+            }
+        }
+    }
+}
+
+/// how a particle's atlas frame is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameSelection {
+    /// one frame, chosen at random when emitted
+    #[default]
+    Random,
+    /// play through the frames over the particle's life
+    OverLife,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    /// units per second
+    pub vx: f32,
+    pub vy: f32,
+    /// degrees clockwise
+    pub rotation: f32,
+    /// degrees per second
+    pub spin: f32,
+    /// seconds
+    pub age: f32,
+    /// seconds
+    pub lifetime: f32,
+    pub frame: usize,
+}
+
+impl Particle {
+    /// from 0 to 1
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0., 1.)
+    }
+}
+
+/// emits and simulates particles. ranges are (min, max), chosen uniformly
+pub struct ParticleEmitter {
+    pub texture: PathBuf,
+    /// areas of the texture. the whole texture if empty
+    pub frames: Vec<TextureRect>,
+    pub frame_selection: FrameSelection,
+
+    /// where particles are emitted
+    pub x: f32,
+    pub y: f32,
+    /// particles per second, emitted while emitting is set
+    pub rate: f32,
+    pub emitting: bool,
+    /// oldest particles are replaced past this
+    pub max_particles: usize,
+    /// longer updates are shortened to this, e.g. the first after a lazy loop
+    /// was idle. otherwise a burst from before would expire at once
+    pub max_dt: Duration,
+
+    /// seconds
+    pub lifetime: (f32, f32),
+    /// degrees clockwise from the positive x axis
+    pub direction: f32,
+    /// degrees. particles leave in a cone this wide, centered on direction
+    pub spread: f32,
+    /// units per second
+    pub speed: (f32, f32),
+    /// units per second per second
+    pub gravity: (f32, f32),
+    /// the fraction of velocity lost per second is 1 - e^-drag
+    pub drag: f32,
+
+    /// degrees clockwise
+    pub rotation: (f32, f32),
+    /// degrees per second
+    pub spin: (f32, f32),
+    /// multiplies the texture
+    pub color: Curve<Color>,
+    /// width of the particle. height keeps the frame's aspect ratio
+    pub size: Curve<f32>,
+
+    particles: Vec<Particle>,
+    /// fractional particles waiting to be emitted
+    accumulator: f32,
+    seed: u64,
+}
+
+impl ParticleEmitter {
+    pub fn new<'a, P: Into<PathLike<'a>>>(texture: P) -> Self {
+        let texture: PathLike = texture.into();
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self {
+            texture: texture.into(),
+            frames: Vec::new(),
+            frame_selection: Default::default(),
+            x: 0.,
+            y: 0.,
+            rate: 0.,
+            emitting: true,
+            max_particles: 1000,
+            max_dt: Duration::from_millis(100),
+            lifetime: (1., 1.),
+            direction: -90.,
+            spread: 360.,
+            speed: (0., 0.),
+            gravity: (0., 0.),
+            drag: 0.,
+            rotation: (0., 0.),
+            spin: (0., 0.),
+            color: Curve::constant(Color {
+                r: 0xFF,
+                g: 0xFF,
+                b: 0xFF,
+                a: 0xFF,
+            }),
+            size: Curve::constant(8.),
+            particles: Vec::new(),
+            accumulator: 0.,
+            seed,
+        }
+    }
+
+    /// make the random choices repeatable. by default the seed is from the
+    /// time
+    pub fn seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// if there are particles to draw
+    pub fn alive(&self) -> bool {
+        !self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.accumulator = 0.;
+    }
+
+    fn random(&mut self, range: (f32, f32)) -> f32 {
+        let t = random_unit(&mut self.seed);
+        range.0.lerp(range.1, t)
+    }
+
+    /// emit some particles now
+    pub fn burst(&mut self, count: usize) {
+        // room is made by evicting the oldest all at once. any of this burst
+        // past the limit would only replace each other
+        let count = count.min(self.max_particles);
+        let evict = (self.particles.len() + count).saturating_sub(self.max_particles);
+        if evict > 0 {
+            self.particles
+                .select_nth_unstable_by(evict - 1, |a, b| b.life().total_cmp(&a.life()));
+            self.particles.drain(..evict);
+        }
+
+        for _ in 0..count {
+            let angle = self
+                .random((
+                    self.direction - self.spread / 2.,
+                    self.direction + self.spread / 2.,
+                ))
+                .to_radians();
+            let speed = self.random(self.speed);
+            let frame = match self.frame_selection {
+                FrameSelection::Random if !self.frames.is_empty() => {
+                    let i = random_unit(&mut self.seed) * self.frames.len() as f32;
+                    (i as usize).min(self.frames.len() - 1)
+                }
+                _ => 0,
+            };
+            let particle = Particle {
+                x: self.x,
+                y: self.y,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                rotation: self.random(self.rotation),
+                spin: self.random(self.spin),
+                age: 0.,
+                lifetime: self.random(self.lifetime),
+                frame,
+            };
+            self.particles.push(particle);
+        }
+    }
+
+    /// emit and move particles, removing the expired ones
+    pub fn update(&mut self, dt: Duration) {
+        let dt = dt.min(self.max_dt).as_secs_f32();
+        let drag = (-self.drag * dt).exp();
+        for p in self.particles.iter_mut() {
+            p.age += dt;
+            p.vx = (p.vx + self.gravity.0 * dt) * drag;
+            p.vy = (p.vy + self.gravity.1 * dt) * drag;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            p.rotation += p.spin * dt;
+            if self.frame_selection == FrameSelection::OverLife && !self.frames.is_empty() {
+                let i = p.life() * self.frames.len() as f32;
+                p.frame = (i as usize).min(self.frames.len() - 1);
+            }
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+
+        if self.emitting && self.rate > 0. {
+            self.accumulator += self.rate * dt;
+            let count = self.accumulator as usize;
+            self.accumulator -= count as f32;
+            self.burst(count);
+        }
+    }
+
+    /// draw with particle positions offset by some screen amount
+    pub fn draw<T: System>(&self, system: &mut T, offset: (f32, f32)) -> Result<(), String> {
+        self.draw_with(system, |TextureDestinationF(rect, rotation)| {
+            let rect = TextureRectF::new(
+                rect.x.get() + offset.0,
+                rect.y.get() + offset.1,
+                rect.w.get(),
+                rect.h.get(),
+            )?;
+            Some(TextureDestinationF(rect, rotation))
+        })
+    }
+
+    /// draw with particle positions in world coordinates
+    pub fn draw_camera<T: System>(&self, system: &mut T, camera: &Camera) -> Result<(), String> {
+        self.draw_with(system, |dst| camera.apply(dst))
+    }
+
+    fn draw_with<T: System, F>(&self, system: &mut T, place: F) -> Result<(), String>
+    where
+        F: Fn(TextureDestinationF) -> Option<TextureDestinationF>,
+    {
+        if self.particles.is_empty() {
+            return Ok(());
+        }
+        let mut texture = system.image(&self.texture)?;
+        let whole = texture.size()?;
+        for p in self.particles.iter() {
+            let life = p.life();
+            let (src, (w, h)) = match self.frames.get(p.frame) {
+                Some(frame) => (TextureSource::Area(*frame), frame.size()),
+                None => (TextureSource::WholeTexture, whole),
+            };
+            let width = self.size.sample(life).unwrap_or(0.);
+            let height = width * h.get() as f32 / w.get() as f32;
+            let rect = match TextureRectF::new(p.x - width / 2., p.y - height / 2., width, height) {
+                Some(v) => v,
+                None => continue, // zero size
+            };
+            let rotation = match NonNaNFinite::<f32>::new(p.rotation) {
+                Ok(angle) => Some(TextureRotationF {
+                    angle,
+                    point: None,
+                    flip_horizontal: false,
+                    flip_vertical: false,
+                }),
+                Err(_) => None,
+            };
+            let dst = match place(TextureDestinationF(rect, rotation)) {
+                Some(v) => v,
+                None => continue,
+            };
+            if let Some(color) = self.color.sample(life) {
+                texture.tint(color)?;
+            }
+            let src: TextureSourceF = match src {
+                TextureSource::WholeTexture => Default::default(),
+                TextureSource::Area(v) => TextureRectF::from(v).into(),
+            };
+            texture.copy_f(src, dst)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve() {
+        let c = Curve {
+            keys: vec![(0., 0.), (0.5, 10.), (1., 0.)],
+        };
+        assert_eq!(c.sample(-1.), Some(0.));
+        assert_eq!(c.sample(0.25), Some(5.));
+        assert_eq!(c.sample(0.75), Some(5.));
+        assert_eq!(c.sample(2.), Some(0.));
+        assert_eq!(Curve::<f32> { keys: vec![] }.sample(0.), None);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let make = || {
+            let mut e = ParticleEmitter::new(PathBuf::from("p.png"));
+            e.seed(7);
+            e.rate = 10.;
+            e.speed = (5., 20.);
+            e.gravity = (0., 9.8);
+            e.lifetime = (0.5, 1.);
+            e
+        };
+        let (mut a, mut b) = (make(), make());
+        for _ in 0..30 {
+            a.update(Duration::from_millis(20));
+            b.update(Duration::from_millis(20));
+        }
+        assert_eq!(a.particles(), b.particles());
+        assert!(a.alive());
+
+        a.emitting = false;
+        for _ in 0..10 {
+            a.update(Duration::from_millis(100));
+        }
+        assert!(!a.alive());
+    }
+
+    #[test]
+    fn test_max_particles() {
+        let mut e = ParticleEmitter::new(PathBuf::from("p.png"));
+        e.max_particles = 3;
+        e.burst(2);
+        e.update(Duration::from_millis(100));
+        e.burst(2);
+        // the oldest was replaced
+        assert_eq!(e.particles().len(), 3);
+        assert_eq!(e.particles().iter().filter(|p| p.age == 0.).count(), 2);
+
+        e.burst(5);
+        assert!(e.particles().len() == 3 && e.particles().iter().all(|p| p.age == 0.));
+
+        e.max_particles = 0;
+        e.burst(1);
+        assert!(!e.alive());
+    }
+
+    #[test]
+    fn test_large_dt() {
+        let mut e = ParticleEmitter::new(PathBuf::from("p.png"));
+        e.seed(1);
+        e.lifetime = (0.5, 0.5);
+        e.burst(4);
+        e.update(Duration::from_secs(60));
+        assert_eq!(e.particles().len(), 4);
+        assert_eq!(e.particles()[0].age, 0.1);
+
+        // emitting doesn't catch up on the whole gap
+        e.clear();
+        e.rate = 100.;
+        e.update(Duration::from_secs(60));
+        assert_eq!(e.particles().len(), 10);
+    }
+}
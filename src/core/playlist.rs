@@ -0,0 +1,234 @@
+use std::path::{Path, PathBuf};
+
+use super::{shuffle::shuffle, PathLike};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RepeatMode {
+    /// stop after the last track
+    #[default]
+    Off,
+    /// play the current track again
+    One,
+    /// go back to the first track after the last
+    All,
+}
+
+/// ordered music tracks. see System::playlist()
+#[derive(Debug, Clone, Default)]
+pub struct Playlist {
+    tracks: Vec<PathBuf>,
+    /// indices into tracks in play order
+    order: Vec<usize>,
+    /// index into order of the current track. None if not started or ended
+    position: Option<usize>,
+    shuffle: bool,
+    repeat: RepeatMode,
+    /// changed each time the order is shuffled
+    seed: u64,
+}
+
+impl Playlist {
+    /// add a track to the end
+    pub fn queue<'a, P: Into<PathLike<'a>>>(&mut self, track: P) {
+        let track: PathLike = track.into();
+        self.order.push(self.tracks.len());
+        self.tracks.push(track.into());
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.order.clear();
+        self.position = None;
+    }
+
+    /// in the order queued
+    pub fn tracks(&self) -> &[PathBuf] {
+        &self.tracks
+    }
+
+    /// the order is shuffled once now, and again each time the playlist
+    /// repeats. the current track is kept
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+        let current = self.position.map(|position| self.order[position]);
+        self.order = (0..self.tracks.len()).collect();
+        if shuffle {
+            self.reshuffle();
+        }
+        if let Some(current) = current {
+            let position = self.order.iter().position(|&i| i == current).unwrap();
+            if shuffle {
+                // the remaining tracks are all after the current one
+                self.order.remove(position);
+                self.order.insert(0, current);
+                self.position = Some(0);
+            } else {
+                self.position = Some(position);
+            }
+        }
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    pub fn set_repeat(&mut self, repeat: RepeatMode) {
+        self.repeat = repeat;
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn current(&self) -> Option<&Path> {
+        self.position
+            .map(|position| self.tracks[self.order[position]].as_path())
+    }
+
+    /// go to the first track. returns it, or None if empty
+    pub fn start(&mut self) -> Option<&Path> {
+        if self.shuffle {
+            self.reshuffle();
+        }
+        self.position = if self.order.is_empty() { None } else { Some(0) };
+        self.current()
+    }
+
+    /// stop without ending. current() becomes None
+    pub fn stop(&mut self) {
+        self.position = None;
+    }
+
+    /// the track that would play after the current one ends naturally
+    pub fn peek_next(&self) -> Option<&Path> {
+        let position = self.position?;
+        if self.repeat == RepeatMode::One {
+            return self.current();
+        }
+        self.peek_following(position)
+    }
+
+    /// the current track ended naturally. move to the next, following the
+    /// repeat mode. returns the new current track, or None if it ended
+    pub fn advance(&mut self) -> Option<&Path> {
+        if self.repeat == RepeatMode::One {
+            return self.current();
+        }
+        self.skip()
+    }
+
+    /// move to the next track, ignoring RepeatMode::One
+    pub fn skip(&mut self) -> Option<&Path> {
+        let position = self.position?;
+        if position + 1 < self.order.len() {
+            self.position = Some(position + 1);
+        } else if self.repeat == RepeatMode::Off {
+            self.position = None;
+        } else {
+            if self.shuffle {
+                self.reshuffle();
+            }
+            self.position = Some(0);
+        }
+        self.current()
+    }
+
+    fn peek_following(&self, position: usize) -> Option<&Path> {
+        if position + 1 < self.order.len() {
+            return Some(&self.tracks[self.order[position + 1]]);
+        }
+        if self.repeat == RepeatMode::Off {
+            return None;
+        }
+        if self.shuffle {
+            // must match what skip() will do
+            let mut order = self.order.clone();
+            shuffle(&mut order, self.seed.wrapping_add(1));
+            return Some(&self.tracks[order[0]]);
+        }
+        Some(&self.tracks[self.order[0]])
+    }
+
+    fn reshuffle(&mut self) {
+        self.seed = self.seed.wrapping_add(1);
+        shuffle(&mut self.order, self.seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(n: usize) -> Playlist {
+        let mut ret = Playlist::default();
+        for i in 0..n {
+            ret.queue(PathBuf::from(i.to_string()));
+        }
+        ret
+    }
+
+    fn name(p: Option<&Path>) -> Option<String> {
+        p.map(|p| p.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_repeat_off() {
+        let mut p = playlist(2);
+        assert_eq!(p.current(), None);
+        assert_eq!(name(p.start()), Some("0".to_owned()));
+        assert_eq!(name(p.peek_next()), Some("1".to_owned()));
+        assert_eq!(name(p.advance()), Some("1".to_owned()));
+        assert_eq!(p.peek_next(), None);
+        assert_eq!(p.advance(), None);
+        assert_eq!(p.current(), None);
+        assert_eq!(p.advance(), None);
+    }
+
+    #[test]
+    fn test_repeat_one() {
+        let mut p = playlist(2);
+        p.set_repeat(RepeatMode::One);
+        p.start();
+        assert_eq!(name(p.peek_next()), Some("0".to_owned()));
+        assert_eq!(name(p.advance()), Some("0".to_owned()));
+        assert_eq!(name(p.skip()), Some("1".to_owned()));
+    }
+
+    #[test]
+    fn test_repeat_all() {
+        let mut p = playlist(2);
+        p.set_repeat(RepeatMode::All);
+        p.start();
+        p.advance();
+        assert_eq!(name(p.peek_next()), Some("0".to_owned()));
+        assert_eq!(name(p.advance()), Some("0".to_owned()));
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut p = playlist(5);
+        p.set_repeat(RepeatMode::All);
+        p.start();
+        p.advance();
+        let current = name(p.current());
+        p.set_shuffle(true);
+        assert_eq!(name(p.current()), current);
+
+        // every track once per cycle, and peek agrees with advance
+        let mut seen = vec![current.clone().unwrap()];
+        for _ in 0..4 {
+            let peeked = name(p.peek_next());
+            let next = name(p.advance());
+            assert_eq!(peeked, next);
+            seen.push(next.unwrap());
+        }
+        seen.sort();
+        assert_eq!(seen, vec!["0", "1", "2", "3", "4"]);
+
+        let peeked = name(p.peek_next());
+        assert_eq!(peeked, name(p.advance()));
+
+        p.set_shuffle(false);
+        assert_eq!(name(p.current()), peeked);
+    }
+}
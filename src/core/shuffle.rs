@@ -33,14 +33,35 @@ mod tests {
             "Deterministic shuffle result did not match"
         );
     }
+
+    #[test]
+    fn test_random_unit() {
+        let mut seed = 42;
+        for _ in 0..1000 {
+            let v = random_unit(&mut seed);
+            assert!((0. ..1.).contains(&v));
+        }
+    }
+}
+
+/// one step of the Linear Congruential Generator
+fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
 }
 
 /// deterministic pseudo random shuffle. don't want to add another dep, so doing
 /// this very simple method by hand, via a Linear Congruential Generator + Knuth
 pub(crate) fn shuffle<T>(v: &mut [T], mut seed: u64) {
     for i in (1..v.len()).rev() {
-        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        seed = next_seed(seed);
         let j = (seed % (i as u64 + 1)) as usize;
         v.swap(i, j);
     }
 }
+
+/// from 0 inclusively to 1 exclusively. advances the seed
+pub(crate) fn random_unit(seed: &mut u64) -> f32 {
+    *seed = next_seed(*seed);
+    // the high bits are the most random
+    (*seed >> 40) as f32 / (1u64 << 24) as f32
+}
@@ -0,0 +1,156 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::shuffle::random_unit;
+
+/// how a SoundSet picks the next sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// never the same sound twice in a row (unless there's only one)
+    #[default]
+    Random,
+    /// in order, wrapping around
+    RoundRobin,
+}
+
+/// how a single play of a sound differs from the original. see
+/// System::sound_variation()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Variation {
+    /// from 0 to 1 inclusively. multiplied with the bus volume
+    pub volume: f32,
+    /// playback speed. changes the pitch too. 1 is unchanged
+    pub speed: f32,
+}
+
+impl Default for Variation {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            speed: 1.,
+        }
+    }
+}
+
+/// several files for one logical sound (e.g. footsteps), so it doesn't sound
+/// the same each time. see System::sound_set()
+#[derive(Debug, Clone)]
+pub struct SoundSet {
+    pub sounds: Vec<PathBuf>,
+    pub selection: Selection,
+    /// from 0 to 1. each play is randomly quieter by up to this portion
+    pub volume_jitter: f32,
+    /// each play is randomly faster or slower by up to this portion. e.g. 0.1
+    /// plays from 0.9 to 1.1 times the speed
+    pub pitch_jitter: f32,
+    /// set for reproducible variations
+    pub seed: u64,
+    /// index of the last sound picked
+    last: Option<usize>,
+}
+
+impl SoundSet {
+    pub fn new(sounds: Vec<PathBuf>) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Self {
+            sounds,
+            selection: Default::default(),
+            volume_jitter: 0.,
+            pitch_jitter: 0.,
+            seed,
+            last: None,
+        }
+    }
+
+    /// pick the next sound and how to vary it. None if there are no sounds
+    pub fn next_variation(&mut self) -> Option<(&Path, Variation)> {
+        let len = self.sounds.len();
+        if len == 0 {
+            return None;
+        }
+
+        let index = match (self.selection, self.last) {
+            (Selection::RoundRobin, Some(last)) => (last + 1) % len,
+            (Selection::RoundRobin, None) => 0,
+            (Selection::Random, Some(last)) if len > 1 => {
+                // pick from the others
+                let offset = 1 + (random_unit(&mut self.seed) * (len - 1) as f32) as usize;
+                (last + offset.min(len - 1)) % len
+            }
+            (Selection::Random, _) => (random_unit(&mut self.seed) * len as f32) as usize,
+        };
+        let index = index.min(len - 1);
+        self.last = Some(index);
+
+        let volume_jitter = self.volume_jitter.clamp(0., 1.);
+        let volume = 1. - random_unit(&mut self.seed) * volume_jitter;
+        let pitch_jitter = self.pitch_jitter.clamp(0., 0.99);
+        let speed = 1. + (random_unit(&mut self.seed) * 2. - 1.) * pitch_jitter;
+
+        Some((&self.sounds[index], Variation { volume, speed }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(n: usize) -> SoundSet {
+        let mut ret = SoundSet::new((0..n).map(|i| PathBuf::from(i.to_string())).collect());
+        ret.seed = 42;
+        ret
+    }
+
+    fn name(set: &mut SoundSet) -> String {
+        let (path, _) = set.next_variation().unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(set(0).next_variation().is_none());
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut s = set(3);
+        s.selection = Selection::RoundRobin;
+        let names: Vec<String> = (0..4).map(|_| name(&mut s)).collect();
+        assert_eq!(names, vec!["0", "1", "2", "0"]);
+    }
+
+    #[test]
+    fn test_random_no_repeat() {
+        let mut s = set(3);
+        let mut previous = name(&mut s);
+        for _ in 0..100 {
+            let next = name(&mut s);
+            assert_ne!(next, previous);
+            previous = next;
+        }
+
+        let mut single = set(1);
+        assert_eq!(name(&mut single), "0");
+        assert_eq!(name(&mut single), "0");
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut s = set(2);
+        let (_, variation) = s.next_variation().unwrap();
+        assert_eq!(variation, Variation::default());
+
+        s.volume_jitter = 0.5;
+        s.pitch_jitter = 0.1;
+        for _ in 0..100 {
+            let (_, variation) = s.next_variation().unwrap();
+            assert!((0.5..=1.).contains(&variation.volume));
+            assert!((0.9..=1.1).contains(&variation.speed));
+        }
+    }
+}
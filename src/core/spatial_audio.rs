@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use super::{
+    audio::{Bus, Priority, SoundHandle},
+    PathLike, System,
+};
+
+/// how volume falls off with distance from the listener. distances are in
+/// world units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Attenuation {
+    /// full volume at the listener, decreasing linearly until silent at
+    /// max_distance
+    Linear { max_distance: f32 },
+    /// full volume within reference_distance, then proportional to
+    /// reference_distance / distance. silent past max_distance
+    Inverse {
+        reference_distance: f32,
+        max_distance: f32,
+    },
+}
+
+impl Attenuation {
+    /// from 0 to 1 inclusively
+    pub fn gain(&self, distance: f32) -> f32 {
+        match *self {
+            Attenuation::Linear { max_distance } => {
+                if distance >= max_distance {
+                    return 0.;
+                }
+                1. - distance / max_distance
+            }
+            Attenuation::Inverse {
+                reference_distance,
+                max_distance,
+            } => {
+                if distance >= max_distance {
+                    return 0.;
+                }
+                if distance <= reference_distance {
+                    return 1.;
+                }
+                reference_distance / distance
+            }
+        }
+    }
+}
+
+/// the point sounds are heard from. world coordinates are y-down, same as
+/// the screen
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Listener {
+    pub x: f32,
+    pub y: f32,
+    /// from 0 to 1. 0 faces north (negative y), increasing rotates clockwise
+    pub facing: f32,
+}
+
+/// a looping sound at a position, e.g. attached to a moving entity. call
+/// SpatialAudio::update_emitter() each frame
+pub struct Emitter<'a, T: System> {
+    pub handle: T::LoopingSoundHandle<'a>,
+    pub bus: Bus,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl<'a, T: System> Emitter<'a, T> {
+    pub fn new(handle: T::LoopingSoundHandle<'a>, bus: Bus, x: f32, y: f32) -> Self {
+        Self { handle, bus, x, y }
+    }
+}
+
+/// plays sounds at world positions. converts to the direction and distance
+/// used by System::sound() and System::loop_sound()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpatialAudio {
+    pub listener: Listener,
+    pub attenuation: Attenuation,
+}
+
+impl SpatialAudio {
+    pub fn new(attenuation: Attenuation) -> Self {
+        Self {
+            listener: Default::default(),
+            attenuation,
+        }
+    }
+
+    /// the direction and distance from the listener, in the form accepted by
+    /// System::sound(). None if the position is out of hearing range
+    pub fn direction_distance(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let dx = x - self.listener.x;
+        let dy = y - self.listener.y;
+        let gain = self.attenuation.gain((dx * dx + dy * dy).sqrt());
+        if gain <= 0. {
+            return None;
+        }
+
+        let direction = if dx == 0. && dy == 0. {
+            0.
+        } else {
+            // clockwise from north, in the y-down coordinate system
+            let bearing = dx.atan2(-dy) / std::f32::consts::TAU;
+            (bearing - self.listener.facing).rem_euclid(1.)
+        };
+
+        // the backend distance is a linear volume decrease
+        Some((direction, 1. - gain))
+    }
+
+    /// play a one-shot sound at a world position. returns None if out of
+    /// hearing range, or see System::sound()
+    pub fn sound<'a, 's, T, P>(
+        &self,
+        system: &'s mut T,
+        sound: P,
+        bus: Bus,
+        priority: Priority,
+        x: f32,
+        y: f32,
+    ) -> Result<Option<SoundHandle>, String>
+    where
+        T: System,
+        P: Into<PathLike<'a>>,
+        's: 'a,
+    {
+        match self.direction_distance(x, y) {
+            Some((direction, distance)) => system.sound(sound, bus, priority, direction, distance),
+            None => Ok(None),
+        }
+    }
+
+    /// call each frame for each emitter. the looping sound is stopped while
+    /// out of hearing range, and started again once in range
+    pub fn update_emitter<'a, T: System>(
+        &self,
+        system: &mut T,
+        emitter: &mut Emitter<'a, T>,
+        fade_in_duration: Option<Duration>,
+    ) -> Result<(), String> {
+        match self.direction_distance(emitter.x, emitter.y) {
+            Some((direction, distance)) => system.loop_sound(
+                &mut emitter.handle,
+                emitter.bus,
+                direction,
+                distance,
+                fade_in_duration,
+            ),
+            None => {
+                system.stop_loop_sound(&mut emitter.handle, None);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.0001
+    }
+
+    #[test]
+    fn test_gain() {
+        let linear = Attenuation::Linear { max_distance: 10. };
+        assert_eq!(linear.gain(0.), 1.);
+        assert!(close(linear.gain(5.), 0.5));
+        assert_eq!(linear.gain(10.), 0.);
+        assert_eq!(linear.gain(20.), 0.);
+
+        let inverse = Attenuation::Inverse {
+            reference_distance: 2.,
+            max_distance: 10.,
+        };
+        assert_eq!(inverse.gain(1.), 1.);
+        assert!(close(inverse.gain(4.), 0.5));
+        assert_eq!(inverse.gain(10.), 0.);
+    }
+
+    #[test]
+    fn test_direction() {
+        let mut spatial = SpatialAudio::new(Attenuation::Linear { max_distance: 100. });
+        spatial.listener.x = 10.;
+        spatial.listener.y = 10.;
+
+        let (direction, distance) = spatial.direction_distance(10., 0.).unwrap(); // north
+        assert!(close(direction, 0.));
+        assert!(close(distance, 0.1));
+        let (direction, _) = spatial.direction_distance(20., 10.).unwrap(); // east
+        assert!(close(direction, 0.25));
+        let (direction, _) = spatial.direction_distance(10., 20.).unwrap(); // south
+        assert!(close(direction, 0.5));
+        let (direction, _) = spatial.direction_distance(0., 10.).unwrap(); // west
+        assert!(close(direction, 0.75));
+
+        // facing east. north is now to the left
+        spatial.listener.facing = 0.25;
+        let (direction, _) = spatial.direction_distance(10., 0.).unwrap();
+        assert!(close(direction, 0.75));
+
+        assert_eq!(spatial.direction_distance(10., 10.), Some((0., 0.)));
+        assert_eq!(spatial.direction_distance(1000., 10.), None);
+    }
+}
@@ -0,0 +1,502 @@
+mod json;
+mod tmj;
+mod tmx;
+mod xml;
+
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    texture_rect::{NonNaNFinite, TextureDestination, TextureRect, TextureRotation},
+    PathLike, System, TextureHandle,
+};
+
+pub const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+pub const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+pub const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// hexagonal maps only. ignored
+pub const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120);
+
+/// a cell in a tile layer, or the tile of a tile object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tile {
+    /// global tile id. 0 is empty
+    pub gid: u32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// swap x and y. done before the other flips
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    /// from a gid with the flip flags in the high bits, as stored by tiled
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            gid: raw & GID_MASK,
+            flip_horizontal: raw & FLIPPED_HORIZONTALLY != 0,
+            flip_vertical: raw & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: raw & FLIPPED_DIAGONALLY != 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gid == 0
+    }
+
+    /// how the tile should be drawn. None if it isn't flipped
+    ///
+    /// a diagonal flip is a quarter turn, so it's only correct for square
+    /// tiles
+    pub fn rotation(&self) -> Option<TextureRotation> {
+        let (angle, flip_horizontal, flip_vertical) = if self.flip_diagonal {
+            // diagonal then h then v == quarter turn after flipping v, with
+            // the other flips swapped
+            (90., self.flip_vertical, !self.flip_horizontal)
+        } else {
+            if !self.flip_horizontal && !self.flip_vertical {
+                return None;
+            }
+            (0., self.flip_horizontal, self.flip_vertical)
+        };
+        Some(TextureRotation {
+            angle: NonNaNFinite::<f32>::new(angle).unwrap(),
+            point: None,
+            flip_horizontal,
+            flip_vertical,
+        })
+    }
+}
+
+/// a custom property set in tiled. colors, files and object references are
+/// kept as strings
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl PropertyValue {
+    fn parse(kind: &str, value: &str) -> Result<Self, String> {
+        let bad = || format!("bad {} property value: {}", kind, value);
+        Ok(match kind {
+            "bool" => PropertyValue::Bool(value == "true"),
+            "int" | "object" => PropertyValue::Int(value.parse().map_err(|_| bad())?),
+            "float" => PropertyValue::Float(value.parse().map_err(|_| bad())?),
+            _ => PropertyValue::String(value.to_owned()),
+        })
+    }
+}
+
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// a single image cut into tiles
+#[derive(Debug, Clone)]
+pub struct Tileset {
+    /// gid of the first tile in this tileset
+    pub first_gid: u32,
+    pub name: String,
+    pub tile_width: NonZeroU32,
+    pub tile_height: NonZeroU32,
+    /// pixels between tiles in the image
+    pub spacing: u32,
+    /// pixels around the tiles in the image
+    pub margin: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub image: PathBuf,
+    pub properties: Properties,
+    /// per tile properties, by local id
+    pub tile_properties: HashMap<u32, Properties>,
+}
+
+impl Tileset {
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.tile_count
+    }
+
+    /// area of the image for a gid in this tileset
+    pub fn source(&self, gid: u32) -> Option<TextureRect> {
+        if !self.contains(gid) || self.columns == 0 {
+            return None;
+        }
+        let local = gid - self.first_gid;
+        let (col, row) = (local % self.columns, local / self.columns);
+        let x = self.margin + col * (self.tile_width.get() + self.spacing);
+        let y = self.margin + row * (self.tile_height.get() + self.spacing);
+        Some(TextureRect {
+            x: x as i32,
+            y: y as i32,
+            w: self.tile_width,
+            h: self.tile_height,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    pub name: String,
+    /// in tiles
+    pub width: u32,
+    /// in tiles
+    pub height: u32,
+    /// row major
+    pub tiles: Vec<Tile>,
+    pub visible: bool,
+    /// pixels
+    pub offset: (i32, i32),
+    pub properties: Properties,
+}
+
+impl TileLayer {
+    pub fn tile(&self, x: u32, y: u32) -> Option<Tile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles
+            .get((y * self.width + x) as usize)
+            .copied()
+            .filter(|t| !t.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// points relative to the object position
+    Polygon(Vec<(f32, f32)>),
+    /// points relative to the object position
+    Polyline(Vec<(f32, f32)>),
+}
+
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    /// called type in older versions of tiled
+    pub class: String,
+    /// pixels
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// degrees clockwise
+    pub rotation: f32,
+    pub visible: bool,
+    pub shape: ObjectShape,
+    /// for tile objects
+    pub tile: Option<Tile>,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<MapObject>,
+    pub visible: bool,
+    /// pixels
+    pub offset: (i32, i32),
+    pub properties: Properties,
+}
+
+/// group layers are flattened into their children
+#[derive(Debug, Clone)]
+pub enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+impl Layer {
+    pub fn name(&self) -> &str {
+        match self {
+            Layer::Tiles(l) => &l.name,
+            Layer::Objects(l) => &l.name,
+        }
+    }
+}
+
+/// an orthogonal map made in the tiled editor (mapeditor.org). finite maps
+/// only. tile data may be csv or uncompressed base64
+#[derive(Debug, Clone)]
+pub struct Tilemap {
+    /// in tiles
+    pub width: u32,
+    /// in tiles
+    pub height: u32,
+    pub tile_width: NonZeroU32,
+    pub tile_height: NonZeroU32,
+    /// sorted by first gid
+    pub tilesets: Vec<Tileset>,
+    /// bottom to top
+    pub layers: Vec<Layer>,
+    pub properties: Properties,
+}
+
+impl Tilemap {
+    /// .tmx is read as xml, anything else (.tmj, .json) as json. tilesets and
+    /// images are relative to the map's directory
+    pub fn load<'a, P: Into<PathLike<'a>>>(path: P) -> Result<Self, String> {
+        let mut buf = None;
+        let path = path.into().get_path(&mut buf);
+        let text = read(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        if is_tmx(path) {
+            Self::from_tmx(&text, dir)
+        } else {
+            Self::from_json(&text, dir)
+        }
+    }
+
+    pub fn from_tmx(text: &str, dir: &Path) -> Result<Self, String> {
+        let mut map = tmx::map(&xml::parse(text)?, dir)?;
+        map.tilesets.sort_by_key(|t| t.first_gid);
+        Ok(map)
+    }
+
+    pub fn from_json(text: &str, dir: &Path) -> Result<Self, String> {
+        let mut map = tmj::map(&json::parse(text)?, dir)?;
+        map.tilesets.sort_by_key(|t| t.first_gid);
+        Ok(map)
+    }
+
+    /// width and height in pixels
+    pub fn pixel_size(&self) -> (u32, u32) {
+        (
+            self.width * self.tile_width.get(),
+            self.height * self.tile_height.get(),
+        )
+    }
+
+    pub fn tileset(&self, gid: u32) -> Option<&Tileset> {
+        self.tileset_index(gid).map(|i| &self.tilesets[i])
+    }
+
+    fn tileset_index(&self, gid: u32) -> Option<usize> {
+        self.tilesets
+            .iter()
+            .rposition(|t| t.first_gid <= gid)
+            .filter(|&i| self.tilesets[i].contains(gid))
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name() == name)
+    }
+
+    /// draw each visible tile layer. offset is the map pixel shown at the
+    /// top left of the viewport. tiles may be drawn partly outside the
+    /// viewport; set a clipping rect to prevent this
+    pub fn draw<T: System>(
+        &self,
+        system: &mut T,
+        viewport: TextureRect,
+        offset: (i32, i32),
+    ) -> Result<(), String> {
+        for layer in self.layers.iter() {
+            if let Layer::Tiles(layer) = layer {
+                if layer.visible {
+                    self.draw_layer(system, layer, viewport, offset)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// draw only the tiles of this layer that are in the viewport
+    pub fn draw_layer<T: System>(
+        &self,
+        system: &mut T,
+        layer: &TileLayer,
+        viewport: TextureRect,
+        offset: (i32, i32),
+    ) -> Result<(), String> {
+        let tw = self.tile_width.get() as i32;
+        let th = self.tile_height.get() as i32;
+        // screen position of the layer's top left
+        let origin_x = viewport.x - offset.0 + layer.offset.0;
+        let origin_y = viewport.y - offset.1 + layer.offset.1;
+
+        // tiles bigger than the grid are aligned to the bottom left of their
+        // cell, so they can reach into view from the left or below
+        let extra_x = self
+            .tilesets
+            .iter()
+            .map(|t| (t.tile_width.get() as i32 - 1) / tw)
+            .max()
+            .unwrap_or(0);
+        let extra_y = self
+            .tilesets
+            .iter()
+            .map(|t| (t.tile_height.get() as i32 - 1) / th)
+            .max()
+            .unwrap_or(0);
+
+        let x0 = (viewport.x - origin_x).div_euclid(tw) - extra_x;
+        let y0 = (viewport.y - origin_y).div_euclid(th);
+        let x1 = (viewport.x + viewport.w.get() as i32 - origin_x).div_euclid(tw);
+        let y1 = (viewport.y + viewport.h.get() as i32 - origin_y).div_euclid(th) + extra_y;
+
+        let x0 = x0.max(0) as u32;
+        let y0 = y0.max(0) as u32;
+        let x1 = (x1.max(-1) + 1).min(layer.width as i32) as u32;
+        let y1 = (y1.max(-1) + 1).min(layer.height as i32) as u32;
+
+        // the visible tiles, in cell order since big tiles can overlap
+        let mut tiles: Vec<(usize, TextureRect, TextureRect, Tile)> = Vec::new();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let tile = match layer.tile(x, y) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let index = match self.tileset_index(tile.gid) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let src = match self.tilesets[index].source(tile.gid) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let dst = TextureRect {
+                    x: origin_x + x as i32 * tw,
+                    y: origin_y + (y as i32 + 1) * th - src.h.get() as i32,
+                    w: src.w,
+                    h: src.h,
+                };
+                // the range above is for the biggest tiles
+                if dst.x + dst.w.get() as i32 <= viewport.x
+                    || dst.y >= viewport.y + viewport.h.get() as i32
+                {
+                    continue;
+                }
+                tiles.push((index, src, dst, tile));
+            }
+        }
+
+        // the image is fetched once for each run of tiles from the same tileset
+        for run in tiles.chunk_by(|a, b| a.0 == b.0) {
+            let mut texture = system.image(&self.tilesets[run[0].0].image)?;
+            for &(_, src, dst, tile) in run {
+                texture.copy(src, TextureDestination(dst, tile.rotation()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn is_tmx(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("tmx") | Some("tsx")
+    )
+}
+
+/// tile layer data. encoding is "csv", "base64" or None (a list of gids).
+/// compressed data isn't supported
+fn decode_tiles(
+    encoding: Option<&str>,
+    compression: Option<&str>,
+    data: &str,
+    len: usize,
+) -> Result<Vec<Tile>, String> {
+    if let Some(compression) = compression.filter(|c| !c.is_empty()) {
+        return Err(format!(
+            "{} compressed tile data isn't supported. save the map with csv or uncompressed base64",
+            compression
+        ));
+    }
+    let raw: Vec<u32> = match encoding {
+        Some("csv") => data
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().map_err(|_| format!("bad tile in csv: {}", v)))
+            .collect::<Result<_, _>>()?,
+        Some("base64") => {
+            let bytes = base64(data)?;
+            if bytes.len() % 4 != 0 {
+                return Err("base64 tile data isn't a multiple of 4 bytes".to_owned());
+            }
+            bytes
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect()
+        }
+        Some(other) => return Err(format!("unknown tile encoding: {}", other)),
+        None => data
+            .split_whitespace()
+            .map(|v| v.parse().map_err(|_| format!("bad tile: {}", v)))
+            .collect::<Result<_, _>>()?,
+    };
+    if raw.len() != len {
+        return Err(format!("expected {} tiles, got {}", len, raw.len()));
+    }
+    Ok(raw.into_iter().map(Tile::from_raw).collect())
+}
+
+fn base64(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return Err(format!("bad base64 character: {}", c as char)),
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn non_zero(v: u32, what: &str) -> Result<NonZeroU32, String> {
+    NonZeroU32::new(v).ok_or_else(|| format!("{} must be non zero", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_flags() {
+        let t = Tile::from_raw(FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY | 7);
+        assert_eq!(t.gid, 7);
+        assert!(t.flip_horizontal && !t.flip_vertical && t.flip_diagonal);
+        let r = t.rotation().unwrap();
+        assert_eq!(r.angle.get(), 90.);
+        assert!(!r.flip_horizontal && !r.flip_vertical);
+        assert!(Tile::from_raw(3).rotation().is_none());
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(base64("AQIDBA==").unwrap(), vec![1, 2, 3, 4]);
+        let tiles = decode_tiles(Some("base64"), None, " AQAAAAAAAIA= ", 2).unwrap();
+        assert_eq!(tiles[0].gid, 1);
+        assert!(tiles[1].flip_horizontal && tiles[1].is_empty());
+        let tiles = decode_tiles(Some("csv"), None, "\n1,2,\n3,0\n", 4).unwrap();
+        assert_eq!(tiles[2].gid, 3);
+        assert!(decode_tiles(Some("base64"), Some("zlib"), "", 0).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use std::{collections::HashMap, path::Path};
+
+use super::{
+    decode_tiles, json::Value, non_zero, tmx, Layer, MapObject, ObjectLayer, ObjectShape,
+    Properties, PropertyValue, Tile, TileLayer, Tilemap, Tileset,
+};
+
+fn field<'a>(v: &'a Value, name: &str) -> Result<&'a Value, String> {
+    v.get(name).ok_or_else(|| format!("missing {}", name))
+}
+
+fn num(v: &Value, name: &str) -> Result<f64, String> {
+    field(v, name)?
+        .as_f64()
+        .ok_or_else(|| format!("{} should be a number", name))
+}
+
+fn num_or(v: &Value, name: &str, default: f64) -> Result<f64, String> {
+    match v.get(name) {
+        Some(_) => num(v, name),
+        None => Ok(default),
+    }
+}
+
+fn uint(v: &Value, name: &str) -> Result<u32, String> {
+    let n = num(v, name)?;
+    if n < 0. || n > u32::MAX as f64 || n.fract() != 0. {
+        return Err(format!("{} should be a positive integer", name));
+    }
+    Ok(n as u32)
+}
+
+fn uint_or(v: &Value, name: &str, default: u32) -> Result<u32, String> {
+    match v.get(name) {
+        Some(_) => uint(v, name),
+        None => Ok(default),
+    }
+}
+
+fn string<'a>(v: &'a Value, name: &str) -> &'a str {
+    v.get(name).and_then(|v| v.as_str()).unwrap_or_default()
+}
+
+fn visible(v: &Value) -> bool {
+    v.get("visible").and_then(|v| v.as_bool()).unwrap_or(true)
+}
+
+fn offset(v: &Value) -> Result<(i32, i32), String> {
+    Ok((
+        num_or(v, "offsetx", 0.)? as i32,
+        num_or(v, "offsety", 0.)? as i32,
+    ))
+}
+
+fn properties(v: &Value) -> Result<Properties, String> {
+    let mut out = HashMap::new();
+    for p in v
+        .get("properties")
+        .and_then(|v| v.as_array())
+        .unwrap_or_default()
+    {
+        let kind = string(p, "type");
+        let value = match field(p, "value")? {
+            Value::String(s) => PropertyValue::parse(kind, s)?,
+            Value::Bool(b) => PropertyValue::Bool(*b),
+            Value::Number(n) => match kind {
+                "int" | "object" => PropertyValue::Int(*n as i64),
+                _ => PropertyValue::Float(*n),
+            },
+            _ => continue, // class properties
+        };
+        out.insert(string(p, "name").to_owned(), value);
+    }
+    Ok(out)
+}
+
+pub(super) fn map(root: &Value, dir: &Path) -> Result<Tilemap, String> {
+    let orientation = string(root, "orientation");
+    if !orientation.is_empty() && orientation != "orthogonal" {
+        return Err(format!("{} maps aren't supported", orientation));
+    }
+    if root.get("infinite").and_then(|v| v.as_bool()) == Some(true) {
+        return Err("infinite maps aren't supported".to_owned());
+    }
+
+    let mut tilesets = Vec::new();
+    for t in field(root, "tilesets")?.as_array().unwrap_or_default() {
+        let first_gid = uint(t, "firstgid")?;
+        let tileset = match t.get("source").and_then(|v| v.as_str()) {
+            Some(source) => tmx::external_tileset(&dir.join(source), first_gid)?,
+            None => tileset(t, first_gid, dir)?,
+        };
+        tilesets.push(tileset);
+    }
+
+    let mut layers = Vec::new();
+    self::layers(root, &mut layers)?;
+
+    Ok(Tilemap {
+        width: uint(root, "width")?,
+        height: uint(root, "height")?,
+        tile_width: non_zero(uint(root, "tilewidth")?, "tilewidth")?,
+        tile_height: non_zero(uint(root, "tileheight")?, "tileheight")?,
+        tilesets,
+        layers,
+        properties: properties(root)?,
+    })
+}
+
+pub(super) fn tileset(v: &Value, first_gid: u32, dir: &Path) -> Result<Tileset, String> {
+    let image = match v.get("image").and_then(|v| v.as_str()) {
+        Some(v) => v,
+        None => return Err("image collection tilesets aren't supported".to_owned()),
+    };
+    let mut tile_properties = HashMap::new();
+    for tile in v
+        .get("tiles")
+        .and_then(|v| v.as_array())
+        .unwrap_or_default()
+    {
+        let properties = properties(tile)?;
+        if !properties.is_empty() {
+            tile_properties.insert(uint(tile, "id")?, properties);
+        }
+    }
+    Ok(Tileset {
+        first_gid,
+        name: string(v, "name").to_owned(),
+        tile_width: non_zero(uint(v, "tilewidth")?, "tilewidth")?,
+        tile_height: non_zero(uint(v, "tileheight")?, "tileheight")?,
+        spacing: uint_or(v, "spacing", 0)?,
+        margin: uint_or(v, "margin", 0)?,
+        columns: uint(v, "columns")?,
+        tile_count: uint(v, "tilecount")?,
+        image: dir.join(image),
+        properties: properties(v)?,
+        tile_properties,
+    })
+}
+
+fn layers(parent: &Value, out: &mut Vec<Layer>) -> Result<(), String> {
+    for l in parent
+        .get("layers")
+        .and_then(|v| v.as_array())
+        .unwrap_or_default()
+    {
+        match string(l, "type") {
+            "tilelayer" => out.push(Layer::Tiles(tile_layer(l)?)),
+            "objectgroup" => out.push(Layer::Objects(object_layer(l)?)),
+            "group" => {
+                let start = out.len();
+                layers(l, out)?;
+                // children take on the group's visibility and offset
+                let (visible, (ox, oy)) = (self::visible(l), offset(l)?);
+                for layer in out[start..].iter_mut() {
+                    let (v, o) = match layer {
+                        Layer::Tiles(l) => (&mut l.visible, &mut l.offset),
+                        Layer::Objects(l) => (&mut l.visible, &mut l.offset),
+                    };
+                    *v &= visible;
+                    *o = (o.0 + ox, o.1 + oy);
+                }
+            }
+            _ => {} // image layers and anything else
+        }
+    }
+    Ok(())
+}
+
+fn tile_layer(v: &Value) -> Result<TileLayer, String> {
+    let width = uint(v, "width")?;
+    let height = uint(v, "height")?;
+    if v.get("chunks").is_some() {
+        return Err("infinite maps aren't supported".to_owned());
+    }
+    let len = width
+        .checked_mul(height)
+        .ok_or_else(|| format!("layer size {}x{} is too big", width, height))?
+        as usize;
+    let tiles = match field(v, "data")? {
+        Value::String(data) => decode_tiles(
+            v.get("encoding").and_then(|v| v.as_str()),
+            v.get("compression").and_then(|v| v.as_str()),
+            data,
+            len,
+        )?,
+        Value::Array(data) => {
+            if data.len() != len {
+                return Err(format!("expected {} tiles, got {}", len, data.len()));
+            }
+            data.iter()
+                .map(|t| match t.as_f64() {
+                    Some(n) if n >= 0. && n <= u32::MAX as f64 => Ok(Tile::from_raw(n as u32)),
+                    _ => Err("bad tile".to_owned()),
+                })
+                .collect::<Result<_, _>>()?
+        }
+        _ => return Err("bad layer data".to_owned()),
+    };
+    Ok(TileLayer {
+        name: string(v, "name").to_owned(),
+        width,
+        height,
+        tiles,
+        visible: visible(v),
+        offset: offset(v)?,
+        properties: properties(v)?,
+    })
+}
+
+fn points(v: &[Value]) -> Result<Vec<(f32, f32)>, String> {
+    v.iter()
+        .map(|p| Ok((num(p, "x")? as f32, num(p, "y")? as f32)))
+        .collect()
+}
+
+fn object_layer(v: &Value) -> Result<ObjectLayer, String> {
+    let mut objects = Vec::new();
+    for o in v
+        .get("objects")
+        .and_then(|v| v.as_array())
+        .unwrap_or_default()
+    {
+        let flag = |name: &str| o.get(name).and_then(|v| v.as_bool()) == Some(true);
+        let shape = if flag("ellipse") {
+            ObjectShape::Ellipse
+        } else if flag("point") {
+            ObjectShape::Point
+        } else if let Some(p) = o.get("polygon").and_then(|v| v.as_array()) {
+            ObjectShape::Polygon(points(p)?)
+        } else if let Some(p) = o.get("polyline").and_then(|v| v.as_array()) {
+            ObjectShape::Polyline(points(p)?)
+        } else {
+            ObjectShape::Rectangle
+        };
+        let tile = match o.get("gid") {
+            Some(_) => Some(Tile::from_raw(uint(o, "gid")?)),
+            None => None,
+        };
+        let class = match string(o, "class") {
+            "" => string(o, "type"),
+            v => v,
+        };
+        objects.push(MapObject {
+            id: uint_or(o, "id", 0)?,
+            name: string(o, "name").to_owned(),
+            class: class.to_owned(),
+            x: num_or(o, "x", 0.)? as f32,
+            y: num_or(o, "y", 0.)? as f32,
+            width: num_or(o, "width", 0.)? as f32,
+            height: num_or(o, "height", 0.)? as f32,
+            rotation: num_or(o, "rotation", 0.)? as f32,
+            visible: visible(o),
+            shape,
+            tile,
+            properties: properties(o)?,
+        });
+    }
+    Ok(ObjectLayer {
+        name: string(v, "name").to_owned(),
+        objects,
+        visible: visible(v),
+        offset: offset(v)?,
+        properties: properties(v)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        let text = r#"{
+ "width": 2, "height": 1, "tilewidth": 8, "tileheight": 8,
+ "orientation": "orthogonal", "infinite": false,
+ "properties": [{"name": "level", "type": "int", "value": 3}],
+ "tilesets": [{"firstgid": 1, "name": "t", "tilewidth": 8, "tileheight": 8,
+   "tilecount": 2, "columns": 2, "image": "t.png"}],
+ "layers": [
+  {"type": "tilelayer", "name": "a", "width": 2, "height": 1,
+   "encoding": "base64", "data": "AgAAAAEAAEA="},
+  {"type": "objectgroup", "name": "o", "visible": false, "objects": [
+   {"id": 1, "class": "door", "x": 1.5, "y": 2, "width": 8, "height": 8,
+    "ellipse": true, "properties": [{"name": "to", "type": "string", "value": "b"}]}
+  ]}
+ ]
+}"#;
+        let map = Tilemap::from_json(text, Path::new("")).unwrap();
+        assert_eq!(map.properties["level"], PropertyValue::Int(3));
+        let a = match &map.layers[0] {
+            Layer::Tiles(l) => l,
+            _ => panic!(),
+        };
+        assert_eq!(a.tile(0, 0).unwrap().gid, 2);
+        assert!(a.tile(1, 0).unwrap().flip_vertical);
+
+        let o = match &map.layers[1] {
+            Layer::Objects(l) => l,
+            _ => panic!(),
+        };
+        assert!(!o.visible);
+        assert_eq!(o.objects[0].x, 1.5);
+        assert_eq!(o.objects[0].shape, ObjectShape::Ellipse);
+        assert_eq!(
+            o.objects[0].properties["to"],
+            PropertyValue::String("b".to_owned())
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::{collections::HashMap, path::Path};
+
+use super::{
+    decode_tiles, is_tmx, non_zero, read, tmj,
+    xml::{self, Element},
+    Layer, MapObject, ObjectLayer, ObjectShape, Properties, PropertyValue, Tile, TileLayer,
+    Tilemap, Tileset,
+};
+
+fn attr<'a>(e: &'a Element, name: &str) -> Result<&'a str, String> {
+    e.attr(name)
+        .ok_or_else(|| format!("<{}> is missing {}", e.name, name))
+}
+
+fn parse_attr<V: std::str::FromStr>(e: &Element, name: &str) -> Result<V, String> {
+    let v = attr(e, name)?;
+    v.parse()
+        .map_err(|_| format!("<{}> has a bad {}: {}", e.name, name, v))
+}
+
+fn parse_attr_or<V: std::str::FromStr>(e: &Element, name: &str, default: V) -> Result<V, String> {
+    match e.attr(name) {
+        Some(_) => parse_attr(e, name),
+        None => Ok(default),
+    }
+}
+
+fn properties(e: &Element) -> Result<Properties, String> {
+    let mut out = HashMap::new();
+    if let Some(properties) = e.child("properties") {
+        for p in properties.children_named("property") {
+            let kind = p.attr("type").unwrap_or("string");
+            // multi line strings are stored as text
+            let value = p.attr("value").unwrap_or(&p.text);
+            out.insert(
+                attr(p, "name")?.to_owned(),
+                PropertyValue::parse(kind, value)?,
+            );
+        }
+    }
+    Ok(out)
+}
+
+fn offset(e: &Element) -> Result<(i32, i32), String> {
+    let x: f32 = parse_attr_or(e, "offsetx", 0.)?;
+    let y: f32 = parse_attr_or(e, "offsety", 0.)?;
+    Ok((x as i32, y as i32))
+}
+
+fn visible(e: &Element) -> bool {
+    e.attr("visible") != Some("0")
+}
+
+pub(super) fn map(root: &Element, dir: &Path) -> Result<Tilemap, String> {
+    if root.name != "map" {
+        return Err(format!("expected <map>, got <{}>", root.name));
+    }
+    if let Some(orientation) = root.attr("orientation").filter(|o| *o != "orthogonal") {
+        return Err(format!("{} maps aren't supported", orientation));
+    }
+    if root.attr("infinite") == Some("1") {
+        return Err("infinite maps aren't supported".to_owned());
+    }
+
+    let mut tilesets = Vec::new();
+    for e in root.children_named("tileset") {
+        let first_gid = parse_attr(e, "firstgid")?;
+        let tileset = match e.attr("source") {
+            Some(source) => external_tileset(&dir.join(source), first_gid)?,
+            None => tileset(e, first_gid, dir)?,
+        };
+        tilesets.push(tileset);
+    }
+
+    let mut layers = Vec::new();
+    self::layers(root, &mut layers)?;
+
+    Ok(Tilemap {
+        width: parse_attr(root, "width")?,
+        height: parse_attr(root, "height")?,
+        tile_width: non_zero(parse_attr(root, "tilewidth")?, "tilewidth")?,
+        tile_height: non_zero(parse_attr(root, "tileheight")?, "tileheight")?,
+        tilesets,
+        layers,
+        properties: properties(root)?,
+    })
+}
+
+/// a tileset in its own file. may be .tsx or json
+pub(super) fn external_tileset(path: &Path, first_gid: u32) -> Result<Tileset, String> {
+    let text = read(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let r = if is_tmx(path) {
+        xml::parse(&text).and_then(|root| tileset(&root, first_gid, dir))
+    } else {
+        super::json::parse(&text).and_then(|root| tmj::tileset(&root, first_gid, dir))
+    };
+    r.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn tileset(e: &Element, first_gid: u32, dir: &Path) -> Result<Tileset, String> {
+    let image = match e.child("image") {
+        Some(v) => v,
+        None => return Err("image collection tilesets aren't supported".to_owned()),
+    };
+    let mut tile_properties = HashMap::new();
+    for tile in e.children_named("tile") {
+        let properties = properties(tile)?;
+        if !properties.is_empty() {
+            tile_properties.insert(parse_attr(tile, "id")?, properties);
+        }
+    }
+    Ok(Tileset {
+        first_gid,
+        name: e.attr("name").unwrap_or_default().to_owned(),
+        tile_width: non_zero(parse_attr(e, "tilewidth")?, "tilewidth")?,
+        tile_height: non_zero(parse_attr(e, "tileheight")?, "tileheight")?,
+        spacing: parse_attr_or(e, "spacing", 0)?,
+        margin: parse_attr_or(e, "margin", 0)?,
+        columns: parse_attr(e, "columns")?,
+        tile_count: parse_attr(e, "tilecount")?,
+        image: dir.join(attr(image, "source")?),
+        properties: properties(e)?,
+        tile_properties,
+    })
+}
+
+fn layers(parent: &Element, out: &mut Vec<Layer>) -> Result<(), String> {
+    for e in parent.children.iter() {
+        match e.name.as_str() {
+            "layer" => out.push(Layer::Tiles(tile_layer(e)?)),
+            "objectgroup" => out.push(Layer::Objects(object_layer(e)?)),
+            "group" => {
+                let start = out.len();
+                layers(e, out)?;
+                // children take on the group's visibility and offset
+                let (visible, (ox, oy)) = (self::visible(e), offset(e)?);
+                for layer in out[start..].iter_mut() {
+                    let (v, o) = match layer {
+                        Layer::Tiles(l) => (&mut l.visible, &mut l.offset),
+                        Layer::Objects(l) => (&mut l.visible, &mut l.offset),
+                    };
+                    *v &= visible;
+                    *o = (o.0 + ox, o.1 + oy);
+                }
+            }
+            _ => {} // image layers and anything else
+        }
+    }
+    Ok(())
+}
+
+fn tile_layer(e: &Element) -> Result<TileLayer, String> {
+    let width: u32 = parse_attr(e, "width")?;
+    let height: u32 = parse_attr(e, "height")?;
+    let data = match e.child("data") {
+        Some(v) => v,
+        None => return Err("<layer> is missing <data>".to_owned()),
+    };
+    if data.child("chunk").is_some() {
+        return Err("infinite maps aren't supported".to_owned());
+    }
+    let len = width
+        .checked_mul(height)
+        .ok_or_else(|| format!("layer size {}x{} is too big", width, height))?
+        as usize;
+    let tiles = match data.attr("encoding") {
+        Some(encoding) => decode_tiles(Some(encoding), data.attr("compression"), &data.text, len)?,
+        None => {
+            // xml <tile gid=""/> elements. empty tiles may leave out the gid
+            let tiles = data
+                .children_named("tile")
+                .map(|t| parse_attr_or(t, "gid", 0).map(Tile::from_raw))
+                .collect::<Result<Vec<_>, _>>()?;
+            if tiles.len() != len {
+                return Err(format!("expected {} tiles, got {}", len, tiles.len()));
+            }
+            tiles
+        }
+    };
+    Ok(TileLayer {
+        name: e.attr("name").unwrap_or_default().to_owned(),
+        width,
+        height,
+        tiles,
+        visible: visible(e),
+        offset: offset(e)?,
+        properties: properties(e)?,
+    })
+}
+
+fn points(e: &Element) -> Result<Vec<(f32, f32)>, String> {
+    attr(e, "points")?
+        .split_whitespace()
+        .map(|p| {
+            let (x, y) = p.split_once(',').unwrap_or((p, ""));
+            match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Ok((x, y)),
+                _ => Err(format!("bad point: {}", p)),
+            }
+        })
+        .collect()
+}
+
+fn object_layer(e: &Element) -> Result<ObjectLayer, String> {
+    let mut objects = Vec::new();
+    for o in e.children_named("object") {
+        let shape = if o.child("ellipse").is_some() {
+            ObjectShape::Ellipse
+        } else if o.child("point").is_some() {
+            ObjectShape::Point
+        } else if let Some(p) = o.child("polygon") {
+            ObjectShape::Polygon(points(p)?)
+        } else if let Some(p) = o.child("polyline") {
+            ObjectShape::Polyline(points(p)?)
+        } else {
+            ObjectShape::Rectangle
+        };
+        let tile = match o.attr("gid") {
+            Some(_) => Some(Tile::from_raw(parse_attr(o, "gid")?)),
+            None => None,
+        };
+        objects.push(MapObject {
+            id: parse_attr_or(o, "id", 0)?,
+            name: o.attr("name").unwrap_or_default().to_owned(),
+            class: o
+                .attr("class")
+                .or(o.attr("type"))
+                .unwrap_or_default()
+                .to_owned(),
+            x: parse_attr_or(o, "x", 0.)?,
+            y: parse_attr_or(o, "y", 0.)?,
+            width: parse_attr_or(o, "width", 0.)?,
+            height: parse_attr_or(o, "height", 0.)?,
+            rotation: parse_attr_or(o, "rotation", 0.)?,
+            visible: visible(o),
+            shape,
+            tile,
+            properties: properties(o)?,
+        });
+    }
+    Ok(ObjectLayer {
+        name: e.attr("name").unwrap_or_default().to_owned(),
+        objects,
+        visible: visible(e),
+        offset: offset(e)?,
+        properties: properties(e)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="music" type="file" value="a.ogg"/>
+  <property name="dark" type="bool" value="true"/>
+ </properties>
+ <tileset firstgid="1" name="t" tilewidth="16" tileheight="16" spacing="1" tilecount="4" columns="2">
+  <image source="tiles.png" width="33" height="33"/>
+  <tile id="3"><properties><property name="solid" type="bool" value="true"/></properties></tile>
+ </tileset>
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,2,
+2147483651,0
+</data>
+ </layer>
+ <group name="g" visible="0" offsetx="4">
+  <objectgroup id="2" name="things">
+   <object id="1" name="spawn" type="player" x="8" y="24"><point/></object>
+   <object id="2" x="0" y="0"><polygon points="0,0 16,0 16,16"/></object>
+  </objectgroup>
+ </group>
+</map>
+"#;
+        let map = Tilemap::from_tmx(text, Path::new("maps")).unwrap();
+        assert_eq!(map.pixel_size(), (32, 32));
+        assert_eq!(map.properties["dark"], PropertyValue::Bool(true));
+
+        let tileset = map.tileset(4).unwrap();
+        assert_eq!(tileset.image, Path::new("maps").join("tiles.png"));
+        assert_eq!(tileset.source(4).unwrap().x, 17);
+        assert_eq!(
+            tileset.tile_properties[&3]["solid"],
+            PropertyValue::Bool(true)
+        );
+
+        let ground = match map.layer("ground").unwrap() {
+            Layer::Tiles(l) => l,
+            _ => panic!(),
+        };
+        let tile = ground.tile(0, 1).unwrap();
+        assert!(tile.gid == 3 && tile.flip_horizontal);
+        assert!(ground.tile(1, 1).is_none());
+
+        let things = match map.layer("things").unwrap() {
+            Layer::Objects(l) => l,
+            _ => panic!(),
+        };
+        assert!(!things.visible && things.offset == (4, 0));
+        assert_eq!(things.objects[0].class, "player");
+        assert_eq!(things.objects[0].shape, ObjectShape::Point);
+        assert!(matches!(&things.objects[1].shape, ObjectShape::Polygon(p) if p.len() == 3));
+
+        let huge = text.replace(
+            r#"<layer id="1" name="ground" width="2" height="2">"#,
+            r#"<layer id="1" name="ground" width="65536" height="65536">"#,
+        );
+        assert!(Tilemap::from_tmx(&huge, Path::new("maps")).is_err());
+    }
+}
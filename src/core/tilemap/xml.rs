@@ -0,0 +1,222 @@
+/// just enough xml for tiled files. no namespaces, doctypes or cdata
+#[derive(Debug, Default)]
+pub(super) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+}
+
+pub(super) fn parse(s: &str) -> Result<Element, String> {
+    let mut p = Parser { s, i: 0 };
+    p.skip_misc()?;
+    let root = p.element()?;
+    p.skip_misc()?;
+    if p.i != s.len() {
+        return Err(p.err("content after root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn err(&self, msg: &str) -> String {
+        format!("xml: {} at byte {}", msg, self.i)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.i..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.i += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), String> {
+        match self.rest().find(end) {
+            Some(v) => {
+                self.i += v + end.len();
+                Ok(())
+            }
+            None => Err(self.err(&format!("missing {}", end))),
+        }
+    }
+
+    /// whitespace, comments and processing instructions
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.err("expected name"));
+        }
+        let name = self.rest()[..len].to_owned();
+        self.i += len;
+        Ok(name)
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        if !self.rest().starts_with('<') {
+            return Err(self.err("expected element"));
+        }
+        self.i += 1;
+        let mut element = Element {
+            name: self.name()?,
+            ..Default::default()
+        };
+
+        // attributes
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.i += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.i += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.err("expected ="));
+            }
+            self.i += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(c) if c == '"' || c == '\'' => c,
+                _ => return Err(self.err("expected quote")),
+            };
+            self.i += 1;
+            let len = match self.rest().find(quote) {
+                Some(v) => v,
+                None => return Err(self.err("unterminated attribute")),
+            };
+            let value = unescape(&self.rest()[..len]);
+            self.i += len + 1;
+            element.attributes.push((key, value));
+        }
+
+        // content
+        loop {
+            let text_len = self.rest().find('<').unwrap_or(self.rest().len());
+            element.text.push_str(&unescape(&self.rest()[..text_len]));
+            self.i += text_len;
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.err(&format!("unclosed <{}>", element.name)));
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("</") {
+                self.i += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.err(&format!("expected </{}>", element.name)));
+                }
+                self.skip_past(">")?;
+                return Ok(element);
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+    }
+}
+
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(v) => v,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                } else if let Some(dec) = entity.strip_prefix('#') {
+                    dec.parse().ok().and_then(char::from_u32)
+                } else {
+                    None
+                }
+            }
+        };
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!-- c --><map a='1' b=\"x &amp; y\">\n <layer/>\n <data>1,2</data>\n</map>\n",
+        )
+        .unwrap();
+        assert_eq!(root.name, "map");
+        assert_eq!(root.attr("b"), Some("x & y"));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.child("data").unwrap().text, "1,2");
+        assert!(parse("<map><layer></map>").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    core::{event::Event, System},
+    ui::{
+        util::{
+            frame_timing::{FrameLimiter, FrameStats, FrameTiming},
+            rust::CellRefOrCell,
+        },
+        widget::{accumulate_events, FrameTransiency, HandlerReturnValue},
+    },
+};
+
+/// accumulates frame time into fixed duration ticks
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    /// duration of one tick. e.g. 60 Hz
+    pub step: Duration,
+    /// most ticks run per frame. if the simulation falls further behind then
+    /// the extra time is dropped, so it slows down instead of spiraling
+    pub max_steps: u32,
+    /// time not yet simulated. less than a step between frames
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            max_steps: 5,
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// from ticks per second
+    pub fn from_rate(rate: u32) -> Self {
+        Self::new(Duration::from_secs(1) / rate.max(1))
+    }
+
+    /// add the frame time. returns how many ticks to run
+    pub fn advance(&mut self, dt: Duration) -> u32 {
+        if self.step.is_zero() {
+            return 0;
+        }
+        self.accumulator += dt;
+        let mut ticks = 0;
+        while self.accumulator >= self.step && ticks < self.max_steps {
+            self.accumulator -= self.step;
+            ticks += 1;
+        }
+        if self.accumulator >= self.step {
+            // caught up as much as allowed. keep the portion of a tick
+            let remainder = self.accumulator.as_nanos() % self.step.as_nanos();
+            self.accumulator = Duration::from_nanos(remainder as u64);
+        }
+        ticks
+    }
+
+    /// from 0 to 1. how far the time is between the last tick and the next.
+    /// used to interpolate between the previous and current states when drawing
+    pub fn alpha(&self) -> f32 {
+        if self.step.is_zero() {
+            return 1.;
+        }
+        (self.accumulator.as_secs_f32() / self.step.as_secs_f32()).clamp(0., 1.)
+    }
+
+    /// time until the next tick is due
+    pub fn until_next(&self) -> Duration {
+        self.step.saturating_sub(self.accumulator)
+    }
+
+    /// drop time not yet simulated
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// the simulation ticks at a fixed rate. frames are drawn continuously
+    #[default]
+    Fixed,
+    /// no ticks. frames only happen on events or when the ui requests it, same
+    /// as gui_loop(). e.g. while in a menu
+    Lazy,
+}
+
+/// see game_loop()
+pub trait GameLoopHandler<T: System> {
+    /// checked at the start of each frame
+    fn mode(&self) -> LoopMode {
+        LoopMode::Fixed
+    }
+
+    /// once per frame, before any ticks. typically update_gui(). dt is the
+    /// duration since the previous frame
+    ///
+    /// events left (not set to None) are given to the next tick, since a frame
+    /// can happen without one
+    fn update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        dt: Duration,
+    ) -> Result<HandlerReturnValue, String>;
+
+    /// one tick of the simulation. events is empty for all but the first tick
+    /// after they were received
+    fn fixed_update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        step: Duration,
+    ) -> Result<(), String>;
+
+    /// once per frame, after the ticks. alpha is from FixedTimestep::alpha(),
+    /// or 1 in lazy mode. not called if update() returned NextFrameNow. the
+    /// loop presents afterward
+    fn draw(&mut self, system: &mut T, alpha: f32) -> Result<(), String>;
+}
+
+/// like gui_loop(), but also runs a simulation at a fixed rate
+pub struct GameLoop<'state> {
+    /// in lazy mode, used in the same way as gui_loop()
+    pub max_delay: Duration,
+    pub timestep: FixedTimestep,
+    /// caps the frame rate, e.g. when vsync is off. in lazy mode, only frames
+    /// requested with NextFrameQuick are capped
+    pub limiter: FrameLimiter,
+    /// recorded each frame. can be shared with an FpsOverlay
+    pub stats: CellRefOrCell<'state, FrameStats>,
+}
+
+impl<'state> GameLoop<'state> {
+    pub fn new(max_delay: Duration, timestep: FixedTimestep) -> Self {
+        Self {
+            max_delay,
+            timestep,
+            limiter: Default::default(),
+            stats: std::cell::Cell::new(FrameStats::default()).into(),
+        }
+    }
+
+    /// in fixed mode, events are accumulated until the next tick is due (or
+    /// the frame limit allows the next frame). System::present() is called
+    /// after each draw
+    pub fn run<T: System, H: GameLoopHandler<T>>(
+        &mut self,
+        system: &mut T,
+        handler: &mut H,
+    ) -> Result<(), String> {
+        // accumulate the events for this frame
+        let mut events_accumulator: Vec<Option<Event>> = Vec::new();
+        // events left by update(), for the next tick
+        let mut tick_events: Vec<Option<Event>> = Vec::new();
+
+        let mut previous_mode = LoopMode::Lazy; // don't catch up on the first frame
+        let mut previous_handle_call = Instant::now();
+        // of the previous frame, once its total is known
+        let mut timing: Option<FrameTiming> = None;
+        loop {
+            let next_handle_call = Instant::now();
+            let dt = next_handle_call - previous_handle_call;
+            previous_handle_call = next_handle_call;
+            if let Some(mut timing) = timing.take() {
+                timing.total = dt;
+                self.stats.scope_take().record(timing);
+            }
+
+            let mode = handler.mode();
+            let frame_transiency = match handler.update(system, &mut events_accumulator, dt)? {
+                HandlerReturnValue::Stop => return Ok(()),
+                HandlerReturnValue::Some(v) => v,
+            };
+
+            let alpha = match mode {
+                LoopMode::Fixed => {
+                    tick_events.extend(events_accumulator.drain(..).filter(|e| e.is_some()));
+                    // time spent idle in lazy mode isn't simulated
+                    let ticks = match previous_mode {
+                        LoopMode::Fixed => self.timestep.advance(dt),
+                        LoopMode::Lazy => 0,
+                    };
+                    for _ in 0..ticks {
+                        handler.fixed_update(system, &mut tick_events, self.timestep.step)?;
+                        tick_events.clear();
+                    }
+                    self.timestep.alpha()
+                }
+                LoopMode::Lazy => {
+                    tick_events.clear();
+                    self.timestep.reset();
+                    1.
+                }
+            };
+            previous_mode = mode;
+            events_accumulator.clear();
+
+            let draw_start = Instant::now();
+            let mut frame_timing = FrameTiming {
+                update: draw_start - previous_handle_call,
+                ..Default::default()
+            };
+            if !matches!(frame_transiency, FrameTransiency::NextFrameNow) {
+                handler.draw(system, alpha)?;
+                let present_start = Instant::now();
+                frame_timing.draw = present_start - draw_start;
+                system.present()?;
+                frame_timing.present = Instant::now() - present_start;
+            }
+            timing = Some(frame_timing);
+
+            match mode {
+                LoopMode::Fixed => {
+                    // always give events a chance to come in, even when behind
+                    let elapsed = Instant::now() - previous_handle_call;
+                    let wait = self
+                        .timestep
+                        .until_next()
+                        .saturating_sub(elapsed)
+                        .max(self.limiter.remaining(Instant::now()))
+                        .max(Duration::from_millis(1));
+                    let max_time = Instant::now() + wait;
+                    // the end of the frame limit is spun for accuracy
+                    let spin = match self.limiter.target {
+                        Some(_) => self.limiter.spin,
+                        None => Duration::ZERO,
+                    };
+                    loop {
+                        let now = Instant::now();
+                        if max_time.saturating_duration_since(now) <= spin {
+                            break;
+                        }
+                        let time_to_wait = max_time - now - spin;
+                        if time_to_wait < Duration::from_millis(1) {
+                            // event_timeout() is in whole milliseconds, and
+                            // would return immediately
+                            std::thread::sleep(time_to_wait);
+                            break;
+                        }
+                        if let Some(event) = system.event_timeout(time_to_wait) {
+                            events_accumulator.push(Some(event));
+                        }
+                    }
+                    self.limiter.wait();
+                }
+                LoopMode::Lazy => accumulate_events(
+                    system,
+                    &mut events_accumulator,
+                    frame_transiency,
+                    previous_handle_call,
+                    self.max_delay,
+                    &mut self.limiter,
+                ),
+            }
+        }
+    }
+}
+
+/// see GameLoop
+pub fn game_loop<T: System, H: GameLoopHandler<T>>(
+    max_delay: Duration,
+    timestep: FixedTimestep,
+    system: &mut T,
+    handler: &mut H,
+) -> Result<(), String> {
+    GameLoop::new(max_delay, timestep).run(system, handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10));
+        assert_eq!(timestep.advance(Duration::from_millis(5)), 0);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+        assert_eq!(timestep.advance(Duration::from_millis(25)), 2);
+        assert_eq!(timestep.until_next(), Duration::from_millis(5));
+        timestep.reset();
+        assert_eq!(timestep.alpha(), 0.);
+    }
+
+    #[test]
+    fn test_catch_up_limit() {
+        let mut timestep = FixedTimestep::from_rate(100);
+        timestep.max_steps = 3;
+        assert_eq!(timestep.advance(Duration::from_millis(1_004)), 3);
+        // the rest is dropped, except the portion of a tick
+        assert_eq!(timestep.until_next(), Duration::from_millis(6));
+        assert_eq!(timestep.advance(Duration::from_millis(6)), 1);
+    }
+}
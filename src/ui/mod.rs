@@ -1,2 +1,4 @@
+pub mod game_loop;
+pub mod scene;
 pub mod util;
 pub mod widget;
@@ -0,0 +1,551 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    core::{
+        clipping_rect::ClippingRect,
+        color::{Color, Surface},
+        event::Event,
+        texture_rect::TextureRect,
+        System, TextureHandle,
+    },
+    ui::{
+        game_loop::{GameLoopHandler, LoopMode},
+        util::{length::AspectRatioPreferredDirection, rect::FRect},
+        widget::{place, FrameTransiency, HandlerReturnValue, Widget, WidgetUpdateEvent},
+    },
+};
+
+/// what a scene wants to happen to the stack. see Scene::update()
+pub enum SceneCommand<'a, T: System> {
+    /// nothing changes
+    None,
+    /// put a scene on the top of the stack
+    Push(Box<dyn Scene<'a, T> + 'a>),
+    /// remove this scene. the loop stops once no scenes are left
+    Pop,
+    /// remove this scene and put another in its place
+    Replace(Box<dyn Scene<'a, T> + 'a>),
+    /// stop the loop
+    Stop,
+}
+
+pub struct SceneUpdate<'a, T: System> {
+    pub transiency: FrameTransiency,
+    pub command: SceneCommand<'a, T>,
+}
+
+impl<'a, T: System> Default for SceneUpdate<'a, T> {
+    fn default() -> Self {
+        Self {
+            transiency: Default::default(),
+            command: SceneCommand::None,
+        }
+    }
+}
+
+/// e.g. main menu, gameplay, pause overlay, settings. see SceneStack
+pub trait Scene<'a, T: System> {
+    /// the ui, placed over the scene's area. updated before update() and drawn
+    /// after draw()
+    fn root(&mut self) -> Option<&mut dyn Widget<T>> {
+        None
+    }
+
+    /// once per frame, with the events the root widget didn't consume
+    fn update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        dt: Duration,
+    ) -> Result<SceneUpdate<'a, T>, String>;
+
+    /// see GameLoopHandler::fixed_update()
+    fn fixed_update(
+        &mut self,
+        _system: &mut T,
+        _events: &mut [Option<Event>],
+        _step: Duration,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// drawn before the root widget, e.g. the game world. area is the window,
+    /// moved while sliding. alpha is from GameLoopHandler::draw()
+    fn draw(&mut self, _system: &mut T, _area: FRect, _alpha: f32) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// while this is the top scene
+    fn mode(&self) -> LoopMode {
+        LoopMode::Fixed
+    }
+
+    /// the scenes below are drawn too, e.g. a pause menu
+    fn overlay(&self) -> bool {
+        false
+    }
+
+    /// the scene below is updated too, with the events that this scene
+    /// didn't consume
+    fn events_fall_through(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TransitionStyle {
+    /// immediate
+    #[default]
+    Cut,
+    /// fade to the color, change scenes, then fade back
+    Fade(Color),
+    /// the new scene slides in from the right. a popped scene slides out
+    Slide,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Transition {
+    pub style: TransitionStyle,
+    pub duration: Duration,
+}
+
+struct ActiveTransition<'a, T: System> {
+    start: Instant,
+    transition: Transition,
+    /// fade: applied halfway through. the index of the scene which gave it
+    pending: Option<(usize, SceneCommand<'a, T>)>,
+    /// slide: a popped or replaced scene, drawn until the transition ends
+    leaving: Option<Box<dyn Scene<'a, T> + 'a>>,
+    /// slide: the leaving scene moves out, instead of the top moving in
+    popping: bool,
+}
+
+impl<'a, T: System> ActiveTransition<'a, T> {
+    /// from 0 to 1
+    fn progress(&self, now: Instant) -> f32 {
+        if self.transition.duration.is_zero() {
+            return 1.;
+        }
+        ((now - self.start).as_secs_f32() / self.transition.duration.as_secs_f32()).clamp(0., 1.)
+    }
+}
+
+/// scenes on top of each other, each with their own update, draw and ui. only
+/// the top scene is updated (see Scene::events_fall_through()) and drawn (see
+/// Scene::overlay())
+///
+/// can be used as the GameLoopHandler, or from the gui_loop() handler
+pub struct SceneStack<'a, T: System> {
+    scenes: Vec<Box<dyn Scene<'a, T> + 'a>>,
+    /// used for each change to the stack
+    pub transition: Transition,
+    active: Option<ActiveTransition<'a, T>>,
+}
+
+impl<'a, T: System> SceneStack<'a, T> {
+    pub fn new(first: Box<dyn Scene<'a, T> + 'a>) -> Self {
+        Self {
+            scenes: vec![first],
+            transition: Default::default(),
+            active: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// immediately, without a transition
+    pub fn push(&mut self, scene: Box<dyn Scene<'a, T> + 'a>) {
+        self.finish_transition();
+        self.scenes.push(scene);
+    }
+
+    /// update the scenes, then change the stack if a scene asked to. stops
+    /// once the stack is empty
+    pub fn update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        dt: Duration,
+    ) -> Result<HandlerReturnValue, String> {
+        let now = Instant::now();
+        self.advance_transition(now);
+        let window = window_area(system)?;
+
+        let mut transiency = FrameTransiency::None;
+        let mut command: Option<(usize, SceneCommand<'a, T>)> = None;
+        for index in (0..self.scenes.len()).rev() {
+            let area = self.scene_area(index, window, now);
+            let scene = &mut self.scenes[index];
+            if let Some(root) = scene.root() {
+                transiency |= update_root(root, events, system, dt, area)?;
+            }
+            let update = scene.update(system, events, dt)?;
+            transiency |= update.transiency;
+            if command.is_none() && !matches!(update.command, SceneCommand::None) {
+                command = Some((index, update.command));
+            }
+            if !scene.events_fall_through() {
+                break;
+            }
+        }
+
+        // the leaving scene keeps its layout while it slides
+        let leaving_area = self.leaving_area(window, now);
+        if let Some(active) = self.active.as_mut() {
+            if let Some(root) = active.leaving.as_mut().and_then(|s| s.root()) {
+                update_root(root, &mut [], system, dt, leaving_area)?;
+            }
+        }
+
+        if let Some((index, command)) = command {
+            if let SceneCommand::Stop = command {
+                return Ok(HandlerReturnValue::Stop);
+            }
+            self.apply(index, command, now);
+            // a pushed scene, or ones uncovered by a pop, are drawn this frame
+            transiency |= self.place_visible(system, window, now)?;
+        }
+        if self.scenes.is_empty() {
+            return Ok(HandlerReturnValue::Stop);
+        }
+        if self.active.is_some() {
+            transiency |= FrameTransiency::NextFrameQuick;
+        }
+        Ok(HandlerReturnValue::Some(transiency))
+    }
+
+    /// the top scene, and the ones below it that events fall through to
+    pub fn fixed_update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        step: Duration,
+    ) -> Result<(), String> {
+        for scene in self.scenes.iter_mut().rev() {
+            scene.fixed_update(system, events, step)?;
+            if !scene.events_fall_through() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// the visible scenes, from the bottom up
+    pub fn draw(&mut self, system: &mut T, alpha: f32) -> Result<(), String> {
+        let now = Instant::now();
+        let window = window_area(system)?;
+        let top = match self.scenes.len().checked_sub(1) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+
+        let (sliding, popping) = match self.active.as_ref() {
+            Some(active) => (
+                matches!(active.transition.style, TransitionStyle::Slide),
+                active.popping,
+            ),
+            None => (false, false),
+        };
+
+        if sliding && !popping {
+            // what's below shows while the top slides in over it
+            for index in self.lowest_visible(top)..top {
+                self.draw_scene(index, system, window, now, alpha)?;
+            }
+            self.draw_leaving(system, window, now, alpha)?;
+            self.draw_scene(top, system, window, now, alpha)?;
+        } else {
+            for index in self.lowest_visible(top + 1)..=top {
+                self.draw_scene(index, system, window, now, alpha)?;
+            }
+            self.draw_leaving(system, window, now, alpha)?;
+        }
+
+        if let Some(active) = self.active.as_ref() {
+            if let TransitionStyle::Fade(color) = active.transition.style {
+                // covered fully at the halfway point
+                let amount = 1. - (active.progress(now) * 2. - 1.).abs();
+                draw_cover(system, color, amount, window)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// lay out the roots which are drawn, without any events
+    fn place_visible(
+        &mut self,
+        system: &mut T,
+        window: FRect,
+        now: Instant,
+    ) -> Result<FrameTransiency, String> {
+        let mut transiency = FrameTransiency::None;
+        let end = self.scenes.len();
+        for index in self.lowest_visible(end)..end {
+            let area = self.scene_area(index, window, now);
+            if let Some(root) = self.scenes[index].root() {
+                transiency |= update_root(root, &mut [], system, Duration::ZERO, area)?;
+            }
+        }
+        Ok(transiency)
+    }
+
+    /// index of the lowest scene drawn, if the top is just below end
+    fn lowest_visible(&self, end: usize) -> usize {
+        let mut index = end;
+        while index > 0 {
+            index -= 1;
+            if !self.scenes[index].overlay() {
+                break;
+            }
+        }
+        index
+    }
+
+    fn draw_scene(
+        &mut self,
+        index: usize,
+        system: &mut T,
+        window: FRect,
+        now: Instant,
+        alpha: f32,
+    ) -> Result<(), String> {
+        let area = self.scene_area(index, window, now);
+        let scene = &mut self.scenes[index];
+        scene.draw(system, area, alpha)?;
+        if let Some(root) = scene.root() {
+            root.draw(system)?;
+        }
+        Ok(())
+    }
+
+    fn draw_leaving(
+        &mut self,
+        system: &mut T,
+        window: FRect,
+        now: Instant,
+        alpha: f32,
+    ) -> Result<(), String> {
+        let area = self.leaving_area(window, now);
+        let scene = match self.active.as_mut().and_then(|a| a.leaving.as_mut()) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        scene.draw(system, area, alpha)?;
+        if let Some(root) = scene.root() {
+            root.draw(system)?;
+        }
+        Ok(())
+    }
+
+    /// the window, except for the top scene sliding in
+    fn scene_area(&self, index: usize, window: FRect, now: Instant) -> FRect {
+        match self.active.as_ref() {
+            Some(active)
+                if matches!(active.transition.style, TransitionStyle::Slide)
+                    && !active.popping
+                    && index + 1 == self.scenes.len() =>
+            {
+                FRect {
+                    x: window.x + window.w * (1. - active.progress(now)),
+                    ..window
+                }
+            }
+            _ => window,
+        }
+    }
+
+    fn leaving_area(&self, window: FRect, now: Instant) -> FRect {
+        match self.active.as_ref() {
+            Some(active) if active.popping => FRect {
+                x: window.x + window.w * active.progress(now),
+                ..window
+            },
+            _ => window,
+        }
+    }
+
+    fn apply(&mut self, index: usize, command: SceneCommand<'a, T>, now: Instant) {
+        self.finish_transition();
+        let transition = self.transition;
+        let mut active = ActiveTransition {
+            start: now,
+            transition,
+            pending: None,
+            leaving: None,
+            popping: false,
+        };
+        let is_top = index + 1 == self.scenes.len();
+        match (transition.style, command) {
+            (_, command) if transition.duration.is_zero() => self.apply_now(index, command),
+            (TransitionStyle::Cut, command) => self.apply_now(index, command),
+            (TransitionStyle::Fade(_), command) => {
+                active.pending = Some((index, command));
+                self.active = Some(active);
+            }
+            (TransitionStyle::Slide, SceneCommand::Push(scene)) => {
+                self.scenes.push(scene);
+                self.active = Some(active);
+            }
+            (TransitionStyle::Slide, SceneCommand::Pop) if is_top => {
+                active.leaving = self.scenes.pop();
+                active.popping = true;
+                self.active = Some(active);
+            }
+            (TransitionStyle::Slide, SceneCommand::Replace(scene)) if is_top => {
+                active.leaving = Some(std::mem::replace(&mut self.scenes[index], scene));
+                self.active = Some(active);
+            }
+            (TransitionStyle::Slide, command) => self.apply_now(index, command),
+        }
+    }
+
+    fn apply_now(&mut self, index: usize, command: SceneCommand<'a, T>) {
+        match command {
+            SceneCommand::Push(scene) => self.scenes.push(scene),
+            SceneCommand::Pop => {
+                if index < self.scenes.len() {
+                    self.scenes.remove(index);
+                }
+            }
+            SceneCommand::Replace(scene) => match self.scenes.get_mut(index) {
+                Some(v) => *v = scene,
+                None => self.scenes.push(scene),
+            },
+            SceneCommand::None | SceneCommand::Stop => {}
+        }
+    }
+
+    fn advance_transition(&mut self, now: Instant) {
+        let progress = match self.active.as_ref() {
+            Some(active) => active.progress(now),
+            None => return,
+        };
+        if progress >= 1. {
+            self.finish_transition();
+        } else if progress >= 0.5 {
+            // fully covered by the fade
+            if let Some((index, command)) = self.active.as_mut().and_then(|a| a.pending.take()) {
+                self.apply_now(index, command);
+            }
+        }
+    }
+
+    fn finish_transition(&mut self) {
+        if let Some(mut active) = self.active.take() {
+            if let Some((index, command)) = active.pending.take() {
+                self.apply_now(index, command);
+            }
+        }
+    }
+}
+
+impl<'a, T: System> GameLoopHandler<T> for SceneStack<'a, T> {
+    fn mode(&self) -> LoopMode {
+        match self.scenes.last() {
+            Some(scene) => scene.mode(),
+            None => LoopMode::Lazy,
+        }
+    }
+
+    fn update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        dt: Duration,
+    ) -> Result<HandlerReturnValue, String> {
+        SceneStack::update(self, system, events, dt)
+    }
+
+    fn fixed_update(
+        &mut self,
+        system: &mut T,
+        events: &mut [Option<Event>],
+        step: Duration,
+    ) -> Result<(), String> {
+        SceneStack::fixed_update(self, system, events, step)
+    }
+
+    fn draw(&mut self, system: &mut T, alpha: f32) -> Result<(), String> {
+        SceneStack::draw(self, system, alpha)
+    }
+}
+
+fn window_area<T: System>(system: &mut T) -> Result<FRect, String> {
+    let (w, h) = system.size()?;
+    Ok(FRect {
+        x: 0.,
+        y: 0.,
+        w: w.get() as f32,
+        h: h.get() as f32,
+    })
+}
+
+/// same as update_gui(), but within an area
+fn update_root<T: System>(
+    root: &mut dyn Widget<T>,
+    events: &mut [Option<Event>],
+    system: &mut T,
+    dt: Duration,
+    area: FRect,
+) -> Result<FrameTransiency, String> {
+    let position = place(root, area, AspectRatioPreferredDirection::default(), system)?;
+    root.update(
+        WidgetUpdateEvent {
+            position,
+            events,
+            clipping_rect: ClippingRect::None,
+            dt,
+        },
+        system,
+    )
+}
+
+/// the color over the window, with its alpha scaled by amount
+fn draw_cover<T: System>(
+    system: &mut T,
+    color: Color,
+    amount: f32,
+    window: FRect,
+) -> Result<(), String> {
+    // discretized, since each is cached
+    const LEVELS: u32 = 32;
+    let level = (amount.clamp(0., 1.) * LEVELS as f32).round() as u32;
+    let color = Color {
+        a: (color.a as u32 * level / LEVELS) as u8,
+        ..color
+    };
+    if color.a == 0 {
+        return Ok(());
+    }
+    let dst: Option<TextureRect> = window.into();
+    let dst = match dst {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    let key = format!(
+        "scene cover {} {} {} {}",
+        color.r, color.g, color.b, color.a
+    );
+    let mut texture = system.pixels(key.into_bytes(), move |_| {
+        Ok(Surface {
+            width: 1.try_into().unwrap(),
+            data: vec![color],
+        })
+    })?;
+    texture.copy(
+        TextureRect {
+            x: 0,
+            y: 0,
+            w: 1.try_into().unwrap(),
+            h: 1.try_into().unwrap(),
+        },
+        dst,
+    )
+}
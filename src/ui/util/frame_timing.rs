@@ -0,0 +1,223 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// where the time went in one frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTiming {
+    /// handling events, updating the ui and running the simulation ticks
+    pub update: Duration,
+    pub draw: Duration,
+    /// System::present(). includes waiting for vsync
+    pub present: Duration,
+    /// from the start of this frame to the start of the next. includes waiting
+    /// for events and frame limiting
+    pub total: Duration,
+}
+
+/// rolling window of frame timings
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    /// number of frames kept
+    pub capacity: usize,
+    frames: VecDeque<FrameTiming>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+impl FrameStats {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            frames: Default::default(),
+        }
+    }
+
+    pub fn record(&mut self, timing: FrameTiming) {
+        while self.frames.len() >= self.capacity.max(1) {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(timing);
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTiming> {
+        self.frames.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// the mean of each part. zero if no frames
+    pub fn average(&self) -> FrameTiming {
+        let count = self.frames.len() as u32;
+        if count == 0 {
+            return Default::default();
+        }
+        let sum = |f: fn(&FrameTiming) -> Duration| self.frames.iter().map(f).sum::<Duration>();
+        FrameTiming {
+            update: sum(|t| t.update) / count,
+            draw: sum(|t| t.draw) / count,
+            present: sum(|t| t.present) / count,
+            total: sum(|t| t.total) / count,
+        }
+    }
+
+    /// the longest frame
+    pub fn max(&self) -> Duration {
+        self.frames
+            .iter()
+            .map(|t| t.total)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// frames per second, from the average frame time. 0 if no frames
+    pub fn fps(&self) -> f32 {
+        to_fps(self.average().total)
+    }
+
+    /// "1% low". frames per second, from the average of the slowest 1% of
+    /// frames (at least one frame)
+    pub fn low_fps(&self) -> f32 {
+        let mut totals: Vec<Duration> = self.frames.iter().map(|t| t.total).collect();
+        if totals.is_empty() {
+            return 0.;
+        }
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        let count = totals.len().div_ceil(100);
+        to_fps(totals[..count].iter().sum::<Duration>() / count as u32)
+    }
+
+    /// one line for display
+    pub fn summary(&self) -> String {
+        let average = self.average();
+        let ms = |d: Duration| d.as_secs_f32() * 1000.;
+        format!(
+            "{:.0} fps ({:.0} low) {:.1} ms max | update {:.1} draw {:.1} present {:.1}",
+            self.fps(),
+            self.low_fps(),
+            ms(self.max()),
+            ms(average.update),
+            ms(average.draw),
+            ms(average.present),
+        )
+    }
+}
+
+fn to_fps(frame_time: Duration) -> f32 {
+    if frame_time.is_zero() {
+        return 0.;
+    }
+    1. / frame_time.as_secs_f32()
+}
+
+/// caps the frame rate, independent of vsync
+#[derive(Debug, Clone)]
+pub struct FrameLimiter {
+    /// minimum duration of a frame. None is unlimited
+    pub target: Option<Duration>,
+    /// sleeping isn't precise. the end of the wait is spun instead
+    pub spin: Duration,
+    /// when the next frame should start
+    next: Option<Instant>,
+}
+
+impl Default for FrameLimiter {
+    fn default() -> Self {
+        Self {
+            target: None,
+            spin: Duration::from_millis(2),
+            next: None,
+        }
+    }
+}
+
+impl FrameLimiter {
+    pub fn from_fps(fps: u32) -> Self {
+        Self {
+            target: Some(Duration::from_secs(1) / fps.max(1)),
+            ..Default::default()
+        }
+    }
+
+    /// time left until the next frame should start
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.next {
+            Some(next) => next.saturating_duration_since(now),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// block until the next frame should start. call once per frame
+    pub fn wait(&mut self) {
+        let target = match self.target {
+            Some(v) => v,
+            None => {
+                self.next = None;
+                return;
+            }
+        };
+
+        let remaining = self.remaining(Instant::now());
+        if remaining > self.spin {
+            std::thread::sleep(remaining - self.spin);
+        }
+        while !self.remaining(Instant::now()).is_zero() {
+            std::hint::spin_loop();
+        }
+
+        let now = Instant::now();
+        self.next = Some(match self.next {
+            // keep the cadence, unless too far behind
+            Some(next) if now - next < target => next + target,
+            _ => now + target,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(ms: u64) -> FrameTiming {
+        FrameTiming {
+            update: Duration::from_millis(ms / 2),
+            draw: Duration::from_millis(ms / 4),
+            present: Duration::from_millis(ms / 4),
+            total: Duration::from_millis(ms),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = FrameStats::new(200);
+        assert_eq!(stats.fps(), 0.);
+        assert_eq!(stats.low_fps(), 0.);
+        for _ in 0..198 {
+            stats.record(frame(10));
+        }
+        stats.record(frame(40));
+        stats.record(frame(20));
+        assert_eq!(stats.max(), Duration::from_millis(40));
+        // 2 slowest frames
+        assert!((stats.low_fps() - 1000. / 30.).abs() < 0.01);
+        assert_eq!(stats.average().update, Duration::from_micros(5_100));
+    }
+
+    #[test]
+    fn test_rolling() {
+        let mut stats = FrameStats::new(2);
+        stats.record(frame(40));
+        stats.record(frame(10));
+        stats.record(frame(10));
+        assert_eq!(stats.frames().count(), 2);
+        assert_eq!(stats.max(), Duration::from_millis(10));
+        assert!((stats.fps() - 100.).abs() < 0.01);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod aspect_ratio;
+pub mod frame_timing;
 pub mod length;
 pub mod rect;
 pub mod rust;
-pub(crate) mod shuffle;
+pub(crate) mod text_draw;
+pub mod text_edit;
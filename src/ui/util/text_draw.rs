@@ -0,0 +1,148 @@
+use std::{
+    num::{NonZeroU16, NonZeroU32},
+    ops::Range,
+    time::Duration,
+};
+
+use crate::{
+    core::{
+        color::{Color, Surface},
+        texture_rect::{TextureRect, TextureRectF, TextureSourceF},
+        NonEmptyStr, System, TextureHandle,
+    },
+    ui::{
+        util::{rect::FRect, text_edit::wrap},
+        widget::FrameTransiency,
+    },
+};
+
+/// the caret is shown then hidden for this long each
+const BLINK: Duration = Duration::from_millis(500);
+
+pub(crate) fn point_size(h: f32) -> NonZeroU16 {
+    NonZeroU16::new(h.round().clamp(1., u16::MAX as f32) as u16).unwrap_or(NonZeroU16::MIN)
+}
+
+/// width of text rendered to height h
+pub(crate) fn text_width<T: System>(
+    sys: &mut T,
+    text: &str,
+    color: Color,
+    h: f32,
+) -> Result<f32, String> {
+    let text: NonEmptyStr = match text.try_into() {
+        Ok(v) => v,
+        Err(()) => return Ok(0.),
+    };
+    let size = sys.text(text, color, point_size(h), None)?.size()?;
+    Ok(size.0.get() as f32 * h / size.1.get() as f32)
+}
+
+/// width of a char rendered at a point size
+pub(crate) fn char_width<T: System>(
+    sys: &mut T,
+    c: char,
+    color: Color,
+    point_size: NonZeroU16,
+) -> Result<f32, String> {
+    let mut buf = [0u8; 4];
+    let s: NonEmptyStr = match (&*c.encode_utf8(&mut buf)).try_into() {
+        Ok(v) => v,
+        Err(()) => return Ok(0.),
+    };
+    let size = sys.text(s, color, point_size, None)?.size()?;
+    Ok(size.0.get() as f32)
+}
+
+/// height of a line of text rendered at a point size
+pub(crate) fn line_height<T: System>(
+    sys: &mut T,
+    color: Color,
+    point_size: NonZeroU16,
+) -> Result<f32, String> {
+    let size = sys
+        .text("A".try_into().unwrap(), color, point_size, None)?
+        .size()?;
+    Ok(size.1.get() as f32)
+}
+
+/// wrap() with chars measured at a point size. MultiLineLabel and TextArea
+/// both wrap with this, so the same text breaks the same way in each
+pub(crate) fn wrap_text<T: System>(
+    sys: &mut T,
+    text: &str,
+    width: f32,
+    color: Color,
+    point_size: NonZeroU16,
+) -> Result<Vec<Range<usize>>, String> {
+    wrap(text, width, |c| char_width(sys, c, color, point_size))
+}
+
+/// draw text rendered to height h, with its top left at x, y
+pub(crate) fn draw_text<T: System>(
+    sys: &mut T,
+    text: &str,
+    color: Color,
+    x: f32,
+    y: f32,
+    h: f32,
+) -> Result<(), String> {
+    let text: NonEmptyStr = match text.try_into() {
+        Ok(v) => v,
+        Err(()) => return Ok(()),
+    };
+    let mut texture = sys.text(text, color, point_size(h), None)?;
+    let size = texture.size()?;
+    let w = size.0.get() as f32 * h / size.1.get() as f32;
+    let dst = match TextureRectF::new(x, y, w, h) {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let src: TextureSourceF = Default::default();
+    texture.copy_f(src, dst)
+}
+
+/// fill an area with a color
+pub(crate) fn fill_rect<T: System>(sys: &mut T, color: Color, area: FRect) -> Result<(), String> {
+    let dst: TextureRect = match area.into() {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let mut texture = sys.pixels(b"ui white pixel".as_slice(), |_| {
+        Ok(Surface {
+            width: NonZeroU32::MIN,
+            data: vec![Color {
+                r: 0xFF,
+                g: 0xFF,
+                b: 0xFF,
+                a: 0xFF,
+            }],
+        })
+    })?;
+    texture.tint(color)?;
+    texture.copy(
+        TextureRect {
+            x: 0,
+            y: 0,
+            w: NonZeroU32::MIN,
+            h: NonZeroU32::MIN,
+        },
+        dst,
+    )
+}
+
+/// width of the caret for text of height h
+pub(crate) fn caret_width(h: f32) -> f32 {
+    (h / 16.).round().max(1.)
+}
+
+/// when the caret next shows or hides
+pub(crate) fn blink_wake(blink: Duration) -> FrameTransiency {
+    let into = blink.as_nanos() % BLINK.as_nanos();
+    FrameTransiency::wake_in(BLINK - Duration::from_nanos(into as u64))
+}
+
+/// if the caret is shown, given the time since it last moved
+pub(crate) fn blink_on(blink: Duration) -> bool {
+    blink.as_nanos() % (BLINK.as_nanos() * 2) < BLINK.as_nanos()
+}
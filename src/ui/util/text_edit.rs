@@ -0,0 +1,667 @@
+use std::{cell::Cell, ops::Range};
+
+use crate::core::{
+    event::{Event, NavKey, NavKeyEvent},
+    System,
+};
+
+/// which characters can be typed or pasted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextFilter {
+    #[default]
+    Any,
+    /// digits, a leading minus and one decimal point
+    Numeric,
+    /// ascii letters, digits and underscore, not starting with a digit
+    Identifier,
+}
+
+impl TextFilter {
+    /// if c can go between before and after
+    pub fn accepts(&self, c: char, before: &str, after: &str) -> bool {
+        match self {
+            TextFilter::Any => true,
+            TextFilter::Numeric => {
+                c.is_ascii_digit()
+                    || (c == '-' && before.is_empty() && !after.starts_with('-'))
+                    || (c == '.' && !before.contains('.') && !after.contains('.'))
+            }
+            TextFilter::Identifier => {
+                c == '_' || c.is_ascii_alphabetic() || (c.is_ascii_digit() && !before.is_empty())
+            }
+        }
+    }
+}
+
+/// limits on what an edit can do to the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EditRules {
+    pub filter: TextFilter,
+    /// in chars
+    pub max_len: Option<usize>,
+    /// newlines are allowed. otherwise they are dropped from pasted text
+    pub multi_line: bool,
+    /// most lines, counting newlines rather than wrapping
+    pub max_lines: Option<usize>,
+    /// copy and cut do nothing and word jumps go to the ends, so that the
+    /// text isn't revealed (e.g. passwords)
+    pub conceal: bool,
+}
+
+/// what an event did to a TextEdit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditAction {
+    /// the event isn't for text editing
+    Ignored,
+    /// the event was used but the text didn't change (e.g. moving the caret)
+    Handled,
+    Changed,
+    /// enter was pressed on single line text
+    Submit,
+}
+
+/// a caret and selection within some text. positions are byte indices, always
+/// on char boundaries after clamp()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextEdit {
+    pub caret: usize,
+    /// the other end of the selection. equal to caret if nothing is selected
+    pub anchor: usize,
+}
+
+#[derive(PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    Other,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Other
+    }
+}
+
+fn floor_boundary(text: &str, mut i: usize) -> usize {
+    i = i.min(text.len());
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn prev_char(text: &str, i: usize) -> usize {
+    text[..i].char_indices().next_back().map_or(0, |(i, _)| i)
+}
+
+fn next_char(text: &str, i: usize) -> usize {
+    text[i..].chars().next().map_or(i, |c| i + c.len_utf8())
+}
+
+/// start of the word before i, skipping whitespace
+pub fn prev_word(text: &str, i: usize) -> usize {
+    let mut chars = text[..i].char_indices().rev().peekable();
+    while chars
+        .next_if(|(_, c)| class(*c) == CharClass::Space)
+        .is_some()
+    {}
+    let start_class = match chars.peek() {
+        Some((_, c)) => class(*c),
+        None => return 0,
+    };
+    let mut out = i;
+    while let Some((j, _)) = chars.next_if(|(_, c)| class(*c) == start_class) {
+        out = j;
+    }
+    out
+}
+
+/// end of the word after i, skipping whitespace
+pub fn next_word(text: &str, i: usize) -> usize {
+    let mut chars = text[i..].char_indices().peekable();
+    while chars
+        .next_if(|(_, c)| class(*c) == CharClass::Space)
+        .is_some()
+    {}
+    let start_class = match chars.peek() {
+        Some((_, c)) => class(*c),
+        None => return text.len(),
+    };
+    while chars.next_if(|(_, c)| class(*c) == start_class).is_some() {}
+    chars.peek().map_or(text.len(), |(j, _)| i + j)
+}
+
+/// start of the line containing i
+pub fn line_start(text: &str, i: usize) -> usize {
+    text[..i].rfind('\n').map_or(0, |j| j + 1)
+}
+
+/// end of the line containing i, before the newline
+pub fn line_end(text: &str, i: usize) -> usize {
+    text[i..].find('\n').map_or(text.len(), |j| i + j)
+}
+
+/// breaks text into lines no wider than width, at whitespace where possible.
+/// measure gives the width of a char. each line is a byte range, without its
+/// newline. whitespace at the end of a line is kept on it, even past width
+///
+/// MultiLineLabel and TextArea both wrap with this. summing chars ignores
+/// kerning, so a line may be a little narrower than width allows
+pub fn wrap<F>(text: &str, width: f32, mut measure: F) -> Result<Vec<Range<usize>>, String>
+where
+    F: FnMut(char) -> Result<f32, String>,
+{
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for paragraph in text.split('\n') {
+        let chars: Vec<(usize, char)> = paragraph.char_indices().collect();
+        let mut start = 0; // index into chars
+        let mut x = 0.;
+        // index into chars after the last whitespace on this line
+        let mut break_at = None;
+        let mut k = 0;
+        while k < chars.len() {
+            let c = chars[k].1;
+            let w = measure(c)?;
+            if x + w > width && k > start && !c.is_whitespace() {
+                let end = match break_at {
+                    Some(b) if b > start => b,
+                    _ => k,
+                };
+                lines.push(offset + chars[start].0..offset + chars[end].0);
+                start = end;
+                k = end;
+                x = 0.;
+                break_at = None;
+                continue;
+            }
+            x += w;
+            if c.is_whitespace() {
+                break_at = Some(k + 1);
+            }
+            k += 1;
+        }
+        let start = chars.get(start).map_or(paragraph.len(), |(i, _)| *i);
+        lines.push(offset + start..offset + paragraph.len());
+        offset += paragraph.len() + 1;
+    }
+    Ok(lines)
+}
+
+/// which of the lines from wrap() holds the caret at i. at a wrap, the caret is
+/// at the start of the next line
+pub fn line_of(lines: &[Range<usize>], i: usize) -> usize {
+    lines.iter().rposition(|l| l.start <= i).unwrap_or_default()
+}
+
+impl TextEdit {
+    /// caret at the end, nothing selected
+    pub fn at_end(text: &str) -> Self {
+        Self {
+            caret: text.len(),
+            anchor: text.len(),
+        }
+    }
+
+    /// keep positions within the text, e.g. after it was changed elsewhere
+    pub fn clamp(&mut self, text: &str) {
+        self.caret = floor_boundary(text, self.caret);
+        self.anchor = floor_boundary(text, self.anchor);
+    }
+
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    pub fn selected_text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.selection()]
+    }
+
+    pub fn select_all(&mut self, text: &str) {
+        self.anchor = 0;
+        self.caret = text.len();
+    }
+
+    /// move the caret. if select, the selection extends to it
+    pub fn move_to(&mut self, i: usize, select: bool) {
+        self.caret = i;
+        if !select {
+            self.anchor = i;
+        }
+    }
+
+    /// removes the selection. returns true if anything was removed
+    pub fn delete_selection(&mut self, text: &mut String) -> bool {
+        let range = self.selection();
+        if range.is_empty() {
+            return false;
+        }
+        text.replace_range(range.clone(), "");
+        self.move_to(range.start, false);
+        true
+    }
+
+    /// replaces the selection with s, keeping what the rules allow. returns
+    /// true if the text changed
+    pub fn insert(&mut self, text: &mut String, s: &str, rules: &EditRules) -> bool {
+        let mut changed = self.delete_selection(text);
+        let mut len = text.chars().count();
+        let mut lines = text.matches('\n').count() + 1;
+        for c in s.chars() {
+            if rules.max_len.is_some_and(|max| len >= max) {
+                break;
+            }
+            let allowed = match c {
+                '\n' => {
+                    rules.multi_line
+                        && rules.filter == TextFilter::Any
+                        && rules.max_lines.is_none_or(|max| lines < max)
+                }
+                c if c.is_control() => false,
+                c => rules
+                    .filter
+                    .accepts(c, &text[..self.caret], &text[self.caret..]),
+            };
+            if !allowed {
+                continue;
+            }
+            text.insert(self.caret, c);
+            self.move_to(self.caret + c.len_utf8(), false);
+            len += 1;
+            if c == '\n' {
+                lines += 1;
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// removes the selection, or the char or word before the caret
+    pub fn backspace(&mut self, text: &mut String, word: bool) -> bool {
+        if !self.has_selection() {
+            self.anchor = if word {
+                prev_word(text, self.caret)
+            } else {
+                prev_char(text, self.caret)
+            };
+        }
+        self.delete_selection(text)
+    }
+
+    /// removes the selection, or the char or word after the caret
+    pub fn delete(&mut self, text: &mut String, word: bool) -> bool {
+        if !self.has_selection() {
+            self.anchor = if word {
+                next_word(text, self.caret)
+            } else {
+                next_char(text, self.caret)
+            };
+        }
+        self.delete_selection(text)
+    }
+
+    pub fn left(&mut self, text: &str, word: bool, select: bool) {
+        let i = if self.has_selection() && !select {
+            self.selection().start
+        } else if word {
+            prev_word(text, self.caret)
+        } else {
+            prev_char(text, self.caret)
+        };
+        self.move_to(i, select);
+    }
+
+    pub fn right(&mut self, text: &str, word: bool, select: bool) {
+        let i = if self.has_selection() && !select {
+            self.selection().end
+        } else if word {
+            next_word(text, self.caret)
+        } else {
+            next_char(text, self.caret)
+        };
+        self.move_to(i, select);
+    }
+
+    pub fn home(&mut self, text: &str, select: bool) {
+        self.move_to(line_start(text, self.caret), select);
+    }
+
+    pub fn end(&mut self, text: &str, select: bool) {
+        self.move_to(line_end(text, self.caret), select);
+    }
+
+    /// caret movement. concealed words aren't revealed, so word jumps go to
+    /// the ends
+    fn nav_key(&mut self, text: &str, e: &NavKeyEvent, rules: &EditRules) -> EditAction {
+        if !e.down {
+            return EditAction::Handled;
+        }
+        let word = if cfg!(target_os = "macos") {
+            e.mods.alt
+        } else {
+            e.mods.ctrl
+        };
+        let select = e.mods.shift;
+        let to_end = word && rules.conceal;
+        match e.key {
+            NavKey::Left if !to_end => self.left(text, word, select),
+            NavKey::Right if !to_end => self.right(text, word, select),
+            NavKey::Left | NavKey::Home => self.home(text, select),
+            NavKey::Right | NavKey::End => self.end(text, select),
+            _ => return EditAction::Ignored,
+        }
+        EditAction::Handled
+    }
+
+    /// applies typing, caret movement and clipboard shortcuts. up, down and
+    /// page keys are ignored since they depend on layout
+    pub fn apply_event<T: System>(
+        &mut self,
+        text: &mut String,
+        event: &Event,
+        rules: &EditRules,
+        system: &mut T,
+    ) -> Result<EditAction, String> {
+        self.clamp(text);
+        let changed = |c: bool| {
+            if c {
+                EditAction::Changed
+            } else {
+                EditAction::Handled
+            }
+        };
+        match event {
+            Event::Text(s) => Ok(changed(self.insert(text, s, rules))),
+            Event::NavKey(e) => Ok(self.nav_key(text, e, rules)),
+            Event::Key(e) => {
+                // printable keys are also sent as Text, which does the typing
+                if !e.down {
+                    return Ok(if e.key.is_ascii_graphic() || e.key == b' ' {
+                        EditAction::Handled
+                    } else {
+                        EditAction::Ignored
+                    });
+                }
+                let word = if cfg!(target_os = "macos") {
+                    e.mods.alt
+                } else {
+                    e.mods.ctrl
+                } && !rules.conceal;
+                if e.mods.shortcut() {
+                    return match e.key.to_ascii_lowercase() {
+                        b'a' => {
+                            self.select_all(text);
+                            Ok(EditAction::Handled)
+                        }
+                        b'c' => {
+                            if !rules.conceal && self.has_selection() {
+                                system.set_clipboard(self.selected_text(text))?;
+                            }
+                            Ok(EditAction::Handled)
+                        }
+                        b'x' => {
+                            if rules.conceal || !self.has_selection() {
+                                return Ok(EditAction::Handled);
+                            }
+                            system.set_clipboard(self.selected_text(text))?;
+                            Ok(changed(self.delete_selection(text)))
+                        }
+                        b'v' => {
+                            let pasted = system.clipboard()?;
+                            Ok(changed(self.insert(text, &pasted, rules)))
+                        }
+                        8 => Ok(changed(self.backspace(text, true))),
+                        127 => Ok(changed(self.delete(text, true))),
+                        _ => Ok(EditAction::Ignored),
+                    };
+                }
+                match e.key {
+                    8 => Ok(changed(self.backspace(text, word))),
+                    127 => Ok(changed(self.delete(text, word))),
+                    b'\r' | b'\n' => {
+                        if rules.multi_line {
+                            Ok(changed(self.insert(text, "\n", rules)))
+                        } else {
+                            Ok(EditAction::Submit)
+                        }
+                    }
+                    k if k.is_ascii_graphic() || k == b' ' => Ok(EditAction::Handled),
+                    _ => Ok(EditAction::Ignored),
+                }
+            }
+            _ => Ok(EditAction::Ignored),
+        }
+    }
+}
+
+/// undo and redo for edits to some text
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<(String, TextEdit)>,
+    redo: Vec<(String, TextEdit)>,
+    /// the last edit was typing, and more typing joins it
+    typing: bool,
+}
+
+impl History {
+    /// most undo steps kept
+    pub const LIMIT: usize = 100;
+
+    /// call when an edit changed the text, with the text and edit from before
+    /// it. consecutive typing is undone together, until a space is typed
+    pub fn record(&mut self, before: String, edit: TextEdit, typing: bool) {
+        self.redo.clear();
+        if !(typing && self.typing) {
+            if self.undo.len() >= Self::LIMIT {
+                self.undo.remove(0);
+            }
+            self.undo.push((before, edit));
+        }
+        self.typing = typing;
+    }
+
+    /// stop the next typing from joining the last, e.g. after the caret moves
+    pub fn end_typing(&mut self) {
+        self.typing = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// returns true if there was something to undo
+    pub fn undo(&mut self, text: &mut String, edit: &mut TextEdit) -> bool {
+        match self.undo.pop() {
+            Some((t, e)) => {
+                self.redo.push((std::mem::replace(text, t), *edit));
+                *edit = e;
+                self.typing = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// returns true if there was something to redo
+    pub fn redo(&mut self, text: &mut String, edit: &mut TextEdit) -> bool {
+        match self.redo.pop() {
+            Some((t, e)) => {
+                self.undo.push((std::mem::replace(text, t), *edit));
+                *edit = e;
+                self.typing = false;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// shared by text inputs and text areas so that at most one is focused, even
+/// if the click which focused it was consumed before reaching the others
+#[derive(Debug, Default)]
+pub struct FocusGroup {
+    /// incremented each time a widget takes focus
+    latest: Cell<u64>,
+}
+
+impl FocusGroup {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// call from a widget's update with its focus state. token identifies
+    /// when the widget took focus. it's unfocused if another took it since
+    pub fn update(&self, focused: &mut bool, token: &mut Option<u64>) {
+        match *token {
+            _ if !*focused => *token = None,
+            None => {
+                let latest = self.latest.get().wrapping_add(1);
+                self.latest.set(latest);
+                *token = Some(latest);
+            }
+            Some(v) if v != self.latest.get() => {
+                *focused = false;
+                *token = None;
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::KeyMods;
+
+    #[test]
+    fn test_insert_rules() {
+        let rules = EditRules {
+            filter: TextFilter::Numeric,
+            max_len: Some(5),
+            ..Default::default()
+        };
+        let mut text = String::new();
+        let mut edit = TextEdit::default();
+        assert!(edit.insert(&mut text, "-1a.2.-34", &rules));
+        assert_eq!(text, "-1.23");
+        assert_eq!(edit.caret, 5);
+
+        let rules = EditRules {
+            filter: TextFilter::Identifier,
+            ..Default::default()
+        };
+        let mut text = String::new();
+        let mut edit = TextEdit::default();
+        edit.insert(&mut text, "9 my_var\n2", &rules);
+        assert_eq!(text, "my_var2");
+    }
+
+    #[test]
+    fn test_words_and_selection() {
+        let mut text = "héllo, wörld  foo".to_owned();
+        assert_eq!(next_word(&text, 0), 6);
+        assert_eq!(next_word(&text, 6), 7);
+        assert_eq!(prev_word(&text, text.len()), 16);
+        assert_eq!(prev_word(&text, 16), 8);
+
+        let mut edit = TextEdit::at_end(&text);
+        edit.left(&text, true, true);
+        assert_eq!(edit.selected_text(&text), "foo");
+        edit.backspace(&mut text, false);
+        assert_eq!(text, "héllo, wörld  ");
+        edit.backspace(&mut text, true);
+        assert_eq!(text, "héllo, ");
+        edit.home(&text, true);
+        edit.insert(&mut text, "a\nb", &Default::default());
+        assert_eq!(text, "ab");
+    }
+
+    #[test]
+    fn test_nav_key_conceal() {
+        let text = "ab cd";
+        let rules = EditRules {
+            conceal: true,
+            ..Default::default()
+        };
+        let key = |key: NavKey, word: bool| NavKeyEvent {
+            key,
+            down: true,
+            mods: KeyMods {
+                ctrl: word,
+                alt: word,
+                ..Default::default()
+            },
+        };
+        let mut edit = TextEdit::at_end(text);
+        edit.nav_key(text, &key(NavKey::Left, false), &rules);
+        assert_eq!(edit.caret, 4);
+        edit.nav_key(text, &key(NavKey::Left, true), &rules);
+        assert_eq!(edit.caret, 0);
+        edit.nav_key(text, &key(NavKey::Right, false), &rules);
+        assert_eq!(edit.caret, 1);
+        edit.nav_key(text, &key(NavKey::Right, true), &rules);
+        assert_eq!(edit.caret, text.len());
+    }
+
+    #[test]
+    fn test_wrap() {
+        // every char is 1 wide
+        let lines = wrap("ab cd efghij\n\nx", 4., |_| Ok(1.)).unwrap();
+        let text = "ab cd efghij\n\nx";
+        let lines: Vec<&str> = lines.into_iter().map(|l| &text[l]).collect();
+        assert_eq!(lines, ["ab ", "cd ", "efgh", "ij", "", "x"]);
+        let lines = wrap("ab cd", 4., |_| Ok(1.)).unwrap();
+        assert_eq!(line_of(&lines, 2), 0);
+        assert_eq!(line_of(&lines, 3), 1);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::default();
+        let mut text = String::new();
+        let mut edit = TextEdit::default();
+        for s in ["a", "b", " ", "c"] {
+            let before = (text.clone(), edit);
+            edit.insert(&mut text, s, &Default::default());
+            history.record(before.0, before.1, s != " ");
+        }
+        assert!(history.undo(&mut text, &mut edit));
+        assert_eq!(text, "ab ");
+        assert!(history.undo(&mut text, &mut edit));
+        assert_eq!(text, "ab");
+        assert!(history.undo(&mut text, &mut edit));
+        assert_eq!(text, "");
+        assert!(!history.undo(&mut text, &mut edit));
+        assert!(history.redo(&mut text, &mut edit));
+        assert_eq!((text.as_str(), edit.caret), ("ab", 2));
+    }
+
+    #[test]
+    fn test_focus_group() {
+        let group = FocusGroup::new();
+        let (mut a, mut a_token) = (true, None);
+        let (mut b, mut b_token) = (false, None);
+        group.update(&mut a, &mut a_token);
+        group.update(&mut b, &mut b_token);
+        assert!(a && !b);
+
+        b = true; // clicked
+        group.update(&mut b, &mut b_token);
+        group.update(&mut a, &mut a_token);
+        assert!(!a && b);
+        group.update(&mut b, &mut b_token);
+        assert!(b);
+    }
+}
@@ -1,7 +1,7 @@
 use std::cell::Cell;
 
 use crate::{
-    core::texture_rect::TextureRect,
+    core::{input::Hotkey, texture_rect::TextureRect},
     ui::{
         util::{
             length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
@@ -50,8 +50,8 @@ pub struct Button<'b, 'state, T: crate::core::System + 'b> {
     pub sizing: NestedContentSizing,
     pub sizing_inherit_choice: ButtonInheritSizing,
 
-    /// a button which can be used to press the button
-    pub hotkey: Option<u8>,
+    /// an action which can be used to press the button
+    pub hotkey: Option<Hotkey<'state>>,
 
     /// state stored for draw from update. under some circumstances this needs
     /// to persist between frames. for example, if the contained button content
@@ -167,27 +167,28 @@ impl<'b, 'state, T: crate::core::System + 'b> Widget<T> for Button<'b, 'state, T
     ) -> Result<FrameTransiency, String> {
         let mut ret = Default::default();
         for e in event.events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                crate::core::event::Event::Key(key_event) => {
-                    // can still occur even if zero area
-                    if let Some(hotkey) = self.hotkey {
-                        if key_event.key == hotkey {
-                            *e = None;
-                            if key_event.down {
-                                self.state.set(ButtonPrivateState {
-                                    s: ButtonState::Pressed,
-                                });
-                            } else {
-                                // rising edge
-                                ret |= (self.functionality)(sys_interface)?;
-                                self.state.set(ButtonPrivateState {
-                                    s: ButtonState::Idle,
-                                });
-                            }
-                        }
-                    }
+            // can still occur even if zero area
+            let hotkey_down = match (self.hotkey.as_ref(), e.as_ref()) {
+                (Some(hotkey), Some(event)) => hotkey.matches(event),
+                _ => None,
+            };
+            if let Some(down) = hotkey_down {
+                *e = None;
+                if down {
+                    self.state.set(ButtonPrivateState {
+                        s: ButtonState::Pressed,
+                    });
+                } else {
+                    // rising edge
+                    ret |= (self.functionality)(sys_interface)?;
+                    self.state.set(ButtonPrivateState {
+                        s: ButtonState::Idle,
+                    });
                 }
-                crate::core::event::Event::Mouse(mouse) => {
+                continue;
+            }
+            match e.as_ref().unwrap() {
+                &crate::core::event::Event::Mouse(mouse) => {
                     let maybe_non_zero_texture_area: Option<TextureRect> = event.position.into();
 
                     if let Some(non_zero_area) = maybe_non_zero_texture_area {
@@ -1,7 +1,7 @@
 use std::{cell::Cell, path::PathBuf};
 
 use crate::{
-    core::{texture_rect::TextureRect, PathLike, TextureHandle},
+    core::{input::Hotkey, texture_rect::TextureRect, PathLike, TextureHandle},
     ui::{
         util::{
             length::{MaxLen, MinLen},
@@ -29,8 +29,8 @@ pub struct CheckBox<'state> {
     pub checked: &'state Cell<bool>,
     pub changed: &'state Cell<bool>,
 
-    /// a button which can be used to toggle this checkbox
-    pub hotkey: Option<u8>,
+    /// an action which can be used to toggle this checkbox
+    pub hotkey: Option<Hotkey<'state>>,
 
     /// state stored for draw from update
     draw_pos: FRect,
@@ -102,20 +102,21 @@ impl<'state, T: crate::core::System> Widget<T> for CheckBox<'state> {
             None => return Ok(Default::default()), // can't click or hover with zero area
         };
         for e in event.events.iter_mut().filter(|e| e.is_some()) {
-            match e.unwrap() {
-                crate::core::event::Event::Key(key_event) => {
-                    if let Some(hotkey) = self.hotkey {
-                        if key_event.key == hotkey {
-                            *e = None;
-                            if !key_event.down {
-                                // rising edge
-                                self.checked.set(!self.checked.get());
-                                self.changed.set(true);
-                            }
-                        }
-                    }
+            let hotkey_down = match (self.hotkey.as_ref(), e.as_ref()) {
+                (Some(hotkey), Some(event)) => hotkey.matches(event),
+                _ => None,
+            };
+            if let Some(down) = hotkey_down {
+                *e = None;
+                if !down {
+                    // rising edge
+                    self.checked.set(!self.checked.get());
+                    self.changed.set(true);
                 }
-                crate::core::event::Event::Mouse(mouse) => {
+                continue;
+            }
+            match e.as_ref().unwrap() {
+                &crate::core::event::Event::Mouse(mouse) => {
                     if non_zero_area.contains_point((mouse.x, mouse.y))
                         && event.clipping_rect.contains_point((mouse.x, mouse.y))
                     {
@@ -0,0 +1,110 @@
+use std::{cell::Cell, time::Duration};
+
+use crate::ui::util::{
+    frame_timing::FrameStats,
+    length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+};
+
+use super::{single_line_label::SingleLineLabel, FrameTransiency, Widget, WidgetUpdateEvent};
+
+/// shows FrameStats::summary() as a line of text. by default in the top left
+/// corner of its parent. see GameLoop::stats and gui_loop_limited()
+pub struct FpsOverlay<'state> {
+    pub stats: &'state Cell<FrameStats>,
+    /// sizing and style. the text is replaced
+    pub label: SingleLineLabel<'static>,
+    /// how often the text changes, so it's readable
+    pub interval: Duration,
+    since_refresh: Option<Duration>,
+}
+
+impl<'state> FpsOverlay<'state> {
+    pub fn new(stats: &'state Cell<FrameStats>) -> Self {
+        let mut label = SingleLineLabel::new(String::new().into());
+        label.min_h = MinLen(16.);
+        label.max_h = MaxLen(16.);
+        label.max_w_fail_policy = MaxLenFailPolicy::NEGATIVE;
+        label.max_h_fail_policy = MaxLenFailPolicy::NEGATIVE;
+        Self {
+            stats,
+            label,
+            interval: Duration::from_millis(500),
+            since_refresh: None,
+        }
+    }
+}
+
+impl<'state, T: crate::core::System> Widget<T> for FpsOverlay<'state> {
+    fn min(&self, sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        self.label.min(sys_interface)
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        Widget::<T>::min_w_fail_policy(&self.label)
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        Widget::<T>::min_h_fail_policy(&self.label)
+    }
+
+    fn max(&self, sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        self.label.max(sys_interface)
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        Widget::<T>::max_w_fail_policy(&self.label)
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        Widget::<T>::max_h_fail_policy(&self.label)
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        Widget::<T>::preferred_portion(&self.label)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.label
+            .preferred_width_from_height(pref_h, sys_interface)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.label
+            .preferred_height_from_width(pref_w, sys_interface)
+    }
+
+    fn update(
+        &mut self,
+        event: WidgetUpdateEvent,
+        sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        let since_refresh = match self.since_refresh {
+            Some(v) => v + event.dt,
+            None => self.interval, // first frame
+        };
+        let since_refresh = if since_refresh >= self.interval {
+            let stats = self.stats.take();
+            self.label.text.set(stats.summary());
+            self.stats.set(stats);
+            Duration::ZERO
+        } else {
+            since_refresh
+        };
+        self.since_refresh = Some(since_refresh);
+        // the numbers change without any events
+        let next_refresh = FrameTransiency::wake_in(self.interval - since_refresh);
+        Ok(self.label.update(event, sys_interface)? | next_refresh)
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        self.label.draw(sys_interface)
+    }
+}
@@ -1,9 +1,9 @@
-use std::{ops::Not, path::PathBuf};
+use std::{num::NonZeroU32, ops::Not, path::PathBuf};
 
 use crate::{
     core::{
         texture_rect::{TextureDestinationF, TextureRect, TextureRectF, TextureSource},
-        PathLike, TextureHandle,
+        PathLike, System, TextureHandle,
     },
     ui::{
         util::{
@@ -25,6 +25,11 @@ pub struct ImageDisplay {
     pub texture_path: PathBuf,
     pub texture_src: TextureSource,
 
+    /// if set, the texture is loaded in the background (see
+    /// System::try_image) and this image is drawn in its place until it's
+    /// ready. it should be small or already loaded
+    pub placeholder: Option<PathBuf>,
+
     /// applicable when request_aspect_ratio is false
     pub aspect_ratio_fail_policy: AspectRatioFailPolicy,
 
@@ -45,6 +50,7 @@ pub struct ImageDisplay {
 
     /// state stored for draw from update
     draw_pos: crate::ui::util::rect::FRect,
+    texture_loaded: bool,
 }
 
 impl ImageDisplay {
@@ -54,6 +60,7 @@ impl ImageDisplay {
         ImageDisplay {
             texture_path: texture_path.to_path_buf(),
             texture_src: Default::default(),
+            placeholder: Default::default(),
             aspect_ratio_fail_policy: Default::default(),
             request_aspect_ratio: true,
             min_w_fail_policy: Default::default(),
@@ -68,7 +75,32 @@ impl ImageDisplay {
             pref_h: Default::default(),
             preferred_ratio_exceed_parent: Default::default(),
             draw_pos: Default::default(),
+            texture_loaded: Default::default(),
+        }
+    }
+
+    /// size of the texture that would be drawn right now
+    fn texture_size<T: System>(
+        &self,
+        sys_interface: &mut T,
+    ) -> Result<(NonZeroU32, NonZeroU32), String> {
+        if let TextureSource::Area(texture_area) = self.texture_src {
+            return Ok(texture_area.size());
+        }
+
+        let placeholder = match &self.placeholder {
+            Some(v) => v,
+            None => {
+                let texture = sys_interface.image(&self.texture_path)?;
+                return texture.size();
+            }
+        };
+
+        if let Some(texture) = sys_interface.try_image(&self.texture_path)? {
+            return texture.size();
         }
+        let texture = sys_interface.image(placeholder)?;
+        texture.size()
     }
 }
 
@@ -84,13 +116,7 @@ impl<T: crate::core::System> Widget<T> for ImageDisplay {
             }
         }
 
-        let size = match self.texture_src {
-            TextureSource::WholeTexture => {
-                let texture = sys_interface.image(&self.texture_path)?;
-                crate::core::TextureHandle::size(&texture)?
-            }
-            TextureSource::Area(texture_area) => texture_area.size(),
-        };
+        let size = self.texture_size(sys_interface)?;
         Ok((
             match self.min_w_policy {
                 MinLenPolicy::Children => MinLen(size.0.get() as f32),
@@ -117,13 +143,7 @@ impl<T: crate::core::System> Widget<T> for ImageDisplay {
                 return Ok((w, h)); // no need to query texture
             }
         }
-        let size = match self.texture_src {
-            TextureSource::WholeTexture => {
-                let texture = sys_interface.image(&self.texture_path)?;
-                crate::core::TextureHandle::size(&texture)?
-            }
-            TextureSource::Area(texture_area) => texture_area.size(),
-        };
+        let size = self.texture_size(sys_interface)?;
         Ok((
             match self.max_w_policy {
                 MaxLenPolicy::Children => MaxLen(size.0.get() as f32),
@@ -158,13 +178,7 @@ impl<T: crate::core::System> Widget<T> for ImageDisplay {
         }
 
         Some(|| -> Result<f32, String> {
-            let size = match self.texture_src {
-                TextureSource::WholeTexture => {
-                    let texture = sys_interface.image(&self.texture_path)?;
-                    crate::core::TextureHandle::size(&texture)?
-                }
-                TextureSource::Area(texture_area) => texture_area.size(),
-            };
+            let size = self.texture_size(sys_interface)?;
 
             let ratio = size.0.get() as f32 / size.1.get() as f32;
             Ok(AspectRatioPreferredDirection::width_from_height(
@@ -183,13 +197,7 @@ impl<T: crate::core::System> Widget<T> for ImageDisplay {
         }
 
         Some(|| -> Result<f32, String> {
-            let size = match self.texture_src {
-                TextureSource::WholeTexture => {
-                    let texture = sys_interface.image(&self.texture_path)?;
-                    crate::core::TextureHandle::size(&texture)?
-                }
-                TextureSource::Area(texture_area) => texture_area.size(),
-            };
+            let size = self.texture_size(sys_interface)?;
 
             let ratio = size.0.get() as f32 / size.1.get() as f32;
             Ok(AspectRatioPreferredDirection::height_from_width(
@@ -201,16 +209,35 @@ impl<T: crate::core::System> Widget<T> for ImageDisplay {
     fn update(
         &mut self,
         event: WidgetUpdateEvent,
-        _sys_interface: &mut T,
+        sys_interface: &mut T,
     ) -> Result<FrameTransiency, String> {
         self.draw_pos = event.position;
+        if self.placeholder.is_some() {
+            let was_loaded = self.texture_loaded;
+            self.texture_loaded = sys_interface.try_image(&self.texture_path)?.is_some();
+            if !self.texture_loaded {
+                // check again soon
+                return Ok(FrameTransiency::NextFrameQuick);
+            }
+            if !was_loaded {
+                // sizing this frame was from the placeholder. redo it with the
+                // loaded texture before drawing
+                return Ok(FrameTransiency::NextFrameNow);
+            }
+        }
         Ok(Default::default())
     }
 
     fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
-        let mut texture = sys_interface.image(&self.texture_path)?;
+        let (mut texture, texture_src) = match &self.placeholder {
+            Some(placeholder) if !self.texture_loaded => (
+                sys_interface.image(placeholder)?,
+                TextureSource::WholeTexture,
+            ),
+            _ => (sys_interface.image(&self.texture_path)?, self.texture_src),
+        };
 
-        let src = match self.texture_src {
+        let src = match texture_src {
             TextureSource::WholeTexture => {
                 let texture_size = texture.size()?;
                 TextureRect {
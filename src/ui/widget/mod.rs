@@ -2,28 +2,41 @@ pub mod image_display;
 pub mod strut;
 
 pub mod border;
+pub mod nine_slice;
 pub mod tiled_image_display;
+pub mod tilemap_view;
 
 pub mod multi_line_label;
 pub mod single_line_label;
+pub mod text_area;
+pub mod text_input;
+
+pub mod fps_overlay;
+pub mod particle_view;
 
 pub mod checkbox;
+pub mod radio_group;
 
 pub mod background;
 pub mod button;
 pub mod clipper;
 pub mod scroller;
 pub mod sizing;
+pub mod slider;
 
 pub mod horizontal_layout;
 pub mod slot;
 pub mod vertical_layout;
 
-use std::time::{Duration, Instant};
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
 
 use crate::{
     core::{clipping_rect::ClippingRect, System},
     ui::util::{
+        frame_timing::{FrameLimiter, FrameStats, FrameTiming},
         length::{
             clamp, AspectRatioPreferredDirection, MaxLen, MaxLenFailPolicy, MinLen,
             MinLenFailPolicy, PreferredPortion,
@@ -194,6 +207,10 @@ pub enum FrameTransiency {
     /// this enum variant means the ui doesn't care what happens
     #[default]
     None,
+    /// like None, but the next frame happens by this instant even without
+    /// events. e.g. a blinking caret or a countdown. combined, the earliest
+    /// instant is used
+    WakeAt(Instant),
     /// another frame should follow quickly after this
     NextFrameQuick,
     /// this frame should not be drawn. the next frame occurs immediately
@@ -201,15 +218,24 @@ pub enum FrameTransiency {
 }
 
 impl FrameTransiency {
+    /// WakeAt, after a duration from now
+    pub fn wake_in(duration: Duration) -> Self {
+        FrameTransiency::WakeAt(Instant::now() + duration)
+    }
+
     fn strength(&self) -> u8 {
         match self {
             FrameTransiency::None => 0,
-            FrameTransiency::NextFrameQuick => 1,
-            FrameTransiency::NextFrameNow => 2,
+            FrameTransiency::WakeAt(_) => 1,
+            FrameTransiency::NextFrameQuick => 2,
+            FrameTransiency::NextFrameNow => 3,
         }
     }
 
     fn strongest(self, other: Self) -> Self {
+        if let (FrameTransiency::WakeAt(a), FrameTransiency::WakeAt(b)) = (self, other) {
+            return FrameTransiency::WakeAt(a.min(b));
+        }
         if self.strength() >= other.strength() {
             self
         } else {
@@ -335,6 +361,34 @@ pub enum HandlerReturnValue {
 pub fn gui_loop<T: System, F>(
     max_delay: Duration,
     system_interface: &mut T,
+    handler: F,
+) -> Result<(), String>
+where
+    F: FnMut(
+        &mut T,
+        &mut [Option<crate::core::event::Event>],
+        Duration,
+    ) -> Result<HandlerReturnValue, String>,
+{
+    gui_loop_limited(
+        max_delay,
+        &mut FrameLimiter::default(),
+        None,
+        system_interface,
+        handler,
+    )
+}
+
+/// same as gui_loop(), but frames requested with NextFrameQuick (e.g. during
+/// an animation) are capped by the limiter
+///
+/// if stats is given, each frame is recorded in it (e.g. for an FpsOverlay).
+/// the handler draws and presents, so its whole call is recorded as the update
+pub fn gui_loop_limited<T: System, F>(
+    max_delay: Duration,
+    limiter: &mut FrameLimiter,
+    stats: Option<&Cell<FrameStats>>,
+    system_interface: &mut T,
     mut handler: F,
 ) -> Result<(), String>
 where
@@ -349,55 +403,136 @@ where
 
     // use for dt calculation
     let mut previous_handle_call = Instant::now();
+    // of the previous frame, recorded once its total is known
+    let mut handler_time: Option<Duration> = None;
     loop {
         let next_handle_draw_call = Instant::now();
-        let handle_result = handler(
-            system_interface,
-            &mut events_accumulator,
-            next_handle_draw_call - previous_handle_call,
-        )?;
+        let dt = next_handle_draw_call - previous_handle_call;
+        if let (Some(stats), Some(update)) = (stats, handler_time) {
+            let mut recorded = stats.take();
+            recorded.record(FrameTiming {
+                update,
+                total: dt,
+                ..Default::default()
+            });
+            stats.set(recorded);
+        }
+        let handle_result = handler(system_interface, &mut events_accumulator, dt)?;
         previous_handle_call = next_handle_draw_call;
+        handler_time = Some(Instant::now() - previous_handle_call);
 
         // handle events accumulation
         events_accumulator.clear();
 
         match handle_result {
             HandlerReturnValue::Stop => return Ok(()),
-            HandlerReturnValue::Some(frame_transiency) => {
-                match frame_transiency {
-                    FrameTransiency::NextFrameNow => {
-                        // don't wait for any events. go back to handler now
+            HandlerReturnValue::Some(frame_transiency) => accumulate_events(
+                system_interface,
+                &mut events_accumulator,
+                frame_transiency,
+                previous_handle_call,
+                max_delay,
+                limiter,
+            ),
+        };
+    }
+}
+
+/// gather the events for the next frame, waiting for them according to the
+/// frame transiency. previous_handle_call is when the frame started. the
+/// limiter applies to NextFrameQuick
+pub(crate) fn accumulate_events<T: System>(
+    system_interface: &mut T,
+    events_accumulator: &mut Vec<Option<crate::core::event::Event>>,
+    frame_transiency: FrameTransiency,
+    previous_handle_call: Instant,
+    max_delay: Duration,
+    limiter: &mut FrameLimiter,
+) {
+    // the mixer progresses when frames are presented
+    let frame_transiency = if system_interface.mix_animating() {
+        frame_transiency | FrameTransiency::NextFrameQuick
+    } else {
+        frame_transiency
+    };
+    match frame_transiency {
+        FrameTransiency::NextFrameNow => {
+            // don't wait for any events. go back to handler now
+        }
+        _ => {
+            let oldest_event = match frame_transiency {
+                FrameTransiency::None => {
+                    // wait up to forever for the first event of this frame to
+                    // come in
+                    let event = system_interface.event();
+                    events_accumulator.push(Some(event));
+                    Instant::now()
+                }
+                FrameTransiency::WakeAt(wake_at) => {
+                    // same, but only until the requested instant
+                    let now = Instant::now();
+                    if wake_at <= now {
+                        return;
                     }
-                    _ => {
-                        let oldest_event = if let FrameTransiency::None = frame_transiency {
-                            // wait up to forever for the first event of this frame to
-                            // come in
-                            let event = system_interface.event();
+                    match system_interface.event_timeout(wake_at - now) {
+                        Some(event) => {
                             events_accumulator.push(Some(event));
                             Instant::now()
-                        } else {
-                            previous_handle_call
-                        };
-
-                        // don't send off the event immediately! wait a bit and
-                        // accumulate several events to be processed together. max bound
-                        // on waiting so that the first event received isn't too stale
-
-                        loop {
-                            let max_time = oldest_event + max_delay;
-                            let now = Instant::now();
-                            if max_time <= now {
-                                break; // can't wait any longer
-                            }
-
-                            let time_to_wait = max_time - now;
-                            if let Some(event) = system_interface.event_timeout(time_to_wait) {
-                                events_accumulator.push(Some(event));
-                            }
                         }
+                        None => return, // woke without events
                     }
                 }
+                _ => previous_handle_call,
+            };
+
+            // don't send off the event immediately! wait a bit and
+            // accumulate several events to be processed together. max bound
+            // on waiting so that the first event received isn't too stale
+            let mut max_time = oldest_event + max_delay;
+            let quick = matches!(frame_transiency, FrameTransiency::NextFrameQuick);
+            if quick && limiter.target.is_some() {
+                // keep accumulating until the frame limit, except the end of
+                // it which is spun
+                let now = Instant::now();
+                max_time = max_time.max(now + limiter.remaining(now).saturating_sub(limiter.spin));
             }
-        };
+
+            loop {
+                let now = Instant::now();
+                if max_time <= now {
+                    break; // can't wait any longer
+                }
+
+                let time_to_wait = max_time - now;
+                if let Some(event) = system_interface.event_timeout(time_to_wait) {
+                    events_accumulator.push(Some(event));
+                }
+            }
+
+            if quick {
+                limiter.wait();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_transiency_combine() {
+        let now = Instant::now();
+        let early = FrameTransiency::WakeAt(now);
+        let late = FrameTransiency::WakeAt(now + Duration::from_secs(1));
+        assert!(matches!(late | early, FrameTransiency::WakeAt(v) if v == now));
+        assert!(matches!(
+            FrameTransiency::None | late,
+            FrameTransiency::WakeAt(_)
+        ));
+        assert!(matches!(
+            early | FrameTransiency::NextFrameQuick,
+            FrameTransiency::NextFrameQuick
+        ));
     }
 }
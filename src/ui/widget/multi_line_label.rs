@@ -1,9 +1,9 @@
-use std::num::NonZeroU16;
+use std::{num::NonZeroU16, ops::Range};
 
 use crate::{
     core::{
         color::Color,
-        texture_rect::{TextureRect, TextureSource},
+        texture_rect::{TextureRect, TextureRectF, TextureSourceF},
         NonEmptyStr, TextureHandle,
     },
     ui::{
@@ -11,6 +11,7 @@ use crate::{
             length::{MaxLenFailPolicy, MinLenFailPolicy, PreferredPortion},
             rect::rect_len_round,
             rust::CellRefOrCell,
+            text_draw::{line_height, wrap_text},
         },
         widget::FrameTransiency,
     },
@@ -40,7 +41,8 @@ impl Default for MultiLineMinHeightFailPolicy {
     }
 }
 
-/// a widget that contains multiline text
+/// a widget that contains multiline text. it's wrapped the same way as a
+/// TextArea
 ///
 /// this widget defines a height_from_width but not a width_from_height. because
 /// of this, the text will be compressed vertically when all of the following
@@ -71,6 +73,9 @@ pub struct MultiLineLabel<'state> {
 
     /// state stored for draw from update
     draw_pos: crate::ui::util::rect::FRect,
+    /// byte ranges of the wrapped lines
+    lines: Vec<Range<usize>>,
+    line_h: f32,
 }
 
 impl<'state> MultiLineLabel<'state> {
@@ -89,6 +94,8 @@ impl<'state> MultiLineLabel<'state> {
             min_h_policy: Default::default(),
             max_h_policy: Default::default(),
             draw_pos: Default::default(),
+            lines: Vec::new(),
+            line_h: 0.,
         }
     }
 }
@@ -132,15 +139,17 @@ impl<'state, T: crate::core::System> Widget<T> for MultiLineLabel<'state> {
                 };
 
                 let text = self.text.scope_take();
-                let text: NonEmptyStr = match text.as_str().try_into() {
-                    Ok(v) => v,
-                    Err(()) => return Ok(0.),
-                };
-
-                let texture =
-                    sys_interface.text(text, self.color, self.point_size, Some(wrap_width))?;
-                let size = texture.size()?;
-                Ok(size.1.get() as f32)
+                if text.is_empty() {
+                    return Ok(0.);
+                }
+                let lines = wrap_text(
+                    sys_interface,
+                    &text,
+                    wrap_width.get() as f32,
+                    self.color,
+                    self.point_size,
+                )?;
+                Ok(lines.len() as f32 * line_height(sys_interface, self.color, self.point_size)?)
             })()),
             _ => None,
         }
@@ -149,9 +158,22 @@ impl<'state, T: crate::core::System> Widget<T> for MultiLineLabel<'state> {
     fn update(
         &mut self,
         event: WidgetUpdateEvent,
-        _sys_interface: &mut T,
+        sys_interface: &mut T,
     ) -> Result<FrameTransiency, String> {
         self.draw_pos = event.position;
+        let text = self.text.scope_take();
+        self.line_h = line_height(sys_interface, self.color, self.point_size)?;
+        self.lines = if text.is_empty() {
+            Vec::new()
+        } else {
+            wrap_text(
+                sys_interface,
+                &text,
+                event.position.w,
+                self.color,
+                self.point_size,
+            )?
+        };
         Ok(Default::default())
     }
 
@@ -160,71 +182,61 @@ impl<'state, T: crate::core::System> Widget<T> for MultiLineLabel<'state> {
             Some(v) => v,
             None => return Ok(()), // no input handling
         };
+        if self.lines.is_empty() {
+            return Ok(());
+        }
 
-        let text = self.text.scope_take();
-        let text: NonEmptyStr = match text.as_str().try_into() {
-            Ok(v) => v,
-            Err(()) => return Ok(()),
-        };
-
-        let mut texture =
-            sys_interface.text(text, self.color, self.point_size, Some(position.w))?;
-        let size = texture.size()?;
-
-        if size.1 <= position.h {
-            let excess = position.h.get() - size.1.get();
-            let excess = excess as f32;
-            let excess = excess * self.max_h_policy.0;
-            let excess = excess.round() as i32;
-            texture.copy(
-                TextureSource::WholeTexture,
-                TextureRect {
-                    x: position.x,
-                    y: position.y + excess,
-                    w: size.0,
-                    h: size.1,
-                },
-            )?;
+        let total_h = self.lines.len() as f32 * self.line_h;
+        let pos_h = position.h.get() as f32;
+        // top of the first line, and the height each line is drawn at
+        let (y, line_h, cut_off) = if total_h <= pos_h {
+            let excess = (pos_h - total_h) * self.max_h_policy.0;
+            (position.y as f32 + excess.round(), self.line_h, false)
         } else {
-            let excess = size.1.get() - position.h.get();
-            let excess = excess as f32;
+            let excess = total_h - pos_h;
             match self.min_h_policy {
                 MultiLineMinHeightFailPolicy::CutOff(v) => {
                     let excess = excess * (1. - v);
-                    let excess = excess.round() as i32;
-                    texture.copy(
-                        TextureRect {
-                            x: 0,
-                            y: excess,
-                            w: size.0,
-                            h: position.h,
-                        },
-                        TextureRect {
-                            x: position.x,
-                            y: position.y,
-                            w: size.0,
-                            h: position.h,
-                        },
-                    )?
+                    (position.y as f32 - excess.round(), self.line_h, true)
                 }
                 MultiLineMinHeightFailPolicy::AllowRunOff(v) => {
                     let excess = excess * (v.0 - 1.);
-                    let excess = excess.round() as i32;
-                    texture.copy(
-                        TextureSource::WholeTexture,
-                        TextureRect {
-                            x: position.x,
-                            y: position.y + excess,
-                            w: size.0,
-                            h: size.1,
-                        },
-                    )?;
+                    (position.y as f32 + excess.round(), self.line_h, false)
                 }
                 MultiLineMinHeightFailPolicy::None(_, _) => {
-                    texture.copy(TextureSource::WholeTexture, position)?;
+                    // compressed to fit
+                    (position.y as f32, pos_h / self.lines.len() as f32, false)
                 }
             }
+        };
+
+        let prev_clip = sys_interface.get_clip();
+        if cut_off {
+            sys_interface.clip(prev_clip.intersect_area(Some(position)));
+        }
+        let ret = (|| {
+            let text = self.text.scope_take();
+            for (i, range) in self.lines.iter().enumerate() {
+                // the text could have been changed since update
+                let line: NonEmptyStr = match text.get(range.clone()).map(|s| s.try_into()) {
+                    Some(Ok(v)) => v,
+                    _ => continue,
+                };
+                let mut texture = sys_interface.text(line, self.color, self.point_size, None)?;
+                let w = texture.size()?.0.get() as f32;
+                let dst =
+                    match TextureRectF::new(position.x as f32, y + i as f32 * line_h, w, line_h) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                let src: TextureSourceF = Default::default();
+                texture.copy_f(src, dst)?;
+            }
+            Ok(())
+        })();
+        if cut_off {
+            sys_interface.clip(prev_clip);
         }
-        Ok(())
+        ret
     }
 }
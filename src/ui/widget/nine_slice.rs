@@ -0,0 +1,322 @@
+use std::{num::NonZeroU32, path::PathBuf};
+
+use crate::{
+    core::{texture_rect::TextureRect, PathLike, TextureHandle},
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{Widget, WidgetUpdateEvent};
+
+/// how a slice fills an area bigger than itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SliceFill {
+    #[default]
+    Stretch,
+    /// repeated, cutting off at the right and bottom
+    Tile,
+}
+
+/// distances in from each side of the source image, in source pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+/// contains some inner content on top of a panel made from a single image.
+/// the image is cut into nine by the insets: the corners are drawn as is,
+/// the edges and center fill the rest
+///
+/// the contained content is placed inside the insets, the same way Border
+/// keeps content off the frame
+pub struct NineSlice<'b, T: crate::core::System + 'b> {
+    pub contained: Box<dyn Widget<T> + 'b>,
+
+    pub texture_path: PathBuf,
+    /// area of the texture holding the nine slices
+    pub texture_src: TextureRect,
+    pub insets: Insets,
+
+    pub edges: SliceFill,
+    pub center: SliceFill,
+    /// default: true
+    pub draw_center: bool,
+    /// each source pixel is drawn as scale by scale pixels. default: 1
+    pub scale: NonZeroU32,
+
+    /// store state for draw from update
+    draw_pos: FRect,
+}
+
+impl<'b, T: crate::core::System + 'b> NineSlice<'b, T> {
+    pub fn new<'a, P: Into<PathLike<'a>>>(
+        contained: Box<dyn Widget<T> + 'b>,
+        texture_path: P,
+        texture_src: TextureRect,
+        insets: Insets,
+    ) -> Self {
+        let texture_path: PathLike = texture_path.into();
+        let texture_path: PathBuf = texture_path.into();
+        Self {
+            contained,
+            texture_path,
+            texture_src,
+            insets,
+            edges: Default::default(),
+            center: Default::default(),
+            draw_center: true,
+            scale: NonZeroU32::MIN,
+            draw_pos: Default::default(),
+        }
+    }
+
+    /// left and right, in screen pixels
+    fn horizontal_inset(&self) -> (u32, u32) {
+        let s = self.scale.get();
+        (self.insets.left * s, self.insets.right * s)
+    }
+
+    /// top and bottom, in screen pixels
+    fn vertical_inset(&self) -> (u32, u32) {
+        let s = self.scale.get();
+        (self.insets.top * s, self.insets.bottom * s)
+    }
+}
+
+/// copy src to fill dst
+fn fill<'a, H: TextureHandle<'a>>(
+    txt: &mut H,
+    src: TextureRect,
+    dst: TextureRect,
+    mode: SliceFill,
+    scale: NonZeroU32,
+) -> Result<(), String> {
+    if mode == SliceFill::Stretch {
+        return txt.copy(src, dst);
+    }
+    let scale = scale.get();
+    let tile_w = src.w.get() * scale;
+    let tile_h = src.h.get() * scale;
+    let mut y = 0;
+    while y < dst.h.get() {
+        let h = tile_h.min(dst.h.get() - y);
+        let mut x = 0;
+        while x < dst.w.get() {
+            let w = tile_w.min(dst.w.get() - x);
+            for (src_x, src_w, dst_x, dst_w) in cut(w, scale) {
+                for (src_y, src_h, dst_y, dst_h) in cut(h, scale) {
+                    let part_src =
+                        TextureRect::new(src.x + src_x as i32, src.y + src_y as i32, src_w, src_h);
+                    let part_dst = TextureRect::new(
+                        dst.x + (x + dst_x) as i32,
+                        dst.y + (y + dst_y) as i32,
+                        dst_w,
+                        dst_h,
+                    );
+                    // None for an empty part
+                    if let (Some(part_src), Some(part_dst)) = (part_src, part_dst) {
+                        txt.copy(part_src, part_dst)?;
+                    }
+                }
+            }
+            x += tile_w;
+        }
+        y += tile_h;
+    }
+    Ok(())
+}
+
+/// splits a tile's length in screen pixels into the whole source pixels, then
+/// the source pixel which is cut off. each is (src offset, src len, dst offset,
+/// dst len). the cut pixel is drawn narrower instead of stretching the rest
+fn cut(len: u32, scale: u32) -> [(u32, u32, u32, u32); 2] {
+    let whole = len / scale;
+    [
+        (0, whole, 0, whole * scale),
+        (whole, 1, whole * scale, len % scale),
+    ]
+}
+
+/// splits a span into before, middle and after. each may be empty
+fn split(start: i32, len: u32, before: u32, after: u32) -> [(i32, u32); 3] {
+    let before = before.min(len);
+    let after = after.min(len - before);
+    [
+        (start, before),
+        (start + before as i32, len - before - after),
+        (start + (len - after) as i32, after),
+    ]
+}
+
+impl<'b, T: crate::core::System> Widget<T> for NineSlice<'b, T> {
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        self.contained.preferred_portion()
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        let (top, bottom) = self.vertical_inset();
+        let (left, right) = self.horizontal_inset();
+        // remove the insets for the contained, then add them back
+        let pref_h = pref_h - (top + bottom) as f32;
+        debug_assert!(pref_h >= 0.); // safe since min() and max() assure this
+        self.contained
+            .preferred_width_from_height(pref_h, sys_interface)
+            .map(|some| some.map(|ok| ok + (left + right) as f32))
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        let (top, bottom) = self.vertical_inset();
+        let (left, right) = self.horizontal_inset();
+        let pref_w = pref_w - (left + right) as f32;
+        debug_assert!(pref_w >= 0.);
+        self.contained
+            .preferred_height_from_width(pref_w, sys_interface)
+            .map(|some| some.map(|ok| ok + (top + bottom) as f32))
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.contained.preferred_ratio_exceed_parent()
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.contained.min_w_fail_policy()
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.contained.min_h_fail_policy()
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.contained.max_w_fail_policy()
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.contained.max_h_fail_policy()
+    }
+
+    fn min(&self, sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        let (left, right) = self.horizontal_inset();
+        let (top, bottom) = self.vertical_inset();
+        let m = self.contained.min(sys_interface)?;
+        Ok((
+            m.0.combined(MinLen((left + right) as f32)),
+            m.1.combined(MinLen((top + bottom) as f32)),
+        ))
+    }
+
+    fn max(&self, sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        let (left, right) = self.horizontal_inset();
+        let (top, bottom) = self.vertical_inset();
+        let m = self.contained.max(sys_interface)?;
+        Ok((
+            m.0.combined(MaxLen((left + right) as f32)),
+            m.1.combined(MaxLen((top + bottom) as f32)),
+        ))
+    }
+
+    fn update(
+        &mut self,
+        mut event: WidgetUpdateEvent,
+        sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.draw_pos = event.position;
+        let (left, right) = self.horizontal_inset();
+        let (top, bottom) = self.vertical_inset();
+        let position_for_child = FRect {
+            x: event.position.x + left as f32,
+            y: event.position.y + top as f32,
+            w: event.position.w - (left + right) as f32,
+            h: event.position.h - (top + bottom) as f32,
+        };
+        self.contained
+            .update(event.sub_event(position_for_child), sys_interface)
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let maybe_pos: Option<TextureRect> = self.draw_pos.into();
+
+        // draw panel if non empty position (and snap to grid)
+        if let Some(pos) = maybe_pos {
+            let src = self.texture_src;
+            let src_cols = split(src.x, src.w.get(), self.insets.left, self.insets.right);
+            let src_rows = split(src.y, src.h.get(), self.insets.top, self.insets.bottom);
+            let (left, right) = self.horizontal_inset();
+            let (top, bottom) = self.vertical_inset();
+            let dst_cols = split(pos.x, pos.w.get(), left, right);
+            let dst_rows = split(pos.y, pos.h.get(), top, bottom);
+
+            let mut txt = sys_interface.image(&self.texture_path)?;
+            for row in 0..3 {
+                for col in 0..3 {
+                    let is_center = row == 1 && col == 1;
+                    if is_center && !self.draw_center {
+                        continue;
+                    }
+                    let part_src = TextureRect::new(
+                        src_cols[col].0,
+                        src_rows[row].0,
+                        src_cols[col].1,
+                        src_rows[row].1,
+                    );
+                    let part_dst = TextureRect::new(
+                        dst_cols[col].0,
+                        dst_rows[row].0,
+                        dst_cols[col].1,
+                        dst_rows[row].1,
+                    );
+                    let (part_src, part_dst) = match (part_src, part_dst) {
+                        (Some(s), Some(d)) => (s, d),
+                        _ => continue, // zero area slice
+                    };
+                    let mode = if is_center {
+                        self.center
+                    } else if row == 1 || col == 1 {
+                        self.edges
+                    } else {
+                        // corners are only scaled
+                        SliceFill::Stretch
+                    };
+                    fill(&mut txt, part_src, part_dst, mode, self.scale)?;
+                }
+            }
+        }
+
+        self.contained.draw(sys_interface)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(10, 30, 4, 6), [(10, 4), (14, 20), (34, 6)]);
+        // insets bigger than the span
+        assert_eq!(split(0, 8, 6, 6), [(0, 6), (6, 0), (6, 2)]);
+    }
+
+    #[test]
+    fn test_cut() {
+        assert_eq!(cut(12, 3), [(0, 4, 0, 12), (4, 1, 12, 0)]);
+        // 2 whole source pixels, then 1 of the 3 screen pixels of the next
+        assert_eq!(cut(7, 3), [(0, 2, 0, 6), (2, 1, 6, 1)]);
+        assert_eq!(cut(2, 3), [(0, 0, 0, 0), (0, 1, 0, 2)]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+
+use crate::{
+    core::particles::ParticleEmitter,
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::CustomSizing, Widget, WidgetUpdateEvent};
+
+/// simulates and draws particles in screen coordinates. doesn't consume
+/// events, so it can be put in front of other widgets with a Background, e.g.
+/// sparkles when a button is pressed
+///
+/// no frames are requested while the emitter is idle. whatever starts it
+/// later (e.g. a button's functionality calling burst()) should return
+/// NextFrameQuick, since the view might have already updated that frame
+pub struct ParticleView<'state> {
+    pub emitter: &'state RefCell<ParticleEmitter>,
+    /// if set, the emitter is moved to this point in the widget each update,
+    /// as a portion of the width and height. default: center
+    pub anchor: Option<(f32, f32)>,
+    pub sizing: CustomSizing,
+}
+
+impl<'state> ParticleView<'state> {
+    pub fn new(emitter: &'state RefCell<ParticleEmitter>) -> Self {
+        Self {
+            emitter,
+            anchor: Some((0.5, 0.5)),
+            sizing: Default::default(),
+        }
+    }
+}
+
+impl<'state, T: crate::core::System> Widget<T> for ParticleView<'state> {
+    fn update(
+        &mut self,
+        event: WidgetUpdateEvent,
+        _sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        let mut emitter = self.emitter.borrow_mut();
+        if let Some((x, y)) = self.anchor {
+            let FRect {
+                x: pos_x,
+                y: pos_y,
+                w,
+                h,
+            } = event.position;
+            emitter.x = pos_x + w * x;
+            emitter.y = pos_y + h * y;
+        }
+        emitter.update(event.dt);
+        Ok(
+            if emitter.alive() || (emitter.emitting && emitter.rate > 0.) {
+                FrameTransiency::NextFrameQuick
+            } else {
+                FrameTransiency::None
+            },
+        )
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        self.emitter.borrow().draw(sys_interface, (0., 0.))
+    }
+
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_width_from_height(pref_h).map(Ok)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_height_from_width(pref_w).map(Ok)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+}
@@ -0,0 +1,299 @@
+use std::cell::Cell;
+
+use crate::{
+    core::{
+        event::{Event, NavKey},
+        input::Hotkey,
+        texture_rect::TextureRect,
+    },
+    ui::{
+        util::length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::NestedContentSizing, Widget, WidgetUpdateEvent};
+
+/// the state shared by a set of RadioButtons, where one is selected at a time
+pub struct RadioGroup<'state> {
+    /// the index of the selected option
+    pub selected: &'state Cell<usize>,
+    /// set when the selection changes. shared by the options, so it isn't
+    /// cleared during update; reset it once handled
+    pub changed: &'state Cell<bool>,
+    /// number of options
+    pub count: usize,
+
+    /// actions which select the previous or next option, wrapping around. the
+    /// arrow keys also work while an option is hovered
+    pub previous: Option<Hotkey<'state>>,
+    pub next: Option<Hotkey<'state>>,
+
+    /// the option under the mouse
+    hovered: Cell<Option<usize>>,
+}
+
+impl<'state> RadioGroup<'state> {
+    pub fn new(selected: &'state Cell<usize>, changed: &'state Cell<bool>, count: usize) -> Self {
+        Self {
+            selected,
+            changed,
+            count,
+            previous: None,
+            next: None,
+            hovered: Cell::new(None),
+        }
+    }
+
+    /// move the selection some options forward or backward, wrapping around
+    pub fn step(&self, n: isize) {
+        if self.count == 0 {
+            return;
+        }
+        let i = self.selected.get().min(self.count - 1) as isize + n;
+        self.select(i.rem_euclid(self.count as isize) as usize);
+    }
+
+    /// sets changed if it wasn't already selected
+    pub fn select(&self, index: usize) {
+        if self.selected.replace(index) != index {
+            self.changed.set(true);
+        }
+    }
+}
+
+/// which of a RadioButton's widgets is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RadioShown {
+    Idle,
+    Hovered,
+    Selected,
+}
+
+/// one option in a RadioGroup. clicking it or using its hotkey selects it,
+/// which deselects the others. place the options in any layout
+pub struct RadioButton<'b, 'state, T: crate::core::System + 'b> {
+    pub group: &'state RadioGroup<'state>,
+    /// which option this is
+    pub index: usize,
+
+    pub idle: Box<dyn Widget<T> + 'b>,
+    pub hovered: Box<dyn Widget<T> + 'b>,
+    pub selected: Box<dyn Widget<T> + 'b>,
+
+    /// sizing is inherited from the content currently shown
+    pub sizing: NestedContentSizing,
+
+    /// an action which selects this option
+    pub hotkey: Option<Hotkey<'state>>,
+
+    /// state stored for draw from update
+    is_hovered: bool,
+    /// the widget which was updated. a later option can change the selection
+    /// in the same frame
+    shown: RadioShown,
+}
+
+impl<'b, 'state, T: crate::core::System + 'b> RadioButton<'b, 'state, T> {
+    pub fn new(
+        group: &'state RadioGroup<'state>,
+        index: usize,
+        idle: Box<dyn Widget<T> + 'b>,
+        hovered: Box<dyn Widget<T> + 'b>,
+        selected: Box<dyn Widget<T> + 'b>,
+    ) -> Self {
+        Self {
+            group,
+            index,
+            idle,
+            hovered,
+            selected,
+            sizing: Default::default(),
+            hotkey: None,
+            is_hovered: false,
+            shown: if group.selected.get() == index {
+                RadioShown::Selected
+            } else {
+                RadioShown::Idle
+            },
+        }
+    }
+
+    fn current_widget(&self) -> &dyn Widget<T> {
+        match self.shown {
+            RadioShown::Idle => self.idle.as_ref(),
+            RadioShown::Hovered => self.hovered.as_ref(),
+            RadioShown::Selected => self.selected.as_ref(),
+        }
+    }
+
+    fn current_widget_mut(&mut self) -> &mut dyn Widget<T> {
+        match self.shown {
+            RadioShown::Idle => self.idle.as_mut(),
+            RadioShown::Hovered => self.hovered.as_mut(),
+            RadioShown::Selected => self.selected.as_mut(),
+        }
+    }
+}
+
+impl<'b, 'state, T: crate::core::System + 'b> Widget<T> for RadioButton<'b, 'state, T> {
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing
+            .preferred_ratio_exceed_parent(self.current_widget())
+    }
+
+    fn min(&self, sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        self.sizing.min(self.current_widget(), sys_interface)
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy(self.current_widget())
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy(self.current_widget())
+    }
+
+    fn max(&self, sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        self.sizing.max(self.current_widget(), sys_interface)
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy(self.current_widget())
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy(self.current_widget())
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        self.sizing.preferred_portion(self.current_widget())
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing
+            .preferred_width_from_height(self.current_widget(), pref_h, sys_interface)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing
+            .preferred_height_from_width(self.current_widget(), pref_w, sys_interface)
+    }
+
+    fn update(
+        &mut self,
+        mut event: WidgetUpdateEvent,
+        sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        let group = self.group;
+        let selected_before = group.selected.get();
+        let area: Option<TextureRect> = event.position.into();
+        for e in event.events.iter_mut().filter(|e| e.is_some()) {
+            // can still occur even if zero area
+            let ev = e.as_ref().unwrap();
+            let hotkey = |h: Option<&Hotkey>| h.and_then(|h| h.matches(ev));
+            if let Some(down) = hotkey(self.hotkey.as_ref()) {
+                *e = None;
+                if !down {
+                    // rising edge
+                    group.select(self.index);
+                }
+                continue;
+            }
+            // the group's events are used by whichever option sees them first
+            let step = match (hotkey(group.previous.as_ref()), hotkey(group.next.as_ref())) {
+                (Some(down), _) => Some((down, -1)),
+                (None, Some(down)) => Some((down, 1)),
+                (None, None) => None,
+            };
+            if let Some((down, n)) = step {
+                *e = None;
+                if down {
+                    group.step(n);
+                }
+                continue;
+            }
+            match *ev {
+                Event::Mouse(mouse) => {
+                    let over = area.is_some_and(|a| a.contains_point((mouse.x, mouse.y)))
+                        && event.clipping_rect.contains_point((mouse.x, mouse.y));
+                    self.is_hovered = over;
+                    if over {
+                        group.hovered.set(Some(self.index));
+                        if mouse.changed {
+                            *e = None;
+                            if !mouse.down {
+                                // rising edge
+                                group.select(self.index);
+                            }
+                        }
+                    } else if group.hovered.get() == Some(self.index) {
+                        group.hovered.set(None);
+                    }
+                }
+                Event::NavKey(nav) if group.hovered.get().is_some() => {
+                    let n = match nav.key {
+                        NavKey::Left | NavKey::Up => -1,
+                        NavKey::Right | NavKey::Down => 1,
+                        _ => continue,
+                    };
+                    *e = None;
+                    if nav.down {
+                        group.step(n);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.shown = if group.selected.get() == self.index {
+            RadioShown::Selected
+        } else if self.is_hovered {
+            RadioShown::Hovered
+        } else {
+            RadioShown::Idle
+        };
+        let mut ret = FrameTransiency::None;
+        if group.selected.get() != selected_before {
+            // options updated before this one show the old selection
+            ret = FrameTransiency::NextFrameNow;
+        }
+        let sizing = self.sizing;
+        ret |= sizing.update_contained(self.current_widget_mut(), &mut event, sys_interface)?;
+        Ok(ret)
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        self.current_widget().draw(sys_interface)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        let selected = Cell::new(0);
+        let changed = Cell::new(false);
+        let group = RadioGroup::new(&selected, &changed, 3);
+        group.step(-1);
+        assert_eq!(selected.get(), 2);
+        assert!(changed.take());
+        group.select(2);
+        assert!(!changed.get());
+        group.step(2);
+        assert_eq!(selected.get(), 1);
+        selected.set(10); // out of range
+        group.step(1);
+        assert_eq!(selected.get(), 0);
+    }
+}
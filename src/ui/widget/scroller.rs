@@ -345,7 +345,7 @@ impl<'b, T: crate::core::System> Widget<T> for Scroller<'b, T> {
         if scroll_y_is_effective || scroll_x_is_effective {
             // handle click and drag scroll
             for e in event.events.iter_mut().filter(|e| e.is_some()) {
-                match e.unwrap() {
+                match *e.as_ref().unwrap() {
                     crate::core::event::Event::MouseWheel(m) => {
                         if pos.contains_point((m.x, m.y))
                             && event.clipping_rect.contains_point((m.x, m.y))
@@ -0,0 +1,709 @@
+use std::{cell::Cell, path::PathBuf};
+
+use crate::{
+    core::{
+        event::{Event, NavKey},
+        input::Hotkey,
+        texture_rect::TextureRect,
+        PathLike, TextureHandle,
+    },
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::CustomSizing, Widget, WidgetUpdateEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SliderDirection {
+    /// min on the left
+    #[default]
+    Horizontal,
+    /// min on the bottom
+    Vertical,
+}
+
+/// clamp to min and max, then round to a multiple of step from min. a step of
+/// 0 is continuous
+pub fn snap(value: f32, min: f32, max: f32, step: f32) -> f32 {
+    let (lo, hi) = if min <= max { (min, max) } else { (max, min) };
+    let value = value.clamp(lo, hi);
+    if step <= 0. {
+        return value;
+    }
+    let stepped = (min + ((value - min) / step).round() * step).clamp(lo, hi);
+    // the last step might not fit evenly, so max is also a choice
+    if (max - value).abs() < (stepped - value).abs() {
+        max
+    } else {
+        stepped
+    }
+}
+
+/// how far value is from min to max, from 0 to 1
+fn portion(value: f32, min: f32, max: f32) -> f32 {
+    if min == max {
+        return 0.;
+    }
+    ((value - min) / (max - min)).clamp(0., 1.)
+}
+
+/// amount moved by one key press
+fn key_step(min: f32, max: f32, step: f32) -> f32 {
+    if step > 0. {
+        step
+    } else {
+        (max - min).abs() / 100.
+    }
+}
+
+/// where the thumb can go. the thumb is square, with sides the width of the
+/// track (or the height if horizontal)
+#[derive(Clone, Copy)]
+struct Track {
+    pos: FRect,
+    direction: SliderDirection,
+}
+
+impl Track {
+    fn thumb_len(&self) -> f32 {
+        match self.direction {
+            SliderDirection::Horizontal => self.pos.h,
+            SliderDirection::Vertical => self.pos.w,
+        }
+    }
+
+    /// distance the thumb's center can move
+    fn travel(&self) -> f32 {
+        let len = match self.direction {
+            SliderDirection::Horizontal => self.pos.w,
+            SliderDirection::Vertical => self.pos.h,
+        };
+        (len - self.thumb_len()).max(0.)
+    }
+
+    /// a point's distance along the track, from the min end
+    fn along(&self, point: (i32, i32)) -> f32 {
+        match self.direction {
+            SliderDirection::Horizontal => point.0 as f32 - self.pos.x,
+            SliderDirection::Vertical => self.pos.y + self.pos.h - point.1 as f32,
+        }
+    }
+
+    /// the thumb center's distance along the track
+    fn center(&self, portion: f32) -> f32 {
+        self.thumb_len() / 2. + portion * self.travel()
+    }
+
+    fn portion_at(&self, along: f32) -> f32 {
+        if self.travel() == 0. {
+            return 0.;
+        }
+        ((along - self.thumb_len() / 2.) / self.travel()).clamp(0., 1.)
+    }
+
+    fn thumb_rect(&self, portion: f32) -> FRect {
+        let len = self.thumb_len();
+        let start = self.center(portion) - len / 2.;
+        match self.direction {
+            SliderDirection::Horizontal => FRect {
+                x: self.pos.x + start,
+                y: self.pos.y,
+                w: len,
+                h: len,
+            },
+            SliderDirection::Vertical => FRect {
+                x: self.pos.x,
+                y: self.pos.y + self.pos.h - start - len,
+                w: len,
+                h: len,
+            },
+        }
+    }
+}
+
+/// what an event asks of a slider
+enum SliderInput {
+    /// mouse pressed at a distance along the track
+    Press(f32),
+    /// mouse moved while dragging
+    Drag(f32),
+    Release,
+    /// move by some key steps
+    Step(f32),
+    ToMin,
+    ToMax,
+}
+
+/// shared event handling for Slider and RangeSlider. consumes the events it
+/// uses
+fn slider_inputs(
+    event: &mut WidgetUpdateEvent,
+    track: Track,
+    dragging: bool,
+    hovered: &mut bool,
+    decrease: Option<&Hotkey>,
+    increase: Option<&Hotkey>,
+) -> Vec<SliderInput> {
+    let mut out = Vec::new();
+    let area: Option<TextureRect> = track.pos.into();
+    for e in event.events.iter_mut().filter(|e| e.is_some()) {
+        let ev = e.as_ref().unwrap();
+        let hotkey = |h: Option<&Hotkey>| h.and_then(|h| h.matches(ev));
+        if let Some(down) = hotkey(decrease) {
+            if down {
+                out.push(SliderInput::Step(-1.));
+            }
+            *e = None;
+            continue;
+        }
+        if let Some(down) = hotkey(increase) {
+            if down {
+                out.push(SliderInput::Step(1.));
+            }
+            *e = None;
+            continue;
+        }
+        match *ev {
+            Event::Mouse(mouse) => {
+                let over = area.is_some_and(|a| a.contains_point((mouse.x, mouse.y)))
+                    && event.clipping_rect.contains_point((mouse.x, mouse.y));
+                *hovered = over;
+                let along = track.along((mouse.x, mouse.y));
+                if mouse.changed && mouse.down && over {
+                    out.push(SliderInput::Press(along));
+                    *e = None;
+                } else if dragging || out.iter().any(|i| matches!(i, SliderInput::Press(_))) {
+                    if mouse.changed {
+                        out.push(SliderInput::Release);
+                    } else {
+                        out.push(SliderInput::Drag(along));
+                    }
+                    *e = None;
+                }
+            }
+            // arrow keys while the mouse is over the slider
+            Event::NavKey(nav) if *hovered || dragging => {
+                if nav.down {
+                    out.push(match nav.key {
+                        NavKey::Left | NavKey::Down => SliderInput::Step(-1.),
+                        NavKey::Right | NavKey::Up => SliderInput::Step(1.),
+                        NavKey::PageDown => SliderInput::Step(-10.),
+                        NavKey::PageUp => SliderInput::Step(10.),
+                        NavKey::Home => SliderInput::ToMin,
+                        NavKey::End => SliderInput::ToMax,
+                    });
+                }
+                *e = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// a thumb dragged along a track to choose a value between min and max
+pub struct Slider<'state> {
+    pub texture_path: PathBuf,
+    /// stretched over the whole widget
+    pub track: TextureRect,
+    pub thumb: TextureRect,
+    /// shown when hovered or dragged
+    pub thumb_faded: TextureRect,
+    pub direction: SliderDirection,
+
+    pub value: &'state Cell<f32>,
+    /// set during update if the value changed
+    pub changed: &'state Cell<bool>,
+    /// default: 0
+    pub min: f32,
+    /// default: 1. can be less than min
+    pub max: f32,
+    /// values snap to multiples of this from min. 0 for continuous
+    pub step: f32,
+
+    /// actions which move the value one step. the arrow keys also work while
+    /// hovered
+    pub decrease: Option<Hotkey<'state>>,
+    pub increase: Option<Hotkey<'state>>,
+
+    pub sizing: CustomSizing,
+
+    /// state stored for draw from update
+    draw_pos: FRect,
+    hovered: bool,
+    /// where the thumb was grabbed, relative to its center
+    grab: Option<f32>,
+}
+
+impl<'state> Slider<'state> {
+    pub fn new<'a, P: Into<PathLike<'a>>>(
+        texture_path: P,
+        track: TextureRect,
+        thumb: TextureRect,
+        thumb_faded: TextureRect,
+        value: &'state Cell<f32>,
+        changed: &'state Cell<bool>,
+    ) -> Self {
+        let texture_path: PathLike = texture_path.into();
+        let texture_path: PathBuf = texture_path.into();
+        Self {
+            texture_path,
+            track,
+            thumb,
+            thumb_faded,
+            direction: Default::default(),
+            value,
+            changed,
+            min: 0.,
+            max: 1.,
+            step: 0.,
+            decrease: None,
+            increase: None,
+            sizing: Default::default(),
+            draw_pos: Default::default(),
+            hovered: false,
+            grab: None,
+        }
+    }
+}
+
+impl<'state, T: crate::core::System> Widget<T> for Slider<'state> {
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_width_from_height(pref_h).map(Ok)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_height_from_width(pref_w).map(Ok)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+
+    fn update(
+        &mut self,
+        mut event: WidgetUpdateEvent,
+        _sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.changed.set(false);
+        self.draw_pos = event.position;
+        let track = Track {
+            pos: self.draw_pos,
+            direction: self.direction,
+        };
+
+        let old = self.value.get();
+        let mut value = snap(old, self.min, self.max, self.step);
+        let inputs = slider_inputs(
+            &mut event,
+            track,
+            self.grab.is_some(),
+            &mut self.hovered,
+            self.decrease.as_ref(),
+            self.increase.as_ref(),
+        );
+        for input in inputs {
+            let value_at = |along: f32| {
+                let p = track.portion_at(along);
+                snap(
+                    self.min + p * (self.max - self.min),
+                    self.min,
+                    self.max,
+                    self.step,
+                )
+            };
+            match input {
+                SliderInput::Press(along) => {
+                    let center = track.center(portion(value, self.min, self.max));
+                    if (along - center).abs() <= track.thumb_len() / 2. {
+                        // grabbed the thumb. keep it under the mouse
+                        self.grab = Some(along - center);
+                    } else {
+                        // clicked the track. jump there
+                        self.grab = Some(0.);
+                        value = value_at(along);
+                    }
+                }
+                SliderInput::Drag(along) => {
+                    if let Some(grab) = self.grab {
+                        value = value_at(along - grab);
+                    }
+                }
+                SliderInput::Release => self.grab = None,
+                SliderInput::Step(n) => {
+                    let step = key_step(self.min, self.max, self.step);
+                    let dir = if self.max >= self.min { 1. } else { -1. };
+                    value = snap(value + n * step * dir, self.min, self.max, self.step);
+                }
+                SliderInput::ToMin => value = self.min,
+                SliderInput::ToMax => value = self.max,
+            }
+        }
+
+        if value != old {
+            self.value.set(value);
+            self.changed.set(true);
+        }
+        Ok(Default::default())
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let pos: TextureRect = match self.draw_pos.into() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let track = Track {
+            pos: self.draw_pos,
+            direction: self.direction,
+        };
+        let mut txt = sys_interface.image(&self.texture_path)?;
+        txt.copy(self.track, pos)?;
+
+        let p = portion(self.value.get(), self.min, self.max);
+        let thumb: Option<TextureRect> = track.thumb_rect(p).into();
+        if let Some(thumb) = thumb {
+            let src = if self.hovered || self.grab.is_some() {
+                self.thumb_faded
+            } else {
+                self.thumb
+            };
+            txt.copy(src, thumb)?;
+        }
+        Ok(())
+    }
+}
+
+/// a slider with two thumbs, choosing a low and high value between min and
+/// max. the thumbs can't pass each other
+pub struct RangeSlider<'state> {
+    pub texture_path: PathBuf,
+    /// stretched over the whole widget
+    pub track: TextureRect,
+    pub thumb: TextureRect,
+    /// shown when hovered or dragged
+    pub thumb_faded: TextureRect,
+    pub direction: SliderDirection,
+
+    pub low: &'state Cell<f32>,
+    pub high: &'state Cell<f32>,
+    /// set during update if either value changed
+    pub changed: &'state Cell<bool>,
+    /// default: 0
+    pub min: f32,
+    /// default: 1. can be less than min
+    pub max: f32,
+    /// values snap to multiples of this from min. 0 for continuous
+    pub step: f32,
+
+    /// actions which move the last used thumb one step. the arrow keys also
+    /// work while hovered
+    pub decrease: Option<Hotkey<'state>>,
+    pub increase: Option<Hotkey<'state>>,
+
+    pub sizing: CustomSizing,
+
+    /// state stored for draw from update
+    draw_pos: FRect,
+    hovered: bool,
+    /// the last used thumb. false for low, true for high
+    active_high: bool,
+    /// where the active thumb was grabbed, relative to its center
+    grab: Option<f32>,
+}
+
+impl<'state> RangeSlider<'state> {
+    pub fn new<'a, P: Into<PathLike<'a>>>(
+        texture_path: P,
+        track: TextureRect,
+        thumb: TextureRect,
+        thumb_faded: TextureRect,
+        low: &'state Cell<f32>,
+        high: &'state Cell<f32>,
+        changed: &'state Cell<bool>,
+    ) -> Self {
+        let texture_path: PathLike = texture_path.into();
+        let texture_path: PathBuf = texture_path.into();
+        Self {
+            texture_path,
+            track,
+            thumb,
+            thumb_faded,
+            direction: Default::default(),
+            low,
+            high,
+            changed,
+            min: 0.,
+            max: 1.,
+            step: 0.,
+            decrease: None,
+            increase: None,
+            sizing: Default::default(),
+            draw_pos: Default::default(),
+            hovered: false,
+            active_high: false,
+            grab: None,
+        }
+    }
+
+    /// keep low and high in range and in order, moving the one not active
+    fn order(&self, low: f32, high: f32) -> (f32, f32) {
+        let low = snap(low, self.min, self.max, self.step);
+        let high = snap(high, self.min, self.max, self.step);
+        let in_order = portion(low, self.min, self.max) <= portion(high, self.min, self.max);
+        match (in_order, self.active_high) {
+            (true, _) => (low, high),
+            (false, true) => (high, high),
+            (false, false) => (low, low),
+        }
+    }
+}
+
+impl<'state, T: crate::core::System> Widget<T> for RangeSlider<'state> {
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_width_from_height(pref_h).map(Ok)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_height_from_width(pref_w).map(Ok)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+
+    fn update(
+        &mut self,
+        mut event: WidgetUpdateEvent,
+        _sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.changed.set(false);
+        self.draw_pos = event.position;
+        let track = Track {
+            pos: self.draw_pos,
+            direction: self.direction,
+        };
+
+        let old = (self.low.get(), self.high.get());
+        let (mut low, mut high) = self.order(old.0, old.1);
+        let inputs = slider_inputs(
+            &mut event,
+            track,
+            self.grab.is_some(),
+            &mut self.hovered,
+            self.decrease.as_ref(),
+            self.increase.as_ref(),
+        );
+        for input in inputs {
+            let value_at = |along: f32| {
+                let p = track.portion_at(along);
+                self.min + p * (self.max - self.min)
+            };
+            let low_center = track.center(portion(low, self.min, self.max));
+            let high_center = track.center(portion(high, self.min, self.max));
+            let active = if self.active_high { high } else { low };
+            let new_active = match input {
+                SliderInput::Press(along) => {
+                    // the nearest thumb. if they overlap, the side clicked
+                    let (to_low, to_high) =
+                        ((along - low_center).abs(), (along - high_center).abs());
+                    self.active_high = if to_low == to_high {
+                        along > high_center
+                    } else {
+                        to_high < to_low
+                    };
+                    let center = if self.active_high {
+                        high_center
+                    } else {
+                        low_center
+                    };
+                    if (along - center).abs() <= track.thumb_len() / 2. {
+                        self.grab = Some(along - center);
+                        continue;
+                    }
+                    self.grab = Some(0.);
+                    value_at(along)
+                }
+                SliderInput::Drag(along) => match self.grab {
+                    Some(grab) => value_at(along - grab),
+                    None => continue,
+                },
+                SliderInput::Release => {
+                    self.grab = None;
+                    continue;
+                }
+                SliderInput::Step(n) => {
+                    let step = key_step(self.min, self.max, self.step);
+                    let dir = if self.max >= self.min { 1. } else { -1. };
+                    active + n * step * dir
+                }
+                SliderInput::ToMin => self.min,
+                SliderInput::ToMax => self.max,
+            };
+            (low, high) = if self.active_high {
+                self.order(low, new_active)
+            } else {
+                self.order(new_active, high)
+            };
+        }
+
+        if (low, high) != old {
+            self.low.set(low);
+            self.high.set(high);
+            self.changed.set(true);
+        }
+        Ok(Default::default())
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let pos: TextureRect = match self.draw_pos.into() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let track = Track {
+            pos: self.draw_pos,
+            direction: self.direction,
+        };
+        let mut txt = sys_interface.image(&self.texture_path)?;
+        txt.copy(self.track, pos)?;
+
+        // the active thumb is drawn on top
+        let thumbs = if self.active_high {
+            [(self.low.get(), false), (self.high.get(), true)]
+        } else {
+            [(self.high.get(), true), (self.low.get(), false)]
+        };
+        for (value, is_high) in thumbs {
+            let p = portion(value, self.min, self.max);
+            let thumb: Option<TextureRect> = track.thumb_rect(p).into();
+            let thumb = match thumb {
+                Some(v) => v,
+                None => continue,
+            };
+            let faded = is_high == self.active_high && (self.hovered || self.grab.is_some());
+            let src = if faded { self.thumb_faded } else { self.thumb };
+            txt.copy(src, thumb)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap() {
+        assert_eq!(snap(0.26, 0., 1., 0.25), 0.25);
+        assert_eq!(snap(7., 0., 10., 3.), 6.);
+        // the last step doesn't fit evenly
+        assert_eq!(snap(9.9, 0., 10., 3.), 10.);
+        assert_eq!(snap(-5., 0., 10., 0.), 0.);
+        // reversed range
+        assert_eq!(snap(3., 10., 0., 4.), 2.);
+    }
+
+    #[test]
+    fn test_track() {
+        let track = Track {
+            pos: FRect {
+                x: 10.,
+                y: 0.,
+                w: 110.,
+                h: 10.,
+            },
+            direction: SliderDirection::Horizontal,
+        };
+        assert_eq!(track.portion_at(track.along((15, 5))), 0.);
+        assert_eq!(track.portion_at(track.along((65, 5))), 0.5);
+        assert_eq!(track.thumb_rect(1.).x, 110.);
+
+        let track = Track {
+            direction: SliderDirection::Vertical,
+            pos: FRect {
+                x: 0.,
+                y: 0.,
+                w: 10.,
+                h: 110.,
+            },
+        };
+        assert_eq!(track.thumb_rect(0.).y, 100.);
+        assert_eq!(track.portion_at(track.along((5, 5))), 1.);
+    }
+}
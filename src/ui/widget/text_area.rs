@@ -0,0 +1,563 @@
+use std::{cell::Cell, num::NonZeroU16, ops::Range, rc::Rc, time::Duration};
+
+use crate::{
+    core::{
+        clipping_rect::ClippingRect,
+        color::Color,
+        event::{Event, NavKey, NavKeyEvent},
+        texture_rect::{TextureRect, TextureRectF, TextureSourceF},
+        NonEmptyStr, System, TextureHandle,
+    },
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+            rust::CellRefOrCell,
+            text_draw::{
+                blink_on, blink_wake, caret_width, char_width, fill_rect, line_height, wrap_text,
+            },
+            text_edit::{line_of, EditAction, EditRules, FocusGroup, History, TextEdit},
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::CustomSizing, Widget, WidgetUpdateEvent};
+
+/// state of a TextArea which should persist between frames
+#[derive(Debug, Clone, Default)]
+pub struct TextAreaState {
+    pub edit: TextEdit,
+    /// receives key and text events
+    pub focused: bool,
+    /// from the focus group, while focused
+    focus_token: Option<u64>,
+    pub history: History,
+    dragging: bool,
+    /// the x the caret keeps to while moving up and down
+    goal_x: Option<f32>,
+    /// time since the caret last moved
+    blink: Duration,
+}
+
+/// multiple lines of editable text, wrapped to the widget's width. click to
+/// focus. ctrl+z to undo, ctrl+y or ctrl+shift+z to redo
+///
+/// like MultiLineLabel, the height comes from the width and text. place it in
+/// a Scroller for long text, and share the scroller's scroll_y so the caret is
+/// kept in view
+pub struct TextArea<'state> {
+    pub text: CellRefOrCell<'state, String>,
+    /// shown while the text is empty
+    pub placeholder: String,
+    /// multi_line is set by default
+    pub rules: EditRules,
+    pub point_size: NonZeroU16,
+
+    pub color: Color,
+    pub placeholder_color: Color,
+    pub selection_color: Color,
+    pub caret_color: Color,
+
+    /// the scroll of a Scroller containing this. it's moved to keep the caret
+    /// in view
+    pub scroll_y: Option<Rc<Cell<i32>>>,
+
+    /// preferred_ratio_exceed_parent is set by default, so the height can
+    /// grow past a containing Scroller
+    pub sizing: CustomSizing,
+
+    /// shared with other text widgets, so focusing one unfocuses the others
+    pub focus_group: Option<&'state FocusGroup>,
+
+    pub state: CellRefOrCell<'state, TextAreaState>,
+
+    /// state stored from update for draw
+    draw_pos: FRect,
+    clip: ClippingRect,
+    lines: Vec<Range<usize>>,
+    line_h: f32,
+}
+
+impl<'state> TextArea<'state> {
+    pub fn new(text: CellRefOrCell<'state, String>, point_size: NonZeroU16) -> Self {
+        let white = Color {
+            r: 0xFF,
+            g: 0xFF,
+            b: 0xFF,
+            a: 0xFF,
+        };
+        Self {
+            text,
+            placeholder: String::new(),
+            rules: EditRules {
+                multi_line: true,
+                ..Default::default()
+            },
+            point_size,
+            color: white,
+            placeholder_color: Color { a: 0x80, ..white },
+            selection_color: Color {
+                r: 0x33,
+                g: 0x66,
+                b: 0xCC,
+                a: 0xFF,
+            },
+            caret_color: white,
+            scroll_y: None,
+            sizing: CustomSizing {
+                preferred_ratio_exceed_parent: true,
+                ..Default::default()
+            },
+            focus_group: None,
+            state: CellRefOrCell::Cell(Default::default()),
+            draw_pos: Default::default(),
+            clip: ClippingRect::None,
+            lines: Vec::new(),
+            line_h: 0.,
+        }
+    }
+
+    fn char_width<T: System>(&self, c: char, sys: &mut T) -> Result<f32, String> {
+        char_width(sys, c, self.color, self.point_size)
+    }
+
+    /// width of some text on one line, rendered whole as draw_line() does. a
+    /// line's prefix is measured with this so kerning doesn't cause drift
+    fn width<T: System>(&self, s: &str, sys: &mut T) -> Result<f32, String> {
+        let s: NonEmptyStr = match s.try_into() {
+            Ok(v) => v,
+            Err(()) => return Ok(0.),
+        };
+        let size = sys.text(s, self.color, self.point_size, None)?.size()?;
+        Ok(size.0.get() as f32)
+    }
+
+    fn line_height<T: System>(&self, sys: &mut T) -> Result<f32, String> {
+        line_height(sys, self.color, self.point_size)
+    }
+
+    fn layout<T: System>(
+        &self,
+        text: &str,
+        width: f32,
+        sys: &mut T,
+    ) -> Result<Vec<Range<usize>>, String> {
+        wrap_text(sys, text, width, self.color, self.point_size)
+    }
+
+    /// the caret position at the end of a line. at a wrap, that's before the
+    /// last char, since after it is the start of the next line
+    fn line_end(&self, text: &str, line: usize) -> usize {
+        let range = self.lines[line].clone();
+        let wrapped = range.end < text.len() && !text[range.end..].starts_with('\n');
+        if wrapped && !range.is_empty() {
+            text[range].char_indices().next_back().unwrap().0 + self.lines[line].start
+        } else {
+            range.end
+        }
+    }
+
+    /// the caret position in a line nearest to x, relative to the widget
+    fn index_at<T: System>(
+        &self,
+        text: &str,
+        line: usize,
+        x: f32,
+        sys: &mut T,
+    ) -> Result<usize, String> {
+        let end = self.line_end(text, line);
+        let start = self.lines[line].start;
+        let boundaries: Vec<usize> = text[start..end]
+            .char_indices()
+            .map(|(i, _)| start + i)
+            .chain(std::iter::once(end))
+            .collect();
+        // widths increase with the boundary. find the first at or past x
+        let (mut lo, mut hi) = (0, boundaries.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.width(&text[start..boundaries[mid]], sys)? < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == 0 {
+            return Ok(start);
+        }
+        let after = self.width(&text[start..boundaries[lo]], sys)?;
+        let before = self.width(&text[start..boundaries[lo - 1]], sys)?;
+        Ok(if x - before < after - x {
+            boundaries[lo - 1]
+        } else {
+            boundaries[lo]
+        })
+    }
+
+    /// the caret position nearest to a point relative to the widget
+    fn hit<T: System>(&self, text: &str, x: f32, y: f32, sys: &mut T) -> Result<usize, String> {
+        let line = (y / self.line_h).floor().max(0.) as usize;
+        let line = line.min(self.lines.len() - 1);
+        self.index_at(text, line, x, sys)
+    }
+
+    /// caret movement which depends on the layout. returns true if handled
+    fn navigate<T: System>(
+        &self,
+        state: &mut TextAreaState,
+        text: &str,
+        e: &NavKeyEvent,
+        sys: &mut T,
+    ) -> Result<bool, String> {
+        let select = e.mods.shift;
+        let caret = state.edit.caret;
+        let line = line_of(&self.lines, caret);
+        let visible_h = match self.clip {
+            ClippingRect::Some(r) => r.h.get() as f32,
+            _ => self.draw_pos.h,
+        };
+        let page = ((visible_h / self.line_h).floor() as usize).max(1);
+        let target = match e.key {
+            NavKey::Home | NavKey::End => {
+                let i = match (e.key, e.mods.ctrl) {
+                    (NavKey::Home, true) => 0,
+                    (NavKey::Home, false) => self.lines[line].start,
+                    (_, true) => text.len(),
+                    (_, false) => self.line_end(text, line),
+                };
+                state.edit.move_to(i, select);
+                state.goal_x = None;
+                return Ok(true);
+            }
+            NavKey::Up => line.checked_sub(1),
+            NavKey::Down => Some(line + 1).filter(|l| *l < self.lines.len()),
+            NavKey::PageUp => Some(line.saturating_sub(page)),
+            NavKey::PageDown => Some((line + page).min(self.lines.len() - 1)),
+            _ => return Ok(false),
+        };
+        let x = match state.goal_x {
+            Some(v) => v,
+            None => self.width(&text[self.lines[line].start..caret], sys)?,
+        };
+        let i = match target {
+            Some(l) => self.index_at(text, l, x, sys)?,
+            // past the first or last line
+            None if e.key == NavKey::Up => 0,
+            None => text.len(),
+        };
+        state.edit.move_to(i, select);
+        state.goal_x = Some(x);
+        Ok(true)
+    }
+
+    /// draw one line of text at its rendered size
+    fn draw_line<T: System>(
+        &self,
+        s: &str,
+        color: Color,
+        x: f32,
+        y: f32,
+        sys: &mut T,
+    ) -> Result<(), String> {
+        let s: NonEmptyStr = match s.try_into() {
+            Ok(v) => v,
+            Err(()) => return Ok(()),
+        };
+        let mut texture = sys.text(s, color, self.point_size, None)?;
+        let size = texture.size()?;
+        let dst = match TextureRectF::new(x, y, size.0.get() as f32, size.1.get() as f32) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let src: TextureSourceF = Default::default();
+        texture.copy_f(src, dst)
+    }
+}
+
+impl<'state, T: System> Widget<T> for TextArea<'state> {
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        Some((|| {
+            let text = self.text.scope_take();
+            let lines = self.layout(&text, pref_w, sys_interface)?;
+            Ok(lines.len() as f32 * self.line_height(sys_interface)?)
+        })())
+    }
+
+    fn update(
+        &mut self,
+        event: WidgetUpdateEvent,
+        sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.draw_pos = event.position;
+        let pos: Option<TextureRect> = self.draw_pos.into();
+        self.clip = event.clipping_rect.intersect_area(pos);
+
+        let mut state_guard = self.state.scope_take();
+        let state = &mut *state_guard;
+        let mut text = self.text.scope_take();
+        state.edit.clamp(&text);
+        // another widget in the group might have taken focus
+        if let Some(group) = self.focus_group {
+            group.update(&mut state.focused, &mut state.focus_token);
+        }
+        state.dragging &= state.focused;
+        self.line_h = self.line_height(sys_interface)?;
+        self.lines = self.layout(&text, self.draw_pos.w, sys_interface)?;
+
+        let mut moved = false;
+        let mut changed = false;
+        for e in event.events.iter_mut().filter(|e| e.is_some()) {
+            if let Some(Event::Mouse(mouse)) = e.as_ref() {
+                let over = pos.is_some_and(|p| p.contains_point((mouse.x, mouse.y)))
+                    && event.clipping_rect.contains_point((mouse.x, mouse.y));
+                let x = mouse.x as f32 - self.draw_pos.x;
+                let y = mouse.y as f32 - self.draw_pos.y;
+                if mouse.changed && mouse.down {
+                    // clicking elsewhere unfocuses
+                    state.focused = over;
+                    state.dragging = over;
+                    if over {
+                        let i = self.hit(&text, x, y, sys_interface)?;
+                        state.edit.move_to(i, false);
+                        state.goal_x = None;
+                        state.history.end_typing();
+                        moved = true;
+                        *e = None;
+                    }
+                } else if state.dragging {
+                    if mouse.changed {
+                        state.dragging = false;
+                    } else {
+                        let i = self.hit(&text, x, y, sys_interface)?;
+                        state.edit.move_to(i, true);
+                        moved = true;
+                    }
+                    *e = None;
+                }
+                continue;
+            }
+            if !state.focused {
+                continue;
+            }
+            let ev = e.as_ref().unwrap();
+
+            if let Event::Key(key) = ev {
+                if key.down && key.mods.shortcut() {
+                    let history_changed = match key.key.to_ascii_lowercase() {
+                        b'z' if key.mods.shift => {
+                            Some(state.history.redo(&mut text, &mut state.edit))
+                        }
+                        b'z' => Some(state.history.undo(&mut text, &mut state.edit)),
+                        b'y' => Some(state.history.redo(&mut text, &mut state.edit)),
+                        _ => None,
+                    };
+                    if let Some(c) = history_changed {
+                        changed |= c;
+                        moved = true;
+                        state.goal_x = None;
+                        self.lines = self.layout(&text, self.draw_pos.w, sys_interface)?;
+                        *e = None;
+                        continue;
+                    }
+                }
+            }
+
+            if let Event::NavKey(nav) = ev {
+                if nav.down && self.navigate(state, &text, nav, sys_interface)? {
+                    state.history.end_typing();
+                    moved = true;
+                    *e = None;
+                    continue;
+                }
+            }
+
+            let before = (text.clone(), state.edit);
+            match state
+                .edit
+                .apply_event(&mut text, ev, &self.rules, sys_interface)?
+            {
+                EditAction::Ignored => continue,
+                EditAction::Handled | EditAction::Submit => {
+                    if state.edit != before.1 {
+                        state.goal_x = None;
+                        state.history.end_typing();
+                        moved = true;
+                    }
+                }
+                EditAction::Changed => {
+                    // words are undone together, spaces and newlines apart
+                    let typing = matches!(ev, Event::Text(s) if !s.contains(char::is_whitespace));
+                    state.history.record(before.0, before.1, typing);
+                    state.goal_x = None;
+                    changed = true;
+                    moved = true;
+                    self.lines = self.layout(&text, self.draw_pos.w, sys_interface)?;
+                }
+            }
+            *e = None;
+        }
+
+        if let Some(group) = self.focus_group {
+            group.update(&mut state.focused, &mut state.focus_token);
+        }
+
+        if moved {
+            state.blink = Duration::ZERO;
+        } else {
+            state.blink += event.dt;
+        }
+
+        // scroll the containing Scroller to keep the caret in view
+        let mut scrolled = false;
+        if let (true, Some(scroll_y), ClippingRect::Some(visible)) =
+            (moved, self.scroll_y.as_ref(), event.clipping_rect)
+        {
+            let line = line_of(&self.lines, state.edit.caret) as f32;
+            let top = (self.draw_pos.y + line * self.line_h).floor() as i32;
+            let bottom = (self.draw_pos.y + (line + 1.) * self.line_h).ceil() as i32;
+            let visible_bottom = visible.y + visible.h.get() as i32;
+            if top < visible.y {
+                scroll_y.set(scroll_y.get() + visible.y - top);
+                scrolled = true;
+            } else if bottom > visible_bottom {
+                scroll_y.set(scroll_y.get() - (bottom - visible_bottom));
+                scrolled = true;
+            }
+        }
+
+        let focused = state.focused;
+        let blink = state.blink;
+        drop(text);
+        drop(state_guard);
+
+        if changed || scrolled {
+            // the layout and scroll changed. place everything again first
+            return Ok(FrameTransiency::NextFrameNow);
+        }
+        Ok(if focused {
+            blink_wake(blink)
+        } else {
+            FrameTransiency::None
+        })
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let pos = self.draw_pos;
+        if pos.w <= 0. || pos.h <= 0. || self.line_h <= 0. {
+            return Ok(());
+        }
+
+        let prev_clip = sys_interface.get_clip();
+        sys_interface.clip(self.clip);
+        let ret = (|| {
+            let state = self.state.scope_take();
+            let text = self.text.scope_take();
+            if text.is_empty() {
+                self.draw_line(
+                    &self.placeholder,
+                    self.placeholder_color,
+                    pos.x,
+                    pos.y,
+                    sys_interface,
+                )?;
+            }
+
+            // only the lines in view
+            let (view_top, view_bottom) = match self.clip {
+                ClippingRect::Some(r) => (r.y as f32, (r.y + r.h.get() as i32) as f32),
+                ClippingRect::Zero => return Ok(()),
+                ClippingRect::None => (pos.y, pos.y + pos.h),
+            };
+            let first = ((view_top - pos.y) / self.line_h).floor().max(0.) as usize;
+            let last = (((view_bottom - pos.y) / self.line_h).ceil().max(0.) as usize)
+                .min(self.lines.len());
+
+            let selection = state.edit.selection();
+            for (l, range) in self.lines.iter().enumerate().take(last).skip(first) {
+                // lines may be stale if the text changed since update
+                if range.end > text.len() {
+                    break;
+                }
+                let y = pos.y + l as f32 * self.line_h;
+
+                if state.focused
+                    && state.edit.has_selection()
+                    && selection.start <= range.end
+                    && selection.end >= range.start
+                {
+                    let start = selection.start.max(range.start);
+                    let end = selection.end.min(range.end);
+                    let x0 = self.width(&text[range.start..start], sys_interface)?;
+                    let mut x1 = self.width(&text[range.start..end], sys_interface)?;
+                    if selection.end > range.end && text[range.end..].starts_with('\n') {
+                        // show that the newline is selected
+                        x1 += self.char_width(' ', sys_interface)?;
+                    }
+                    let area = FRect {
+                        x: pos.x + x0,
+                        y,
+                        w: x1 - x0,
+                        h: self.line_h,
+                    };
+                    fill_rect(sys_interface, self.selection_color, area)?;
+                }
+
+                self.draw_line(&text[range.clone()], self.color, pos.x, y, sys_interface)?;
+            }
+
+            if state.focused && blink_on(state.blink) && !self.lines.is_empty() {
+                let caret = state.edit.caret.min(text.len());
+                let l = line_of(&self.lines, caret);
+                let start = self.lines[l].start.min(caret);
+                let x = self.width(&text[start..caret], sys_interface)?;
+                let area = FRect {
+                    x: pos.x + x,
+                    y: pos.y + l as f32 * self.line_h,
+                    w: caret_width(self.line_h),
+                    h: self.line_h,
+                };
+                fill_rect(sys_interface, self.caret_color, area)?;
+            }
+            Ok(())
+        })();
+        sys_interface.clip(prev_clip);
+        ret
+    }
+}
@@ -0,0 +1,370 @@
+use std::time::Duration;
+
+use crate::{
+    core::{
+        clipping_rect::ClippingRect, color::Color, event::Event, texture_rect::TextureRect, System,
+    },
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+            rust::CellRefOrCell,
+            text_draw::{blink_on, blink_wake, caret_width, draw_text, fill_rect, text_width},
+            text_edit::{EditAction, EditRules, FocusGroup, TextEdit},
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::CustomSizing, Widget, WidgetUpdateEvent};
+
+/// called with the text when enter is pressed
+pub type OnSubmit<'state, T> =
+    Box<dyn FnMut(&str, &mut T) -> Result<FrameTransiency, String> + 'state>;
+
+/// state of a TextInput which should persist between frames
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextInputState {
+    pub edit: TextEdit,
+    /// receives key and text events
+    pub focused: bool,
+    /// from the focus group, while focused
+    focus_token: Option<u64>,
+    dragging: bool,
+    /// how far the text is moved left to keep the caret visible
+    scroll_x: f32,
+    /// time since the caret last moved
+    blink: Duration,
+}
+
+/// a single line of editable text. click to focus. the text is rendered at the
+/// widget's height, and scrolls horizontally to keep the caret in view
+pub struct TextInput<'state, T: System> {
+    pub text: CellRefOrCell<'state, String>,
+    /// shown while the text is empty
+    pub placeholder: String,
+    pub rules: EditRules,
+    /// each char is shown as this instead, and copying is disabled
+    pub password: Option<char>,
+    pub on_submit: Option<OnSubmit<'state, T>>,
+
+    pub color: Color,
+    pub placeholder_color: Color,
+    pub selection_color: Color,
+    pub caret_color: Color,
+
+    pub sizing: CustomSizing,
+
+    /// shared with other text widgets, so focusing one unfocuses the others
+    pub focus_group: Option<&'state FocusGroup>,
+
+    pub state: CellRefOrCell<'state, TextInputState>,
+
+    /// state stored from update for draw
+    draw_pos: FRect,
+    clip: ClippingRect,
+    /// relative to draw_pos.x, after scrolling
+    caret_x: f32,
+    selection_x: Option<(f32, f32)>,
+}
+
+impl<'state, T: System> TextInput<'state, T> {
+    pub fn new(text: CellRefOrCell<'state, String>) -> Self {
+        let white = Color {
+            r: 0xFF,
+            g: 0xFF,
+            b: 0xFF,
+            a: 0xFF,
+        };
+        Self {
+            text,
+            placeholder: String::new(),
+            rules: Default::default(),
+            password: None,
+            on_submit: None,
+            color: white,
+            placeholder_color: Color { a: 0x80, ..white },
+            selection_color: Color {
+                r: 0x33,
+                g: 0x66,
+                b: 0xCC,
+                a: 0xFF,
+            },
+            caret_color: white,
+            sizing: Default::default(),
+            focus_group: None,
+            state: CellRefOrCell::Cell(Default::default()),
+            draw_pos: Default::default(),
+            clip: ClippingRect::None,
+            caret_x: 0.,
+            selection_x: None,
+        }
+    }
+
+    /// the text as drawn
+    fn shown(&self, text: &str) -> String {
+        match self.password {
+            Some(c) => std::iter::repeat_n(c, text.chars().count()).collect(),
+            None => text.to_owned(),
+        }
+    }
+
+    /// width of text[..i] as drawn
+    fn width_to(&self, text: &str, i: usize, sys: &mut T) -> Result<f32, String> {
+        let shown = self.shown(&text[..i]);
+        text_width(sys, &shown, self.color, self.draw_pos.h)
+    }
+
+    /// the char boundary in text nearest to x, relative to the text's start
+    fn hit(&self, text: &str, x: f32, sys: &mut T) -> Result<usize, String> {
+        let boundaries: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+        // widths increase with i. find the first boundary at or past x
+        let (mut lo, mut hi) = (0, boundaries.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.width_to(text, boundaries[mid], sys)? < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == 0 {
+            return Ok(0);
+        }
+        let after = self.width_to(text, boundaries[lo], sys)?;
+        let before = self.width_to(text, boundaries[lo - 1], sys)?;
+        Ok(if x - before < after - x {
+            boundaries[lo - 1]
+        } else {
+            boundaries[lo]
+        })
+    }
+}
+
+impl<'state, T: System> Widget<T> for TextInput<'state, T> {
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_width_from_height(pref_h).map(Ok)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_height_from_width(pref_w).map(Ok)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+
+    fn update(
+        &mut self,
+        event: WidgetUpdateEvent,
+        sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.draw_pos = event.position;
+        let pos: Option<TextureRect> = self.draw_pos.into();
+        self.clip = event.clipping_rect.intersect_area(pos);
+
+        let rules = EditRules {
+            conceal: self.rules.conceal || self.password.is_some(),
+            ..self.rules
+        };
+        let mut state = self.state.get();
+        let mut text = self.text.scope_take();
+        state.edit.clamp(&text);
+        // another widget in the group might have taken focus
+        if let Some(group) = self.focus_group {
+            group.update(&mut state.focused, &mut state.focus_token);
+        }
+        state.dragging &= state.focused;
+
+        let mut moved = false;
+        let mut submit = false;
+        for e in event.events.iter_mut().filter(|e| e.is_some()) {
+            if let Some(Event::Mouse(mouse)) = e.as_ref() {
+                let over = pos.is_some_and(|p| p.contains_point((mouse.x, mouse.y)))
+                    && event.clipping_rect.contains_point((mouse.x, mouse.y));
+                let x = mouse.x as f32 - self.draw_pos.x + state.scroll_x;
+                if mouse.changed && mouse.down {
+                    // clicking elsewhere unfocuses
+                    state.focused = over;
+                    state.dragging = over;
+                    if over {
+                        let i = self.hit(&text, x, sys_interface)?;
+                        state.edit.move_to(i, false);
+                        moved = true;
+                        *e = None;
+                    }
+                } else if state.dragging {
+                    if mouse.changed {
+                        state.dragging = false;
+                    } else {
+                        let i = self.hit(&text, x, sys_interface)?;
+                        state.edit.move_to(i, true);
+                        moved = true;
+                    }
+                    *e = None;
+                }
+                continue;
+            }
+            if !state.focused {
+                continue;
+            }
+            let action =
+                state
+                    .edit
+                    .apply_event(&mut text, e.as_ref().unwrap(), &rules, sys_interface)?;
+            match action {
+                EditAction::Ignored => continue,
+                EditAction::Handled | EditAction::Changed => moved = true,
+                EditAction::Submit => submit = true,
+            }
+            *e = None;
+        }
+
+        if let Some(group) = self.focus_group {
+            group.update(&mut state.focused, &mut state.focus_token);
+        }
+
+        if moved {
+            state.blink = Duration::ZERO;
+        } else {
+            state.blink += event.dt;
+        }
+
+        // keep the caret in view
+        let h = self.draw_pos.h;
+        let caret_w = caret_width(h);
+        let caret_x = self.width_to(&text, state.edit.caret, sys_interface)?;
+        let total = self.width_to(&text, text.len(), sys_interface)?;
+        if caret_x + caret_w - state.scroll_x > self.draw_pos.w {
+            state.scroll_x = caret_x + caret_w - self.draw_pos.w;
+        }
+        if caret_x < state.scroll_x {
+            state.scroll_x = caret_x;
+        }
+        state.scroll_x = state
+            .scroll_x
+            .min(total + caret_w - self.draw_pos.w)
+            .max(0.);
+
+        self.caret_x = caret_x - state.scroll_x;
+        self.selection_x = if state.edit.has_selection() {
+            let range = state.edit.selection();
+            let start = self.width_to(&text, range.start, sys_interface)?;
+            let end = self.width_to(&text, range.end, sys_interface)?;
+            Some((start - state.scroll_x, end - state.scroll_x))
+        } else {
+            None
+        };
+        let submitted = if submit { Some(text.clone()) } else { None };
+        drop(text);
+        self.state.set(state);
+
+        let mut ret = if state.focused {
+            blink_wake(state.blink)
+        } else {
+            FrameTransiency::None
+        };
+        if let (Some(text), Some(on_submit)) = (submitted, self.on_submit.as_mut()) {
+            ret |= on_submit(&text, sys_interface)?;
+        }
+        Ok(ret)
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let pos = self.draw_pos;
+        if pos.w <= 0. || pos.h <= 0. {
+            return Ok(());
+        }
+        let state = self.state.get();
+
+        let prev_clip = sys_interface.get_clip();
+        sys_interface.clip(self.clip);
+        let ret = (|| {
+            if state.focused {
+                if let Some((start, end)) = self.selection_x {
+                    let area = FRect {
+                        x: pos.x + start,
+                        y: pos.y,
+                        w: end - start,
+                        h: pos.h,
+                    };
+                    fill_rect(sys_interface, self.selection_color, area)?;
+                }
+            }
+
+            let text = self.text.scope_take();
+            if text.is_empty() {
+                draw_text(
+                    sys_interface,
+                    &self.placeholder,
+                    self.placeholder_color,
+                    pos.x,
+                    pos.y,
+                    pos.h,
+                )?;
+            } else {
+                draw_text(
+                    sys_interface,
+                    &self.shown(&text),
+                    self.color,
+                    pos.x - state.scroll_x,
+                    pos.y,
+                    pos.h,
+                )?;
+            }
+
+            if state.focused && blink_on(state.blink) {
+                let area = FRect {
+                    x: pos.x + self.caret_x,
+                    y: pos.y,
+                    w: caret_width(pos.h),
+                    h: pos.h,
+                };
+                fill_rect(sys_interface, self.caret_color, area)?;
+            }
+            Ok(())
+        })();
+        sys_interface.clip(prev_clip);
+        ret
+    }
+}
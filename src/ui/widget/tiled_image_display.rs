@@ -67,7 +67,7 @@ impl<T: crate::core::System> Widget<T> for TiledImageDisplay {
             let pos: Option<TextureRect> = self.background_draw_pos.into();
             if let Some(pos) = pos {
                 for e in event.events.iter_mut().filter(|e| e.is_some()) {
-                    match e.unwrap() {
+                    match e.as_ref().unwrap() {
                         crate::core::event::Event::Mouse(m) => {
                             if pos.contains_point((m.x, m.y)) {
                                 *e = None;
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+
+use crate::{
+    core::{
+        clipping_rect::ClippingRect, texture_rect::TextureRect, tilemap::Layer, tilemap::Tilemap,
+    },
+    ui::{
+        util::{
+            length::{MaxLen, MaxLenFailPolicy, MinLen, MinLenFailPolicy, PreferredPortion},
+            rect::FRect,
+        },
+        widget::FrameTransiency,
+    },
+};
+
+use super::{sizing::CustomSizing, Widget, WidgetUpdateEvent};
+
+/// displays a tilemap, clipped to the widget's position
+pub struct TilemapView<'state> {
+    pub map: &'state Tilemap,
+    /// the map pixel shown at the top left
+    pub offset: &'state Cell<(i32, i32)>,
+    /// names of the tile layers to draw. all visible tile layers if None
+    pub layers: Option<Vec<String>>,
+    pub sizing: CustomSizing,
+
+    /// default: true. consume mouse events over position
+    pub consume: bool,
+
+    /// state stored from update for draw
+    draw_pos: FRect,
+    clip: ClippingRect,
+}
+
+impl<'state> TilemapView<'state> {
+    pub fn new(map: &'state Tilemap, offset: &'state Cell<(i32, i32)>) -> Self {
+        Self {
+            map,
+            offset,
+            layers: None,
+            sizing: Default::default(),
+            consume: true,
+            draw_pos: Default::default(),
+            clip: ClippingRect::None,
+        }
+    }
+}
+
+impl<'state, T: crate::core::System> Widget<T> for TilemapView<'state> {
+    fn update(
+        &mut self,
+        event: WidgetUpdateEvent,
+        _sys_interface: &mut T,
+    ) -> Result<FrameTransiency, String> {
+        self.draw_pos = event.position;
+        let pos: Option<TextureRect> = self.draw_pos.into();
+        self.clip = event.clipping_rect.intersect_area(pos);
+        if self.consume {
+            // consume mouse events over position
+            if let Some(pos) = pos {
+                for e in event.events.iter_mut().filter(|e| e.is_some()) {
+                    let over = match e.as_ref().unwrap() {
+                        crate::core::event::Event::Mouse(m) => pos.contains_point((m.x, m.y)),
+                        crate::core::event::Event::MouseWheel(m) => pos.contains_point((m.x, m.y)),
+                        _ => false,
+                    };
+                    if over {
+                        *e = None;
+                    }
+                }
+            }
+        }
+        Ok(Default::default())
+    }
+
+    fn draw(&self, sys_interface: &mut T) -> Result<(), String> {
+        let pos: TextureRect = match self.draw_pos.into() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let previous_clipping_rect = sys_interface.get_clip();
+        sys_interface.clip(self.clip);
+        let r = match &self.layers {
+            None => self.map.draw(sys_interface, pos, self.offset.get()),
+            Some(names) => names
+                .iter()
+                .try_for_each(|name| match self.map.layer(name) {
+                    Some(Layer::Tiles(layer)) => {
+                        self.map
+                            .draw_layer(sys_interface, layer, pos, self.offset.get())
+                    }
+                    _ => Ok(()),
+                }),
+        };
+        // reset clipping rect for following elements that will be drawn after
+        sys_interface.clip(previous_clipping_rect);
+        r
+    }
+
+    fn min(&self, _sys_interface: &mut T) -> Result<(MinLen, MinLen), String> {
+        Ok((self.sizing.min_w, self.sizing.min_h))
+    }
+
+    fn min_w_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_w_fail_policy
+    }
+
+    fn min_h_fail_policy(&self) -> MinLenFailPolicy {
+        self.sizing.min_h_fail_policy
+    }
+
+    fn max(&self, _sys_interface: &mut T) -> Result<(MaxLen, MaxLen), String> {
+        Ok((self.sizing.max_w, self.sizing.max_h))
+    }
+
+    fn max_w_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_w_fail_policy
+    }
+
+    fn max_h_fail_policy(&self) -> MaxLenFailPolicy {
+        self.sizing.max_h_fail_policy
+    }
+
+    fn preferred_portion(&self) -> (PreferredPortion, PreferredPortion) {
+        (self.sizing.preferred_w, self.sizing.preferred_h)
+    }
+
+    fn preferred_width_from_height(
+        &self,
+        pref_h: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_width_from_height(pref_h).map(Ok)
+    }
+
+    fn preferred_height_from_width(
+        &self,
+        pref_w: f32,
+        _sys_interface: &mut T,
+    ) -> Option<Result<f32, String>> {
+        self.sizing.preferred_height_from_width(pref_w).map(Ok)
+    }
+
+    fn preferred_ratio_exceed_parent(&self) -> bool {
+        self.sizing.preferred_ratio_exceed_parent
+    }
+}
@@ -282,8 +282,8 @@ impl<'b, T: crate::core::System> Widget<T> for VerticalLayout<'b, T> {
         e_err_accumulation = e_err_accumulation.round();
         let mut e_err_accumulation = e_err_accumulation as usize;
 
-        crate::ui::util::shuffle::shuffle(&mut indices_not_at_min, 1234);
-        crate::ui::util::shuffle::shuffle(&mut indices_at_min, 5678);
+        crate::core::shuffle::shuffle(&mut indices_not_at_min, 1234);
+        crate::core::shuffle::shuffle(&mut indices_at_min, 5678);
         indices_not_at_min.extend(indices_at_min);
         let visit_indices = indices_not_at_min;
 